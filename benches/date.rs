@@ -25,10 +25,18 @@ fn range(criterion: &mut Criterion) {
     });
 }
 
+fn print(criterion: &mut Criterion) {
+    let ast = full_moon::parse(DATE_SOURCE).unwrap();
+
+    criterion.bench_function("print ast of date", move |b| {
+        b.iter(|| full_moon::print(black_box(&ast)))
+    });
+}
+
 criterion_group! {
     name = benches;
     config = Criterion::default().sample_size(20);
-    targets = tokenize, parse, range
+    targets = tokenize, parse, range, print
 }
 
 criterion_main!(benches);
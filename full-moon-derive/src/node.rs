@@ -30,6 +30,7 @@ impl DeriveGenerator for NodeGenerator {
         let pattern = quote! {{
             range => $range:expr,
             similar => $similar:expr,
+            children => $children:expr,
         }};
 
         quote! {
@@ -41,6 +42,10 @@ impl DeriveGenerator for NodeGenerator {
                 ("similar", #pattern) => {
                     $similar
                 };
+
+                ("children", #pattern) => {
+                    $children
+                };
             }
 
             impl #impl_generics crate::node::Node for #input_ident #ty_generics #where_clause {
@@ -55,6 +60,10 @@ impl DeriveGenerator for NodeGenerator {
                 fn similar(&self, other: &Self) -> bool {
                     #macro_name!("similar", { #tokens })
                 }
+
+                fn children(&self) -> Vec<&dyn crate::node::Node> {
+                    #macro_name!("children", { #tokens })
+                }
             }
 
             impl #impl_generics crate::private::Sealed for #input_ident #ty_generics #where_clause {}
@@ -66,10 +75,12 @@ impl StructGenerator for NodeGenerator {
     fn generate(ident: &syn::Ident, strukt: &syn::DataStruct) -> TokenStream {
         let range = StructRangeGenerator::generate(ident, strukt);
         let similar = StructSimilarGenerator::generate(ident, strukt);
+        let children = StructChildrenGenerator::generate(ident, strukt);
 
         quote! {
             range => { #range },
             similar => { #similar },
+            children => { #children },
         }
     }
 }
@@ -145,6 +156,7 @@ impl EnumGenerator for NodeGenerator {
     fn generate(ident: &syn::Ident, enumm: &syn::DataEnum) -> TokenStream {
         let range = EnumRangeGenerator::generate(ident, enumm);
         let similar = EnumSimilarGenerator::generate(ident, enumm);
+        let children = EnumChildrenGenerator::generate(ident, enumm);
 
         quote! {
             range => {
@@ -153,6 +165,7 @@ impl EnumGenerator for NodeGenerator {
             },
 
             similar => { #similar },
+            children => { #children },
         }
     }
 }
@@ -258,6 +271,26 @@ impl MatchEnumGenerator for EnumRangeGenerator {
     }
 }
 
+pub struct StructChildrenGenerator;
+
+impl StructGenerator for StructChildrenGenerator {
+    fn generate(_: &syn::Ident, strukt: &syn::DataStruct) -> TokenStream {
+        let fields = strukt
+            .fields
+            .iter()
+            .map(|field| field.ident.as_ref().unwrap())
+            .collect::<Vec<_>>();
+
+        quote! {
+            let mut children = Vec::new();
+            #(
+                self.#fields.collect_as_children(&mut children);
+            )*
+            children
+        }
+    }
+}
+
 pub struct EnumSimilarGenerator;
 
 impl MatchEnumGenerator for EnumSimilarGenerator {
@@ -328,3 +361,62 @@ impl MatchEnumGenerator for EnumSimilarGenerator {
         }
     }
 }
+
+pub struct EnumChildrenGenerator;
+
+impl MatchEnumGenerator for EnumChildrenGenerator {
+    fn case_named(
+        input: &syn::Ident,
+        variant: &syn::Ident,
+        named: &syn::FieldsNamed,
+    ) -> TokenStream {
+        let fields = named
+            .named
+            .iter()
+            .map(|field| field.ident.as_ref().unwrap())
+            .collect::<Vec<_>>();
+
+        quote! {
+            #input::#variant {
+                #(#fields,)*
+            } => {
+                let mut children = Vec::new();
+                #(
+                    #fields.collect_as_children(&mut children);
+                )*
+                children
+            }
+        }
+    }
+
+    fn case_unnamed(
+        input: &syn::Ident,
+        variant: &syn::Ident,
+        fields: &syn::FieldsUnnamed,
+    ) -> TokenStream {
+        let fields: Vec<_> = fields
+            .unnamed
+            .iter()
+            .enumerate()
+            .map(|(index, _)| format_ident!("__self_{}", index))
+            .collect();
+
+        quote! {
+            #input::#variant(
+                #(#fields,)*
+            ) => {
+                let mut children = Vec::new();
+                #(
+                    #fields.collect_as_children(&mut children);
+                )*
+                children
+            }
+        }
+    }
+
+    fn case_unit(input: &syn::Ident, variant: &syn::Ident) -> TokenStream {
+        quote! {
+            #input::#variant => { Vec::new() }
+        }
+    }
+}
@@ -63,7 +63,20 @@ pub fn parse(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let symbols = parse_macro_input!(input as SymbolsInput).symbols;
 
     let string = symbols.values().collect::<Vec<_>>();
-    let splits = string.chunks(ALT_LIMIT).map(|string| {
+    let ident: Vec<_> = symbols.keys().collect();
+
+    // Keywords are recognized by looking up identifiers, everything else with the combinator
+    let (keywords, operators): (Vec<_>, Vec<_>) = symbols
+        .iter()
+        .partition(|(_, string)| is_keyword(&string.value()));
+
+    let (keyword_ident, keyword_string): (Vec<_>, Vec<_>) = keywords.into_iter().unzip();
+    let operator_string = operators
+        .into_iter()
+        .map(|(_, string)| string)
+        .collect::<Vec<_>>();
+
+    let splits = operator_string.chunks(ALT_LIMIT).map(|string| {
         quote! {
             alt((#(
                 tag(#string),
@@ -71,8 +84,6 @@ pub fn parse(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
         }
     });
 
-    let ident: Vec<_> = symbols.keys().collect();
-
     let output = quote! {
         /// A literal symbol, used for both words important to syntax (like while) and operators (like +)
         #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -105,31 +116,45 @@ pub fn parse(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
             }
         }
 
-        fn parse_symbol(code: &str) -> IResult<&str, &str> {
-            let combinator = alt((
-                #(
-                    #splits,
-                )*
-            ));
+        impl Symbol {
+            /// Returns the symbol a keyword is tokenized as, such as `Symbol::Local` for `local`.
+            /// Returns `None` if the text is not a keyword, including symbols that aren't words, such as `+`.
+            /// This is the only place the tokenizer recognizes keywords.
+            ///
+            /// ```rust
+            /// # use full_moon::tokenizer::Symbol;
+            /// assert_eq!(Symbol::from_keyword("local"), Some(Symbol::Local));
+            /// assert_eq!(Symbol::from_keyword("locals"), None);
+            /// assert_eq!(Symbol::from_keyword("+"), None);
+            /// ```
+            pub fn from_keyword(keyword: &str) -> Option<Symbol> {
+                Some(match keyword {
+                    #(#keyword_string => Symbol::#keyword_ident,)*
+                    _ => return None,
+                })
+            }
+        }
 
+        fn parse_symbol(code: &str) -> IResult<&str, &str> {
             if code.chars().next().unwrap().is_ascii_alphanumeric() {
                 let identifier = match parse_identifier(code) {
                     Ok((_, identifier)) => identifier,
                     Err(_) => panic!("Parsing identifier failed"),
                 };
 
-                let expected_len = identifier.len();
-                let (input, find) = combinator(code)?;
-
-                if find.len() == expected_len {
-                    Ok((input, find))
+                if Symbol::from_keyword(identifier).is_some() {
+                    Ok((&code[identifier.len()..], identifier))
                 } else {
-                    use nom::error::ParseError;
-
                     // TODO: How does nom produce errors?
                     Err(nom::Err::Error(("symbol not found", nom::error::ErrorKind::Alt)))
                 }
             } else {
+                let combinator = alt((
+                    #(
+                        #splits,
+                    )*
+                ));
+
                 combinator(code)
             }
         }
@@ -137,3 +162,9 @@ pub fn parse(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 
     output.into()
 }
+
+fn is_keyword(string: &str) -> bool {
+    string
+        .chars()
+        .all(|character| character.is_ascii_alphabetic())
+}
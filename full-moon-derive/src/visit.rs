@@ -25,14 +25,16 @@ enum VisitHint {
     Skip,
 	SkipVisitSelf,
     VisitAs(String),
+    // A contained span, such as `(...)`, visited around the field inside of it
+    Contains(String),
 }
 
 impl Hint for VisitHint {
 	fn key_value(key: String, value: String) -> Option<Self> {
-		if key == "visit_as" {
-			Some(VisitHint::VisitAs(value))
-		} else {
-			None
+		match key.as_str() {
+			"visit_as" => Some(VisitHint::VisitAs(value)),
+			"contains" => Some(VisitHint::Contains(value)),
+			_ => None,
 		}
 	}
 
@@ -106,6 +108,14 @@ impl DeriveGenerator for VisitGenerator {
                         }
                     }
 
+                    macro_rules! visit_contained {
+                        ($span: expr, $contained: expr, $visitor: expr) => {
+                            $visitor.visit_contained_span(&$span);
+                            $contained.visit($visitor);
+                            $visitor.visit_contained_span_end(&$span);
+                        }
+                    }
+
                     #visit_self
                     #tokens
                     #visit_self_end
@@ -120,6 +130,14 @@ impl DeriveGenerator for VisitGenerator {
                         }
                     }
 
+                    macro_rules! visit_contained {
+                        ($span: expr, $contained: expr, $visitor: expr) => {
+                            $visitor.visit_contained_span(&mut $span);
+                            $contained.visit_mut($visitor);
+                            $visitor.visit_contained_span_end(&mut $span);
+                        }
+                    }
+
                     #visit_self
                     #tokens
                     #visit_self_end
@@ -129,17 +147,55 @@ impl DeriveGenerator for VisitGenerator {
     }
 }
 
-impl StructGenerator for VisitGenerator {
-    fn generate(_: &syn::Ident, strukt: &syn::DataStruct) -> TokenStream {
-        let fields = strukt
-            .fields
-            .iter()
-            .filter(|field| search_hint("visit", &field.attrs) != Some(VisitHint::Skip))
-			.map(|field| field.ident.as_ref());
+// Visits each field in turn, except for the one inside a contained span, which is visited in between its tokens.
+// `place` gives the expression for a field from its name, such as `self.field`, and `span_place` gives the
+// contained span itself rather than a reference to it.
+fn visit_fields(
+    fields: &[&syn::Field],
+    place: impl Fn(&syn::Ident) -> TokenStream,
+    span_place: impl Fn(&syn::Ident) -> TokenStream,
+) -> TokenStream {
+    let contained: Vec<_> = fields
+        .iter()
+        .filter_map(|field| match search_hint("visit", &field.attrs) {
+            Some(VisitHint::Contains(contained)) => Some(contained),
+            _ => None,
+        })
+        .collect();
+
+    let visits = fields.iter().filter_map(|field| {
+        let ident = field.ident.as_ref().unwrap();
+        if contained.contains(&ident.to_string()) {
+            return None;
+        }
 
-        quote! {
-            #(visit!(self.#fields, visitor);)*
+        match search_hint("visit", &field.attrs) {
+            Some(VisitHint::Skip) => None,
+            Some(VisitHint::Contains(contained)) => {
+                let span = span_place(ident);
+                let contained = place(&syn::Ident::new(&contained, ident.span()));
+                Some(quote! { visit_contained!(#span, #contained, visitor); })
+            }
+            _ => {
+                let field = place(ident);
+                Some(quote! { visit!(#field, visitor); })
+            }
         }
+    });
+
+    quote! {
+        #(#visits)*
+    }
+}
+
+impl StructGenerator for VisitGenerator {
+    fn generate(_: &syn::Ident, strukt: &syn::DataStruct) -> TokenStream {
+        let fields: Vec<_> = strukt.fields.iter().collect();
+        visit_fields(
+            &fields,
+            |ident| quote! { self.#ident },
+            |ident| quote! { self.#ident },
+        )
     }
 }
 
@@ -155,13 +211,18 @@ impl MatchEnumGenerator for VisitGenerator {
             .map(|field| field.ident.as_ref().unwrap())
             .collect();
 
+        // The fields are bound by reference, so contained spans are dereferenced to borrow them again
+        let visits = visit_fields(
+            &named.named.iter().collect::<Vec<_>>(),
+            |ident| quote! { #ident },
+            |ident| quote! { *#ident },
+        );
+
         quote! {
             #input::#variant {
                 #(#fields,)*
             } => {
-                #(
-                    visit!(#fields, visitor);
-                )*
+                #visits
             }
         }
     }
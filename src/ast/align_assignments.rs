@@ -0,0 +1,134 @@
+// Lines up the `=` of assignments on consecutive lines, refer to `Ast::align_assignments`
+use super::*;
+use crate::visitors::{VisitMut, VisitorMut};
+
+// The `=` of an assignment and the values after it, `None` for a local assignment without values
+fn equal_and_values<'a, 'b>(
+    stmt: &'b Stmt<'a>,
+) -> Option<(&'b TokenReference<'a>, &'b Punctuated<'a, Expression<'a>>)> {
+    match stmt {
+        Stmt::Assignment(assignment) => Some((&assignment.equal_token, &assignment.expr_list)),
+        Stmt::LocalAssignment(local_assignment) => Some((
+            local_assignment.equal_token.as_ref()?,
+            &local_assignment.expr_list,
+        )),
+        _ => None,
+    }
+}
+
+fn equal_token_mut<'a, 'b>(stmt: &'b mut Stmt<'a>) -> Option<&'b mut TokenReference<'a>> {
+    match stmt {
+        Stmt::Assignment(assignment) => Some(&mut assignment.equal_token),
+        Stmt::LocalAssignment(local_assignment) => local_assignment.equal_token.as_mut(),
+        _ => None,
+    }
+}
+
+fn trivia_text<'a, 'b>(trivia: impl Iterator<Item = &'b Token<'a>>) -> String
+where
+    'a: 'b,
+{
+    trivia.map(ToString::to_string).collect()
+}
+
+// The column the `=` of an assignment is at, counted from the start of the statement's line,
+// if the assignment is on a line of its own
+fn equal_column(stmt: &Stmt) -> Option<usize> {
+    let (equal_token, values) = equal_and_values(stmt)?;
+    let code = stmt.to_string();
+    if code.trim().contains('\n') {
+        return None;
+    }
+
+    // Everything from the `=` onwards, without the whitespace before it
+    let after_equal = format!(
+        "{}{}{}",
+        **equal_token,
+        trivia_text(equal_token.trailing_trivia()),
+        values
+    );
+    let before_equal = &code[..code.len() - after_equal.len()];
+    let line = match before_equal.rfind('\n') {
+        Some(new_line) => &before_equal[new_line + 1..],
+        None => before_equal,
+    };
+
+    Some(line.chars().count())
+}
+
+// Pads the names of each run of assignments on consecutive lines, so their `=` are at the same column
+fn align_block(block: &mut Block, top_level: bool) {
+    let mut runs = Vec::new();
+    let mut run: Vec<(usize, usize)> = Vec::new();
+    let mut previous_ends_line = top_level;
+
+    for (index, (stmt, semicolon)) in block.stmts.iter().enumerate() {
+        let code = format!("{}{}", stmt, display_option(semicolon));
+        let leading_trivia = &code[..code.len() - code.trim_start().len()];
+
+        // Statements only have trivia before them if they start a new line, as anything else on the line
+        // before them is trailing trivia of the token before. Without any, only the previous statement can tell.
+        let starts_line = !leading_trivia.is_empty() || previous_ends_line;
+        previous_ends_line = code.ends_with('\n');
+
+        let column = match equal_column(stmt) {
+            Some(column) if starts_line => column,
+            _ => {
+                runs.push(std::mem::take(&mut run));
+                continue;
+            }
+        };
+
+        // A blank line between two assignments ends the run. So does a comment, as an assignment after one
+        // isn't on a line of its own, which is checked for above.
+        if leading_trivia.contains('\n') {
+            runs.push(std::mem::take(&mut run));
+        }
+
+        run.push((index, column));
+    }
+
+    runs.push(run);
+
+    for run in runs {
+        let target = match run.iter().map(|&(_, column)| column).max() {
+            Some(target) if run.len() > 1 => target,
+            _ => continue,
+        };
+
+        for (index, column) in run {
+            if column == target {
+                continue;
+            }
+
+            let equal_token = equal_token_mut(&mut block.stmts[index].0)
+                .expect("aligned statement isn't an assignment");
+            let mut leading_trivia: Vec<_> = equal_token.leading_trivia().cloned().collect();
+            leading_trivia.push(Token::new(TokenType::Whitespace {
+                characters: " ".repeat(target - column).into(),
+            }));
+
+            *equal_token = TokenReference::new(
+                leading_trivia,
+                (**equal_token).clone(),
+                equal_token.trailing_trivia().cloned().collect(),
+            );
+        }
+    }
+}
+
+pub(crate) fn align_assignments(block: &mut Block) {
+    block.visit_mut(&mut AssignmentAligner { top_level: true });
+}
+
+struct AssignmentAligner {
+    // Only the first block visited is the top level one, which starts at the start of a line
+    top_level: bool,
+}
+
+impl<'a> VisitorMut<'a> for AssignmentAligner {
+    fn visit_block(&mut self, block: &mut Block<'a>) {
+        let top_level = std::mem::replace(&mut self.top_level, false);
+        align_block(block, top_level);
+    }
+}
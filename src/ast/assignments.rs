@@ -0,0 +1,132 @@
+//! The statements that write to a variable by name, such as `x = 1`, `local x = 1`, `for x = 1, 10 do`
+//! or `local function x() end`, found with [`Ast::assignments_to`](../struct.Ast.html#method.assignments_to).
+//! Each [`Write`](struct.Write.html) holds the name written to and the kind of statement it's in.
+use super::*;
+use crate::visitors::{Visit, Visitor};
+
+/// The kind of statement a [`Write`](struct.Write.html) happens in
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum WriteKind {
+    /// An assignment, such as `x = 1` or `x, y = 1, 2`
+    Assignment,
+    /// A local assignment, such as `local x = 1`
+    LocalAssignment,
+    /// The index variable of a numeric for loop, such as `for x = 1, 10 do end`
+    NumericFor,
+    /// One of the names of a generic for loop, such as `for x in pairs(t) do end`
+    GenericFor,
+    /// A local function declaration, such as `local function x() end`
+    LocalFunction,
+    /// A function declaration with a single name, such as `function x() end`.
+    /// Declaring a function on a table, such as `function t.x() end`, isn't a write to `x`.
+    FunctionDeclaration,
+    /// A compound assignment, such as `x += 1`.
+    /// Only available when the "roblox" feature flag is enabled.
+    #[cfg(feature = "roblox")]
+    CompoundAssignment,
+}
+
+/// A statement writing to a variable
+#[derive(Clone, Debug, PartialEq)]
+pub struct Write<'a> {
+    name: TokenReference<'a>,
+    kind: WriteKind,
+}
+
+impl<'a> Write<'a> {
+    /// The name being written to, such as the `x` in `x, y = 1, 2`.
+    /// Use its positions to find where the write happens.
+    pub fn name(&self) -> &TokenReference<'a> {
+        &self.name
+    }
+
+    /// The kind of statement doing the write
+    pub fn kind(&self) -> WriteKind {
+        self.kind
+    }
+}
+
+pub(crate) struct WriteVisitor<'a, 'b> {
+    name: &'b str,
+    writes: Vec<Write<'a>>,
+}
+
+impl<'a, 'b> WriteVisitor<'a, 'b> {
+    pub(crate) fn new(name: &'b str) -> Self {
+        Self {
+            name,
+            writes: Vec::new(),
+        }
+    }
+
+    pub(crate) fn find(mut self, block: &Block<'a>) -> Vec<Write<'a>> {
+        block.visit(&mut self);
+        self.writes
+    }
+
+    fn check_names<'c>(
+        &mut self,
+        kind: WriteKind,
+        names: impl Iterator<Item = &'c TokenReference<'a>>,
+    ) where
+        'a: 'c,
+    {
+        for name in names {
+            if name.to_string() == self.name {
+                self.writes.push(Write {
+                    name: name.clone(),
+                    kind,
+                });
+            }
+        }
+    }
+}
+
+impl<'a, 'b> Visitor<'a> for WriteVisitor<'a, 'b> {
+    fn visit_stmt(&mut self, stmt: &Stmt<'a>) {
+        match stmt {
+            Stmt::Assignment(assignment) => self.check_names(
+                WriteKind::Assignment,
+                assignment.var_list().iter().filter_map(|var| match var {
+                    Var::Name(name) => Some(name),
+                    Var::Expression(_) => None,
+                }),
+            ),
+
+            Stmt::LocalAssignment(local_assignment) => self.check_names(
+                WriteKind::LocalAssignment,
+                local_assignment.name_list().iter(),
+            ),
+
+            Stmt::NumericFor(numeric_for) => self.check_names(
+                WriteKind::NumericFor,
+                std::iter::once(numeric_for.index_variable()),
+            ),
+
+            Stmt::GenericFor(generic_for) => {
+                self.check_names(WriteKind::GenericFor, generic_for.names().iter())
+            }
+
+            Stmt::LocalFunction(local_function) => self.check_names(
+                WriteKind::LocalFunction,
+                std::iter::once(local_function.name()),
+            ),
+
+            Stmt::FunctionDeclaration(function_declaration) => {
+                let name = function_declaration.name();
+                if name.names().len() == 1 && name.method_name().is_none() {
+                    self.check_names(WriteKind::FunctionDeclaration, name.names().iter())
+                }
+            }
+
+            #[cfg(feature = "roblox")]
+            Stmt::CompoundAssignment(compound_assignment) => {
+                if let Var::Name(name) = compound_assignment.lhs() {
+                    self.check_names(WriteKind::CompoundAssignment, std::iter::once(name))
+                }
+            }
+
+            _ => {}
+        }
+    }
+}
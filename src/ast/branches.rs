@@ -0,0 +1,93 @@
+//! The conditions that decide which code runs next, such as the `x` in `if x then end` or `while x do end`,
+//! along with what kind of statement each one belongs to.
+//! [`Ast::branch_conditions`](../struct.Ast.html#method.branch_conditions) lists them for coverage and complexity tools.
+use super::*;
+use crate::{
+    node::Node,
+    visitors::{Visit, Visitor},
+};
+
+/// The kind of code a [`BranchCondition`](struct.BranchCondition.html) is from
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BranchKind {
+    /// The condition of an if statement, such as `x` in `if x then end`
+    If,
+    /// The condition of an elseif, such as `y` in `if x then elseif y then end`
+    ElseIf,
+    /// The condition of a while loop, such as `x` in `while x do end`
+    While,
+    /// The `until` condition of a repeat loop, such as `x` in `repeat until x`
+    Repeat,
+}
+
+/// A condition deciding which code runs next
+#[derive(Clone, Debug, PartialEq)]
+pub struct BranchCondition<'a> {
+    kind: BranchKind,
+    condition: Expression<'a>,
+}
+
+impl<'a> BranchCondition<'a> {
+    /// The kind of code the condition is from
+    pub fn kind(&self) -> BranchKind {
+        self.kind
+    }
+
+    /// The condition itself
+    pub fn condition(&self) -> &Expression<'a> {
+        &self.condition
+    }
+
+    /// The position the condition starts at
+    pub fn start_position(&self) -> Option<Position> {
+        self.condition.start_position()
+    }
+
+    /// The position the condition ends at
+    pub fn end_position(&self) -> Option<Position> {
+        self.condition.end_position()
+    }
+}
+
+pub(crate) struct BranchConditionVisitor<'a> {
+    conditions: Vec<BranchCondition<'a>>,
+}
+
+impl<'a> BranchConditionVisitor<'a> {
+    pub(crate) fn new() -> Self {
+        Self {
+            conditions: Vec::new(),
+        }
+    }
+
+    pub(crate) fn find(mut self, block: &Block<'a>) -> Vec<BranchCondition<'a>> {
+        block.visit(&mut self);
+        self.conditions
+    }
+
+    fn push(&mut self, kind: BranchKind, condition: &Expression<'a>) {
+        self.conditions.push(BranchCondition {
+            kind,
+            condition: condition.clone(),
+        });
+    }
+}
+
+impl<'a> Visitor<'a> for BranchConditionVisitor<'a> {
+    fn visit_if(&mut self, r#if: &If<'a>) {
+        self.push(BranchKind::If, r#if.condition());
+    }
+
+    fn visit_else_if(&mut self, else_if: &ElseIf<'a>) {
+        self.push(BranchKind::ElseIf, else_if.condition());
+    }
+
+    fn visit_while(&mut self, r#while: &While<'a>) {
+        self.push(BranchKind::While, r#while.condition());
+    }
+
+    // The `until` condition comes after the body, so it's only added once the body's conditions are
+    fn visit_repeat_end(&mut self, repeat: &Repeat<'a>) {
+        self.push(BranchKind::Repeat, repeat.until());
+    }
+}
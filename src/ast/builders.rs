@@ -0,0 +1,124 @@
+//! Builders to create nodes in code, such as if statements, rather than parsing them
+use super::*;
+use crate::tokenizer::Symbol;
+
+fn symbol<'a>(
+    leading_trivia: Vec<Token<'a>>,
+    symbol: Symbol,
+    trailing_trivia: Vec<Token<'a>>,
+) -> TokenReference<'a> {
+    TokenReference::new(
+        leading_trivia,
+        Token::new(TokenType::Symbol { symbol }),
+        trailing_trivia,
+    )
+}
+
+// A new line before the keyword after a block, unless the block already ends with one
+fn after_block<'a>(block: &Block<'a>) -> Vec<Token<'a>> {
+    let code = block.to_string();
+    if code.is_empty() || code.ends_with('\n') {
+        Vec::new()
+    } else {
+        vec![whitespace("\n")]
+    }
+}
+
+// The `then` after a condition, ending the line so the block starts on its own
+fn then_token<'a>() -> TokenReference<'a> {
+    symbol(vec![whitespace(" ")], Symbol::Then, vec![whitespace("\n")])
+}
+
+/// Builds an [`If`](../struct.If.html) out of its conditions and blocks,
+/// creating the keywords and the whitespace between them.
+/// Each keyword starts a new line, and the code in the blocks is kept as it is.
+///
+/// ```rust
+/// # use full_moon::ast::{builders::IfBuilder, LastStmt};
+/// # fn main() -> Result<(), Box<std::error::Error>> {
+/// let condition = full_moon::parse("return x")?;
+/// let condition = match condition.nodes().last_stmts() {
+///     Some(LastStmt::Return(r#return)) => r#return.returns().iter().next().unwrap().clone(),
+///     _ => unreachable!(),
+/// };
+///
+/// let block = full_moon::parse("print(x)\n")?;
+/// let r#if = IfBuilder::new(condition, block.nodes().clone()).build();
+/// assert_eq!(r#if.to_string(), "if x then\nprint(x)\nend");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct IfBuilder<'a> {
+    condition: Expression<'a>,
+    block: Block<'a>,
+    else_ifs: Vec<(Expression<'a>, Block<'a>)>,
+    else_block: Option<Block<'a>>,
+}
+
+impl<'a> IfBuilder<'a> {
+    /// Starts an if statement, with the code in `block` being run if `condition` is true
+    pub fn new(condition: Expression<'a>, block: Block<'a>) -> Self {
+        Self {
+            condition,
+            block,
+            else_ifs: Vec::new(),
+            else_block: None,
+        }
+    }
+
+    /// Adds an `elseif` branch after the ones before it
+    pub fn else_if(mut self, condition: Expression<'a>, block: Block<'a>) -> Self {
+        self.else_ifs.push((condition, block));
+        self
+    }
+
+    /// Sets the `else` branch, replacing any set before
+    pub fn else_block(mut self, block: Block<'a>) -> Self {
+        self.else_block = Some(block);
+        self
+    }
+
+    /// Creates the [`If`](../struct.If.html)
+    pub fn build(self) -> If<'a> {
+        let mut previous_block = after_block(&self.block);
+
+        let else_if = if self.else_ifs.is_empty() {
+            None
+        } else {
+            let mut else_ifs = Vec::with_capacity(self.else_ifs.len());
+
+            for (condition, block) in self.else_ifs {
+                let leading_trivia = std::mem::replace(&mut previous_block, after_block(&block));
+                else_ifs.push(ElseIf {
+                    else_if_token: symbol(leading_trivia, Symbol::ElseIf, vec![whitespace(" ")]),
+                    condition,
+                    then_token: then_token(),
+                    block,
+                });
+            }
+
+            Some(else_ifs)
+        };
+
+        let else_token = match &self.else_block {
+            Some(block) => {
+                let leading_trivia = std::mem::replace(&mut previous_block, after_block(block));
+                Some(symbol(leading_trivia, Symbol::Else, vec![whitespace("\n")]))
+            }
+
+            None => None,
+        };
+
+        If {
+            if_token: symbol(Vec::new(), Symbol::If, vec![whitespace(" ")]),
+            condition: self.condition,
+            then_token: then_token(),
+            block: self.block,
+            else_if,
+            else_token,
+            r#else: self.else_block,
+            end_token: symbol(previous_block, Symbol::End, Vec::new()),
+        }
+    }
+}
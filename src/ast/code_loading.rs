@@ -0,0 +1,101 @@
+//! Calls that load and run code while the program is running, such as `load(code)` or `require(path)`,
+//! for auditing code that should be sandboxed. Which functions count as loaders, and how severe calls to them are,
+//! can be changed by passing [`Loader`](struct.Loader.html)s to
+//! [`Ast::code_loads_with`](../struct.Ast.html#method.code_loads_with).
+use super::{requires::RequireVisitor, *};
+
+/// How much a call that loads code should be looked into
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Severity {
+    /// The call is unlikely to load code that isn't meant to be run
+    Low,
+    /// The call loads code from somewhere that could be changed, such as a file
+    Medium,
+    /// The call runs whatever code it's given, such as `load` or `loadstring`
+    High,
+}
+
+/// A function that loads code, and how a call to it is flagged
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Loader<'b> {
+    /// The name of the function, such as `loadstring`
+    pub name: &'b str,
+    /// The severity calls to it are flagged with
+    pub severity: Severity,
+    /// Whether calls are only flagged when they don't pass a single string literal,
+    /// such as `require(path)` but not `require("module")`
+    pub dynamic_only: bool,
+}
+
+/// The loaders [`Ast::code_loads`](../struct.Ast.html#method.code_loads) looks for:
+/// `load` and `loadstring` with a high severity, `dofile` with a medium severity,
+/// and `require` with a medium severity when it's [dynamic](struct.CodeLoad.html#method.is_dynamic)
+pub const DEFAULT_LOADERS: &[Loader<'static>] = &[
+    Loader {
+        name: "load",
+        severity: Severity::High,
+        dynamic_only: false,
+    },
+    Loader {
+        name: "loadstring",
+        severity: Severity::High,
+        dynamic_only: false,
+    },
+    Loader {
+        name: "dofile",
+        severity: Severity::Medium,
+        dynamic_only: false,
+    },
+    Loader {
+        name: "require",
+        severity: Severity::Medium,
+        dynamic_only: true,
+    },
+];
+
+/// A call that loads code, such as `load(code)`
+#[derive(Clone, Debug, PartialEq)]
+pub struct CodeLoad<'a> {
+    loader: TokenReference<'a>,
+    severity: Severity,
+    dynamic: bool,
+}
+
+impl<'a> CodeLoad<'a> {
+    /// The name of the function being called, the `load` part of `load(code)`, which is where the call starts
+    pub fn loader(&self) -> &TokenReference<'a> {
+        &self.loader
+    }
+
+    /// The severity of the call, from the [`Loader`](struct.Loader.html) it was found with
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Whether the call doesn't pass a single string literal, such as `load(code)` but not `load("return 1")`
+    pub fn is_dynamic(&self) -> bool {
+        self.dynamic
+    }
+}
+
+pub(crate) fn find_code_loads<'a>(block: &Block<'a>, loaders: &[Loader]) -> Vec<CodeLoad<'a>> {
+    let names: Vec<_> = loaders.iter().map(|loader| loader.name).collect();
+
+    RequireVisitor::new(&names)
+        .find(block)
+        .into_iter()
+        .filter_map(|call| {
+            let name = call.loader().to_string();
+            let loader = loaders.iter().find(|loader| loader.name == name)?;
+            if loader.dynamic_only && !call.is_dynamic() {
+                return None;
+            }
+
+            Some(CodeLoad {
+                loader: call.loader().clone(),
+                severity: loader.severity,
+                dynamic: call.is_dynamic(),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,139 @@
+//! The [`CommentStyle`](enum.CommentStyle.html)s comments can be written in, `-- line comments` or
+//! `--[[ block comments ]]`, which [`Ast::convert_comments`](../struct.Ast.html#method.convert_comments)
+//! converts every comment it can to.
+use super::*;
+use crate::tokenizer::long_bracket_level;
+
+/// The style to write comments in
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CommentStyle {
+    /// Single line comments, such as `-- comment`
+    Line,
+    /// Multi line comments, such as `--[[ comment ]]`
+    Block,
+}
+
+fn single_line_comment(token: &Token) -> Option<String> {
+    match &*token.token_type() {
+        TokenType::SingleLineComment { comment } => Some(comment.to_string()),
+        _ => None,
+    }
+}
+
+// Whether only whitespace is in between the start of the line and the token
+fn starts_line(tokens: &[&Token], index: usize) -> bool {
+    for token in tokens[..index].iter().rev() {
+        match whitespace_characters(token) {
+            Some(characters) if characters.contains('\n') => return true,
+            Some(_) => {}
+            None => return false,
+        }
+    }
+
+    true
+}
+
+// Whether only whitespace is in between the token and the end of the line
+fn ends_line(tokens: &[&Token], index: usize) -> bool {
+    for token in &tokens[index + 1..] {
+        match whitespace_characters(token) {
+            Some(characters) if characters.contains('\n') => return true,
+            Some(_) => {}
+            None => return token.token_kind() == TokenKind::Eof,
+        }
+    }
+
+    true
+}
+
+// Joins every run of line comments that are each on their own line into one block comment,
+// keeping the new lines and indentation in between them
+fn to_block_comments(tokens: &[&Token]) {
+    let mut index = 0;
+
+    while index < tokens.len() {
+        let mut contents = match single_line_comment(tokens[index]) {
+            Some(comment) => comment,
+            None => {
+                index += 1;
+                continue;
+            }
+        };
+
+        let mut end = index;
+        if starts_line(tokens, index) {
+            loop {
+                let mut next = end + 1;
+                let mut separator = String::new();
+
+                while let Some(characters) = tokens
+                    .get(next)
+                    .and_then(|token| whitespace_characters(token))
+                {
+                    separator.push_str(&characters);
+                    next += 1;
+                }
+
+                // A blank line in between the comments ends the run
+                match tokens
+                    .get(next)
+                    .and_then(|token| single_line_comment(token))
+                {
+                    Some(comment) if separator.matches('\n').count() == 1 => {
+                        contents.push_str(&separator);
+                        contents.push_str(&comment);
+                        end = next;
+                    }
+
+                    _ => break,
+                }
+            }
+        }
+
+        *tokens[index].token_type.borrow_mut() = TokenType::MultiLineComment {
+            blocks: long_bracket_level(&contents),
+            comment: Cow::Owned(contents),
+        };
+
+        for token in &tokens[index + 1..=end] {
+            *token.token_type.borrow_mut() = TokenType::Whitespace {
+                characters: Cow::Borrowed(""),
+            };
+        }
+
+        index = end + 1;
+    }
+}
+
+// Whether the comment would start a block comment if it was after `--`, such as `[[`
+fn opens_block(comment: &str) -> bool {
+    comment.starts_with('[') && comment[1..].trim_start_matches('=').starts_with('[')
+}
+
+// Turns every block comment that fits on one line and has nothing after it on that line into a line comment
+fn to_line_comments(tokens: &[&Token]) {
+    for (index, token) in tokens.iter().enumerate() {
+        let comment = match &*token.token_type() {
+            TokenType::MultiLineComment { comment, .. }
+                if !comment.contains('\n') && !opens_block(comment) =>
+            {
+                comment.clone()
+            }
+
+            _ => continue,
+        };
+
+        if ends_line(tokens, index) {
+            *token.token_type.borrow_mut() = TokenType::SingleLineComment { comment };
+        }
+    }
+}
+
+pub(crate) fn convert_comments(tokens: &Arena<Token>, style: CommentStyle) {
+    let tokens: Vec<_> = tokens.iter().map(|(_, token)| token).collect();
+
+    match style {
+        CommentStyle::Line => to_line_comments(&tokens),
+        CommentStyle::Block => to_block_comments(&tokens),
+    }
+}
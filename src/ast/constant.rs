@@ -0,0 +1,527 @@
+//! The [`Constant`](enum.Constant.html) values of expressions made only of constants, such as `1 + 2` or
+//! `"a" .. "b"`, worked out the way Lua would with
+//! [`Expression::eval_constant`](../enum.Expression.html#method.eval_constant).
+use super::*;
+use std::cmp::Ordering;
+
+/// The value of a constant expression
+#[derive(Clone, Debug, PartialEq)]
+pub enum Constant<'a> {
+    /// `nil`
+    Nil,
+    /// `true` or `false`
+    Boolean(bool),
+    /// A number, such as `3.5`. Lua 5.1 represents every number as a float.
+    Number(f64),
+    /// A string, with its escape sequences already applied
+    String(Cow<'a, str>),
+}
+
+impl<'a> Constant<'a> {
+    /// Whether the value is considered true by conditions, which is everything except `nil` and `false`
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Constant::Nil => false,
+            Constant::Boolean(boolean) => *boolean,
+            _ => true,
+        }
+    }
+
+    // Numbers, and strings that can be converted to numbers, as arithmetic does
+    fn to_number(&self) -> Option<f64> {
+        match self {
+            Constant::Number(number) => Some(*number),
+            Constant::String(string) => parse_number(string.trim()),
+            _ => None,
+        }
+    }
+
+    // Numbers with an exact integer value, and strings that can be converted to them, as bitwise operators need
+    #[cfg(feature = "lua53")]
+    fn to_integer(&self) -> Option<i64> {
+        let number = self.to_number()?;
+        if number.fract() == 0.0 && number >= i64::MIN as f64 && number < -(i64::MIN as f64) {
+            Some(number as i64)
+        } else {
+            None
+        }
+    }
+
+    // Strings, and numbers converted to strings, as concatenation does
+    fn to_lua_string(&self) -> Option<Cow<'a, str>> {
+        match self {
+            Constant::Number(number) => Some(Cow::Owned(format_number(*number))),
+            Constant::String(string) => Some(string.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> fmt::Display for Constant<'a> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Constant::Nil => formatter.write_str("nil"),
+            Constant::Boolean(boolean) => boolean.fmt(formatter),
+            Constant::Number(number) => formatter.write_str(&format_number(*number)),
+            Constant::String(string) => string.fmt(formatter),
+        }
+    }
+}
+
+// Something operators are used on
+pub(crate) enum Operand<'a, 'b> {
+    Value(&'b Value<'a>),
+    Parentheses(&'b Expression<'a>),
+}
+
+// The expressions of the Ast are stored without operator precedence, `1 - 2 - 3` is stored as `1 - (2 - 3)`.
+// An expression is flattened into its operands and operators, which are then rebuilt with precedence applied.
+enum Item<'a, 'b> {
+    Operand(Operand<'a, 'b>),
+    UnOp(&'b UnOp<'a>),
+    BinOp(&'b BinOp<'a>),
+}
+
+pub(crate) enum Operation<'a, 'b> {
+    Operand(Operand<'a, 'b>),
+    Unary(&'b UnOp<'a>, Box<Operation<'a, 'b>>),
+    Binary(
+        Box<Operation<'a, 'b>>,
+        &'b BinOp<'a>,
+        Box<Operation<'a, 'b>>,
+    ),
+}
+
+// Operators with a higher precedence are done first
+const UNARY_PRECEDENCE: u8 = 11;
+
+// The precedence of a binary operator, and whether it's right associative
+fn precedence(bin_op: &BinOp) -> (u8, bool) {
+    match bin_op {
+        BinOp::Or(_) => (1, false),
+        BinOp::And(_) => (2, false),
+        BinOp::GreaterThan(_)
+        | BinOp::GreaterThanEqual(_)
+        | BinOp::LessThan(_)
+        | BinOp::LessThanEqual(_)
+        | BinOp::TildeEqual(_)
+        | BinOp::TwoEqual(_) => (3, false),
+        #[cfg(feature = "lua53")]
+        BinOp::Pipe(_) => (4, false),
+        #[cfg(feature = "lua53")]
+        BinOp::Tilde(_) => (5, false),
+        #[cfg(feature = "lua53")]
+        BinOp::Ampersand(_) => (6, false),
+        #[cfg(feature = "lua53")]
+        BinOp::TwoLessThan(_) | BinOp::TwoGreaterThan(_) => (7, false),
+        BinOp::TwoDots(_) => (8, true),
+        BinOp::Plus(_) | BinOp::Minus(_) => (9, false),
+        BinOp::Star(_) | BinOp::Slash(_) | BinOp::Percent(_) => (10, false),
+        #[cfg(feature = "lua53")]
+        BinOp::DoubleSlash(_) => (10, false),
+        BinOp::Caret(_) => (12, true),
+    }
+}
+
+impl<'a, 'b> Operation<'a, 'b> {
+    pub(crate) fn new(expression: &'b Expression<'a>) -> Self {
+        let mut items = Vec::new();
+        let mut expression = expression;
+
+        loop {
+            match expression {
+                Expression::Parentheses { expression, .. } => {
+                    items.push(Item::Operand(Operand::Parentheses(expression)));
+                    break;
+                }
+
+                Expression::UnaryOperator {
+                    unop,
+                    expression: operand,
+                } => {
+                    items.push(Item::UnOp(unop));
+                    expression = operand;
+                }
+
+                Expression::Value { value, binop, .. } => {
+                    items.push(Item::Operand(Operand::Value(value)));
+
+                    match binop {
+                        Some(binop) => {
+                            items.push(Item::BinOp(binop.bin_op()));
+                            expression = binop.rhs();
+                        }
+
+                        None => break,
+                    }
+                }
+            }
+        }
+
+        let mut items = items.into_iter().peekable();
+        Self::parse_binary(&mut items, 0)
+    }
+
+    fn parse_binary(
+        items: &mut std::iter::Peekable<std::vec::IntoIter<Item<'a, 'b>>>,
+        min_precedence: u8,
+    ) -> Self {
+        let mut lhs = Self::parse_unary(items);
+
+        while let Some(Item::BinOp(bin_op)) = items.peek() {
+            let bin_op = *bin_op;
+            let (precedence, right_associative) = precedence(bin_op);
+            if precedence < min_precedence {
+                break;
+            }
+
+            items.next();
+            let rhs = Self::parse_binary(
+                items,
+                if right_associative {
+                    precedence
+                } else {
+                    precedence + 1
+                },
+            );
+
+            lhs = Operation::Binary(Box::new(lhs), bin_op, Box::new(rhs));
+        }
+
+        lhs
+    }
+
+    fn parse_unary(items: &mut std::iter::Peekable<std::vec::IntoIter<Item<'a, 'b>>>) -> Self {
+        match items.next() {
+            Some(Item::UnOp(unop)) => Operation::Unary(
+                unop,
+                // `-x ^ 2` is `-(x ^ 2)`, as `^` has a higher precedence than unary operators
+                Box::new(Self::parse_binary(items, UNARY_PRECEDENCE + 1)),
+            ),
+
+            Some(Item::Operand(operand)) => Operation::Operand(operand),
+
+            _ => unreachable!("expression doesn't alternate between operands and operators"),
+        }
+    }
+
+    pub(crate) fn eval(&self) -> Option<Constant<'a>> {
+        match self {
+            Operation::Operand(Operand::Value(value)) => eval_value(value),
+            Operation::Operand(Operand::Parentheses(expression)) => expression.eval_constant(),
+
+            Operation::Unary(unop, operand) => {
+                let operand = operand.eval()?;
+
+                match unop {
+                    UnOp::Not(_) => Some(Constant::Boolean(!operand.is_truthy())),
+                    UnOp::Minus(_) => Some(Constant::Number(-operand.to_number()?)),
+                    UnOp::Hash(_) => match operand {
+                        Constant::String(string) => Some(Constant::Number(string.len() as f64)),
+                        _ => None,
+                    },
+                    #[cfg(feature = "lua53")]
+                    UnOp::Tilde(_) => Some(Constant::Number(!operand.to_integer()? as f64)),
+                }
+            }
+
+            // `and` and `or` give back one of their operands rather than a boolean,
+            // and only evaluate the right hand side if they need to
+            Operation::Binary(lhs, BinOp::And(_), rhs) => {
+                let lhs = lhs.eval()?;
+                if lhs.is_truthy() {
+                    rhs.eval()
+                } else {
+                    Some(lhs)
+                }
+            }
+
+            Operation::Binary(lhs, BinOp::Or(_), rhs) => {
+                let lhs = lhs.eval()?;
+                if lhs.is_truthy() {
+                    Some(lhs)
+                } else {
+                    rhs.eval()
+                }
+            }
+
+            Operation::Binary(lhs, bin_op, rhs) => eval_bin_op(bin_op, lhs.eval()?, rhs.eval()?),
+        }
+    }
+}
+
+fn eval_value<'a>(value: &Value<'a>) -> Option<Constant<'a>> {
+    match value {
+        Value::Number(token) => match &*token.token_type() {
+            TokenType::Number { text } => parse_number(text).map(Constant::Number),
+            _ => unreachable!("number value isn't a number token"),
+        },
+
+        Value::String(token) => match &*token.token_type() {
+            TokenType::StringLiteral {
+                literal,
+                multi_line: Some(_),
+                ..
+            } => {
+                // A new line right after the opening brackets isn't part of the string
+                let string = literal
+                    .strip_prefix("\r\n")
+                    .or_else(|| literal.strip_prefix('\n'))
+                    .unwrap_or(literal);
+                Some(Constant::String(Cow::Owned(string.to_owned())))
+            }
+
+            TokenType::StringLiteral { literal, .. } => {
+                if literal.contains('\\') {
+                    unescape(literal).map(|string| Constant::String(Cow::Owned(string)))
+                } else {
+                    Some(Constant::String(literal.clone()))
+                }
+            }
+
+            _ => unreachable!("string value isn't a string token"),
+        },
+
+        Value::Symbol(token) => match &*token.token_type() {
+            TokenType::Symbol {
+                symbol: Symbol::Nil,
+            } => Some(Constant::Nil),
+            TokenType::Symbol {
+                symbol: Symbol::True,
+            } => Some(Constant::Boolean(true)),
+            TokenType::Symbol {
+                symbol: Symbol::False,
+            } => Some(Constant::Boolean(false)),
+            _ => None,
+        },
+
+        Value::ParseExpression(expression) => expression.eval_constant(),
+
+        _ => None,
+    }
+}
+
+fn eval_bin_op<'a>(
+    bin_op: &BinOp<'a>,
+    lhs: Constant<'a>,
+    rhs: Constant<'a>,
+) -> Option<Constant<'a>> {
+    let arithmetic = |operation: fn(f64, f64) -> f64| {
+        Some(Constant::Number(operation(
+            lhs.to_number()?,
+            rhs.to_number()?,
+        )))
+    };
+
+    #[cfg(feature = "lua53")]
+    let bitwise = |operation: fn(i64, i64) -> i64| {
+        Some(Constant::Number(
+            operation(lhs.to_integer()?, rhs.to_integer()?) as f64,
+        ))
+    };
+
+    match bin_op {
+        BinOp::Plus(_) => arithmetic(|lhs, rhs| lhs + rhs),
+        BinOp::Minus(_) => arithmetic(|lhs, rhs| lhs - rhs),
+        BinOp::Star(_) => arithmetic(|lhs, rhs| lhs * rhs),
+        BinOp::Slash(_) => arithmetic(|lhs, rhs| lhs / rhs),
+        BinOp::Percent(_) => arithmetic(|lhs, rhs| lhs - (lhs / rhs).floor() * rhs),
+        BinOp::Caret(_) => arithmetic(f64::powf),
+        #[cfg(feature = "lua53")]
+        BinOp::DoubleSlash(_) => arithmetic(|lhs, rhs| (lhs / rhs).floor()),
+
+        #[cfg(feature = "lua53")]
+        BinOp::Ampersand(_) => bitwise(|lhs, rhs| lhs & rhs),
+        #[cfg(feature = "lua53")]
+        BinOp::Pipe(_) => bitwise(|lhs, rhs| lhs | rhs),
+        #[cfg(feature = "lua53")]
+        BinOp::Tilde(_) => bitwise(|lhs, rhs| lhs ^ rhs),
+        #[cfg(feature = "lua53")]
+        BinOp::TwoLessThan(_) => bitwise(shift_left),
+        #[cfg(feature = "lua53")]
+        BinOp::TwoGreaterThan(_) => bitwise(|lhs, rhs| shift_left(lhs, rhs.wrapping_neg())),
+
+        BinOp::TwoDots(_) => {
+            let mut string = lhs.to_lua_string()?.into_owned();
+            string.push_str(&rhs.to_lua_string()?);
+            Some(Constant::String(Cow::Owned(string)))
+        }
+
+        BinOp::TwoEqual(_) => Some(Constant::Boolean(lhs == rhs)),
+        BinOp::TildeEqual(_) => Some(Constant::Boolean(lhs != rhs)),
+
+        BinOp::LessThan(_) => {
+            compare(&lhs, &rhs).map(|ordering| Constant::Boolean(ordering == Ordering::Less))
+        }
+        BinOp::LessThanEqual(_) => {
+            compare(&lhs, &rhs).map(|ordering| Constant::Boolean(ordering != Ordering::Greater))
+        }
+        BinOp::GreaterThan(_) => {
+            compare(&lhs, &rhs).map(|ordering| Constant::Boolean(ordering == Ordering::Greater))
+        }
+        BinOp::GreaterThanEqual(_) => {
+            compare(&lhs, &rhs).map(|ordering| Constant::Boolean(ordering != Ordering::Less))
+        }
+
+        BinOp::And(_) | BinOp::Or(_) => unreachable!("and/or are evaluated lazily"),
+    }
+}
+
+// Shifts are logical, filling in zeroes, and shifting by a negative amount shifts the other way
+#[cfg(feature = "lua53")]
+fn shift_left(number: i64, shift: i64) -> i64 {
+    if shift <= -64 || shift >= 64 {
+        0
+    } else if shift >= 0 {
+        ((number as u64) << shift) as i64
+    } else {
+        ((number as u64) >> -shift) as i64
+    }
+}
+
+// Only numbers with numbers, and strings with strings, can be compared
+fn compare(lhs: &Constant, rhs: &Constant) -> Option<Ordering> {
+    match (lhs, rhs) {
+        (Constant::Number(lhs), Constant::Number(rhs)) => lhs.partial_cmp(rhs),
+        (Constant::String(lhs), Constant::String(rhs)) => Some(lhs.cmp(rhs)),
+        _ => None,
+    }
+}
+
+pub(crate) fn parse_number(text: &str) -> Option<f64> {
+    if text.starts_with("0x") || text.starts_with("0X") {
+        return parse_hex_number(&text[2..]);
+    }
+
+    // Rust also accepts words such as `inf`, which Lua doesn't
+    if text.is_empty()
+        || !text
+            .chars()
+            .all(|character| "0123456789.eE+-".contains(character))
+    {
+        return None;
+    }
+
+    text.parse().ok()
+}
+
+// Hexadecimal numbers can have a fraction and a binary exponent since Lua 5.2, such as `0x1.8p1`
+fn parse_hex_number(digits: &str) -> Option<f64> {
+    let (mantissa, exponent) = match digits.find(&['p', 'P'][..]) {
+        Some(index) => (&digits[..index], digits[index + 1..].parse().ok()?),
+        None => (digits, 0),
+    };
+
+    let (whole, fraction) = match mantissa.find('.') {
+        Some(index) => (&mantissa[..index], &mantissa[index + 1..]),
+        None => (mantissa, ""),
+    };
+
+    if whole.is_empty() && fraction.is_empty() {
+        return None;
+    }
+
+    let mut number = 0.0;
+    for digit in whole.chars() {
+        number = number * 16.0 + f64::from(digit.to_digit(16)?);
+    }
+
+    let mut scale = 1.0 / 16.0;
+    for digit in fraction.chars() {
+        number += f64::from(digit.to_digit(16)?) * scale;
+        scale /= 16.0;
+    }
+
+    Some(number * 2f64.powi(exponent))
+}
+
+// Formats a number the same way Lua does, with up to 14 significant digits
+fn format_number(number: f64) -> String {
+    if number.is_nan() {
+        return "nan".to_owned();
+    } else if number.is_infinite() {
+        return if number > 0.0 { "inf" } else { "-inf" }.to_owned();
+    } else if number == number.trunc() && number.abs() < 1e15 {
+        return format!("{}", number as i64);
+    }
+
+    let scientific = format!("{:.13e}", number);
+    let (mantissa, exponent) = scientific.split_at(scientific.find('e').unwrap());
+    let exponent: i32 = exponent[1..].parse().unwrap();
+
+    let trim = |string: String| {
+        if string.contains('.') {
+            string
+                .trim_end_matches('0')
+                .trim_end_matches('.')
+                .to_owned()
+        } else {
+            string
+        }
+    };
+
+    if !(-4..14).contains(&exponent) {
+        format!(
+            "{}e{}{:02}",
+            trim(mantissa.to_owned()),
+            if exponent < 0 { '-' } else { '+' },
+            exponent.abs()
+        )
+    } else {
+        trim(format!("{:.*}", (13 - exponent) as usize, number))
+    }
+}
+
+// Applies the escape sequences of a quoted string, such as `\n`.
+// Returns None if the string has bytes that aren't valid UTF-8, such as `\255`.
+fn unescape(literal: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(literal.len());
+    let mut characters = literal.chars().peekable();
+
+    while let Some(character) = characters.next() {
+        if character != '\\' {
+            let mut buffer = [0; 4];
+            bytes.extend_from_slice(character.encode_utf8(&mut buffer).as_bytes());
+            continue;
+        }
+
+        let escaped = match characters.next()? {
+            'a' => b'\x07',
+            'b' => b'\x08',
+            'f' => b'\x0c',
+            'n' | '\n' => b'\n',
+            'r' => b'\r',
+            't' => b'\t',
+            'v' => b'\x0b',
+            digit if digit.is_ascii_digit() => {
+                let mut code = digit.to_digit(10)?;
+                for _ in 0..2 {
+                    match characters.peek().and_then(|digit| digit.to_digit(10)) {
+                        Some(digit) => {
+                            code = code * 10 + digit;
+                            characters.next();
+                        }
+
+                        None => break,
+                    }
+                }
+
+                if code > 255 {
+                    return None;
+                }
+
+                code as u8
+            }
+
+            other => {
+                let mut buffer = [0; 4];
+                bytes.extend_from_slice(other.encode_utf8(&mut buffer).as_bytes());
+                continue;
+            }
+        };
+
+        bytes.push(escaped);
+    }
+
+    String::from_utf8(bytes).ok()
+}
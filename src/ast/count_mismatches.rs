@@ -0,0 +1,104 @@
+//! An opt-in lint for assignments where the number of values doesn't match the number of names,
+//! such as `a, b = 1` or `local a = 1, 2`. A call or `...` at the end of the values can give any number of them,
+//! so [`Ast::count_mismatches`](../struct.Ast.html#method.count_mismatches) only flags those when there are
+//! more values than names even without it.
+use super::*;
+use crate::{
+    node::Node,
+    visitors::{Visit, Visitor},
+};
+
+/// An assignment giving more or fewer values than it has names, such as `a, b = 1` or `a = 1, 2`
+#[derive(Clone, Debug, PartialEq)]
+pub struct CountMismatch<'a> {
+    stmt: Stmt<'a>,
+    names: usize,
+    values: ValueCount,
+}
+
+impl<'a> CountMismatch<'a> {
+    /// The assignment, either a [`Stmt::Assignment`](../enum.Stmt.html#variant.Assignment) or a
+    /// [`Stmt::LocalAssignment`](../enum.Stmt.html#variant.LocalAssignment)
+    pub fn stmt(&self) -> &Stmt<'a> {
+        &self.stmt
+    }
+
+    /// How many names are assigned to, 2 for `a, b = 1`
+    pub fn name_count(&self) -> usize {
+        self.names
+    }
+
+    /// How many values are given, `Exact(1)` for `a, b = 1`.
+    /// This is only [`AtLeast`](../enum.ValueCount.html#variant.AtLeast) a count if the last value is a function call or `...`,
+    /// such as `AtLeast(3)` for `a, b = 1, 2, 3, f()`.
+    pub fn value_count(&self) -> ValueCount {
+        self.values
+    }
+
+    /// The position the assignment starts at
+    pub fn start_position(&self) -> Option<Position> {
+        self.stmt.start_position()
+    }
+
+    /// The position the assignment ends at
+    pub fn end_position(&self) -> Option<Position> {
+        self.stmt.end_position()
+    }
+}
+
+pub(crate) struct CountMismatchVisitor<'a> {
+    mismatches: Vec<CountMismatch<'a>>,
+}
+
+impl<'a> CountMismatchVisitor<'a> {
+    pub(crate) fn new() -> Self {
+        Self {
+            mismatches: Vec::new(),
+        }
+    }
+
+    pub(crate) fn find(mut self, block: &Block<'a>) -> Vec<CountMismatch<'a>> {
+        block.visit(&mut self);
+        self.mismatches
+    }
+
+    fn check(&mut self, stmt: &Stmt<'a>, names: usize, values: &Punctuated<'a, Expression<'a>>) {
+        let values = values.value_count();
+
+        // A function call or `...` at the end can give back any number of values,
+        // so it's only a mismatch if there are too many values even when it gives back none
+        let mismatched = match values {
+            ValueCount::Exact(count) => count != names,
+            ValueCount::AtLeast(count) => count > names,
+        };
+
+        if mismatched {
+            self.mismatches.push(CountMismatch {
+                stmt: stmt.clone(),
+                names,
+                values,
+            });
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for CountMismatchVisitor<'a> {
+    fn visit_stmt(&mut self, stmt: &Stmt<'a>) {
+        match stmt {
+            Stmt::Assignment(assignment) => {
+                self.check(stmt, assignment.var_list().len(), assignment.expr_list())
+            }
+
+            // `local a, b` with no values is a common way to declare variables, so it isn't a mismatch
+            Stmt::LocalAssignment(local_assignment) if !local_assignment.expr_list().is_empty() => {
+                self.check(
+                    stmt,
+                    local_assignment.name_list().len(),
+                    local_assignment.expr_list(),
+                )
+            }
+
+            _ => {}
+        }
+    }
+}
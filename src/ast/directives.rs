@@ -0,0 +1,49 @@
+//! The Luau directives in the comments at the top of a file, such as `--!strict` or `--!optimize 2`,
+//! split into their name and argument by [`Ast::luau_directives`](../struct.Ast.html#method.luau_directives).
+//! Only available when the "roblox" feature flag is enabled.
+use super::*;
+
+/// A comment giving Luau a directive, such as `--!strict` or `--!optimize 2`
+#[derive(Clone, Debug, PartialEq)]
+pub struct LuauDirective<'a> {
+    comment: Token<'a>,
+    name: String,
+    argument: Option<String>,
+}
+
+impl<'a> LuauDirective<'a> {
+    /// Reads the directive of a comment, such as `--!strict`.
+    /// Returns `None` if the token isn't a single line comment starting with `--!`.
+    pub fn from_comment(comment: &Token<'a>) -> Option<Self> {
+        let text = match &*comment.token_type() {
+            TokenType::SingleLineComment { comment } => comment.strip_prefix('!')?.to_owned(),
+            _ => return None,
+        };
+
+        let mut words = text.trim().splitn(2, char::is_whitespace);
+        let name = words.next().filter(|name| !name.is_empty())?.to_owned();
+        let argument = words.next().map(|argument| argument.trim().to_owned());
+
+        Some(Self {
+            comment: comment.clone(),
+            name,
+            argument,
+        })
+    }
+
+    /// The comment the directive is written in
+    pub fn comment(&self) -> &Token<'a> {
+        &self.comment
+    }
+
+    /// The name of the directive, the `optimize` part of `--!optimize 2`
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// What's written after the name of the directive, the `2` part of `--!optimize 2`.
+    /// Returns `None` if there's nothing, as with `--!strict`.
+    pub fn argument(&self) -> Option<&str> {
+        self.argument.as_deref()
+    }
+}
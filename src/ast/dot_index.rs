@@ -0,0 +1,120 @@
+// Rewrites indexing such as `t["foo"]` into `t.foo`, refer to `Index::to_dot`
+use super::*;
+use crate::{
+    tokenizer::{self, TokenKind},
+    visitors::{VisitMut, VisitorMut},
+};
+
+// The string an expression is made of, the `"foo"` in `"foo"`
+fn as_string<'a, 'b>(expression: &'b Expression<'a>) -> Option<&'b TokenReference<'a>> {
+    match expression {
+        Expression::Value {
+            value,
+            binop: None,
+            #[cfg(feature = "roblox")]
+                as_assertion: None,
+        } => match &**value {
+            Value::String(string) => Some(string),
+            _ => None,
+        },
+
+        _ => None,
+    }
+}
+
+// Whether the text is tokenized as a single identifier, so it isn't a keyword such as `end`
+// and is made of only letters, digits and underscores, not starting with a digit
+fn is_identifier(text: &str) -> bool {
+    // `goto` is a keyword from Lua 5.2, but it's still tokenized as an identifier
+    if cfg!(feature = "lua52") && text == "goto" {
+        return false;
+    }
+
+    match tokenizer::tokens(text).as_deref() {
+        Ok([identifier, eof]) => {
+            identifier.token_kind() == TokenKind::Identifier && eof.token_kind() == TokenKind::Eof
+        }
+
+        _ => false,
+    }
+}
+
+impl<'a> Index<'a> {
+    /// The index written with a dot, turning `["foo"]` into `.foo`.
+    /// This is only done when the key is a string whose contents are a valid name,
+    /// and not a keyword, such as `end` or, with the `lua52` feature, `goto`, or something that isn't a name, such as `2x` or `a b`.
+    /// Returns `None` for anything else, including keys that aren't strings, strings with escapes in them,
+    /// and keys with comments around them, as the comments would be lost.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::{Stmt, Suffix, Var};
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse(r#"t["foo"] = 1"#)?;
+    /// let index = match ast.nodes().iter_stmts().next() {
+    ///     Some(Stmt::Assignment(assignment)) => match assignment.var_list().iter().next() {
+    ///         Some(Var::Expression(var_expression)) => match var_expression.iter_suffixes().next() {
+    ///             Some(Suffix::Index(index)) => index,
+    ///             _ => unreachable!(),
+    ///         },
+    ///         _ => unreachable!(),
+    ///     },
+    ///     _ => unreachable!(),
+    /// };
+    ///
+    /// assert_eq!(index.to_dot().unwrap().to_string(), ".foo ");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_dot(&self) -> Option<Index<'a>> {
+        let (brackets, expression) = match self {
+            Index::Brackets {
+                brackets,
+                expression,
+            } => (brackets, expression),
+            Index::Dot { .. } => return None,
+        };
+
+        let string = as_string(expression)?;
+        let literal = match &*string.token_type() {
+            TokenType::StringLiteral { literal, .. } if is_identifier(literal) => literal.clone(),
+            _ => return None,
+        };
+
+        let (left_bracket, right_bracket) = brackets.tokens();
+        if has_comments(left_bracket) || has_comments(string) || has_comments(right_bracket) {
+            return None;
+        }
+
+        Some(Index::Dot {
+            dot: TokenReference::new(
+                left_bracket.leading_trivia().cloned().collect(),
+                Token::new(TokenType::Symbol {
+                    symbol: Symbol::Dot,
+                }),
+                Vec::new(),
+            ),
+            name: TokenReference::new(
+                Vec::new(),
+                Token::new(TokenType::Identifier {
+                    identifier: literal,
+                }),
+                right_bracket.trailing_trivia().cloned().collect(),
+            ),
+        })
+    }
+}
+
+// Rewrites every index in the block that can be written with a dot
+pub(crate) fn use_dot_indexing(block: &mut Block) {
+    block.visit_mut(&mut DotIndexRewriter);
+}
+
+struct DotIndexRewriter;
+
+impl<'a> VisitorMut<'a> for DotIndexRewriter {
+    fn visit_index(&mut self, index: &mut Index<'a>) {
+        if let Some(dot_index) = index.to_dot() {
+            *index = dot_index;
+        }
+    }
+}
@@ -0,0 +1,202 @@
+// Removes `do end` blocks and `if` branches with nothing in them, refer to `Ast::remove_empty_blocks`
+use super::*;
+use crate::visitors::{VisitMut, VisitorMut};
+
+fn trivia_has_comments<'a, 'b>(mut trivia: impl Iterator<Item = &'b Token<'a>>) -> bool
+where
+    'a: 'b,
+{
+    trivia.any(is_comment)
+}
+
+// Whether evaluating the condition can't call any functions, such as `true` or `x`, but not `x.y` or `f()`
+fn is_side_effect_free(condition: &Expression) -> bool {
+    condition.eval_constant().is_some() || as_name(condition).is_some()
+}
+
+// The call an expression is made of, the `f()` in `f()`
+fn as_call<'a, 'b>(expression: &'b Expression<'a>) -> Option<&'b FunctionCall<'a>> {
+    match expression {
+        Expression::Value {
+            value,
+            binop: None,
+            #[cfg(feature = "roblox")]
+                as_assertion: None,
+        } => match &**value {
+            Value::FunctionCall(call) => Some(call),
+            _ => None,
+        },
+
+        _ => None,
+    }
+}
+
+fn with_trivia<'a>(
+    token: &TokenReference<'a>,
+    leading_trivia: Vec<Token<'a>>,
+    trailing_trivia: Vec<Token<'a>>,
+) -> TokenReference<'a> {
+    TokenReference::new(leading_trivia, (**token).clone(), trailing_trivia)
+}
+
+// The call as its own statement, taking the place of the `if` it was the condition of.
+// Returns `None` if the call doesn't start with a name, as `(f)()` can't be a statement.
+fn call_stmt<'a>(if_stmt: &If<'a>, call: &FunctionCall<'a>) -> Option<FunctionCall<'a>> {
+    let mut call = call.clone();
+
+    // The call takes the trivia before `if`, such as its indentation
+    call.prefix = match &call.prefix {
+        Prefix::Name(name) => Prefix::Name(with_trivia(
+            name,
+            if_stmt.if_token.leading_trivia().cloned().collect(),
+            name.trailing_trivia().cloned().collect(),
+        )),
+        Prefix::Expression(_) => return None,
+    };
+
+    // ...and the trivia after `end`, such as the new line, which replaces the whitespace before `then`
+    let trailing_trivia: Vec<_> = if_stmt.end_token.trailing_trivia().cloned().collect();
+    let arguments = match call.suffixes.last_mut() {
+        Some(Suffix::Call(Call::AnonymousCall(arguments))) => arguments,
+        Some(Suffix::Call(Call::MethodCall(method_call))) => &mut method_call.args,
+        _ => unreachable!("function call doesn't end with a call"),
+    };
+
+    match arguments {
+        FunctionArgs::Parentheses { parentheses, .. } => {
+            let (start, end) = parentheses.tokens();
+            let end = with_trivia(
+                end,
+                end.leading_trivia().cloned().collect(),
+                trailing_trivia,
+            );
+            *parentheses = ContainedSpan::new(start.clone(), end);
+        }
+
+        FunctionArgs::String(string) => {
+            *string = with_trivia(
+                string,
+                string.leading_trivia().cloned().collect(),
+                trailing_trivia,
+            );
+        }
+
+        FunctionArgs::TableConstructor(table) => {
+            let (start, end) = table.braces.tokens();
+            let end = with_trivia(
+                end,
+                end.leading_trivia().cloned().collect(),
+                trailing_trivia,
+            );
+            table.braces = ContainedSpan::new(start.clone(), end);
+        }
+    }
+
+    Some(call)
+}
+
+// Drops the empty branches at the end of the if statement that can be dropped.
+// Returns whether every branch left is empty.
+fn remove_empty_branches(if_stmt: &mut If) -> bool {
+    let else_is_empty = if_stmt
+        .r#else
+        .as_ref()
+        .map(Block::is_empty)
+        .unwrap_or(false);
+    if else_is_empty && !has_comments(&if_stmt.else_token) {
+        if_stmt.else_token = None;
+        if_stmt.r#else = None;
+    }
+
+    // An empty `elseif` can only be dropped if nothing comes after it and its condition can't do anything
+    if if_stmt.r#else.is_none() {
+        if let Some(else_ifs) = &mut if_stmt.else_if {
+            while let Some(else_if) = else_ifs.last() {
+                if !else_if.block.is_empty()
+                    || !is_side_effect_free(&else_if.condition)
+                    || has_comments(else_if)
+                {
+                    break;
+                }
+
+                else_ifs.pop();
+            }
+
+            if else_ifs.is_empty() {
+                if_stmt.else_if = None;
+            }
+        }
+    }
+
+    if_stmt.block.is_empty() && if_stmt.else_if.is_none() && if_stmt.r#else.is_none()
+}
+
+// What an if statement with nothing in it is replaced with, `None` if it can be removed
+fn simplify_empty_if<'a>(
+    if_stmt: &If<'a>,
+    semicolon: &Option<TokenReference<'a>>,
+) -> Option<Option<Stmt<'a>>> {
+    if has_comments(semicolon) {
+        return None;
+    }
+
+    if is_side_effect_free(&if_stmt.condition) {
+        if has_comments(if_stmt) {
+            return None;
+        }
+
+        return Some(None);
+    }
+
+    // Comments before `if` and after `end` are kept, but not those in between
+    let call = as_call(&if_stmt.condition)?;
+    if trivia_has_comments(if_stmt.if_token.trailing_trivia())
+        || has_comments(&if_stmt.condition)
+        || has_comments(&if_stmt.then_token)
+        || trivia_has_comments(if_stmt.end_token.leading_trivia())
+    {
+        return None;
+    }
+
+    call_stmt(if_stmt, call).map(|call| Some(Stmt::FunctionCall(call)))
+}
+
+// Removes the empty blocks in the block and everything inside of it
+pub(crate) fn remove_empty_blocks(block: &mut Block) {
+    block.visit_mut(&mut EmptyBlockRemover);
+}
+
+struct EmptyBlockRemover;
+
+impl<'a> VisitorMut<'a> for EmptyBlockRemover {
+    // Blocks are only looked at once everything inside of them is, so `do do end end` is removed entirely
+    fn visit_block_end(&mut self, block: &mut Block<'a>) {
+        let mut stmts = Vec::new();
+
+        for (stmt, semicolon) in std::mem::take(&mut block.stmts) {
+            match stmt {
+                Stmt::Do(r#do)
+                    if r#do.block.is_empty()
+                        && !has_comments(&r#do)
+                        && !has_comments(&semicolon) => {}
+
+                Stmt::If(mut if_stmt) => {
+                    if !remove_empty_branches(&mut if_stmt) {
+                        stmts.push((Stmt::If(if_stmt), semicolon));
+                        continue;
+                    }
+
+                    match simplify_empty_if(&if_stmt, &semicolon) {
+                        Some(Some(stmt)) => stmts.push((stmt, semicolon)),
+                        Some(None) => {}
+                        None => stmts.push((Stmt::If(if_stmt), semicolon)),
+                    }
+                }
+
+                stmt => stmts.push((stmt, semicolon)),
+            }
+        }
+
+        block.stmts = stmts;
+    }
+}
@@ -0,0 +1,55 @@
+//! Finds the statement that code at a position is part of, such as to select it in an editor.
+//! When statements are nested, [`Depth`](enum.Depth.html) picks either the innermost or the outermost one,
+//! as given to [`Ast::enclosing_statement_with`](../struct.Ast.html#method.enclosing_statement_with).
+use super::*;
+use crate::{
+    node::Node,
+    visitors::{Visit, Visitor},
+};
+
+/// Which statement to find when statements containing the position are nested inside each other
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Depth {
+    /// The statement nested the deepest, such as a call inside the body of a loop
+    Innermost,
+    /// The statement that isn't inside any other, such as the loop a call is inside of
+    Outermost,
+}
+
+pub(crate) struct EnclosingStatementVisitor<'a> {
+    position: Position,
+    depth: Depth,
+    stmt: Option<Stmt<'a>>,
+}
+
+impl<'a> EnclosingStatementVisitor<'a> {
+    pub(crate) fn new(position: Position, depth: Depth) -> Self {
+        Self {
+            position,
+            depth,
+            stmt: None,
+        }
+    }
+
+    pub(crate) fn find(mut self, block: &Block<'a>) -> Option<Stmt<'a>> {
+        block.visit(&mut self);
+        self.stmt
+    }
+}
+
+impl<'a> Visitor<'a> for EnclosingStatementVisitor<'a> {
+    // Statements are visited before the ones inside of them, and only nested statements can both contain the position
+    fn visit_stmt(&mut self, stmt: &Stmt<'a>) {
+        if self.depth == Depth::Outermost && self.stmt.is_some() {
+            return;
+        }
+
+        if stmt
+            .span()
+            .map(|span| span.contains(self.position))
+            .unwrap_or(false)
+        {
+            self.stmt = Some(stmt.clone());
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Comparisons with `==` or `~=` where one side is a number written as a float, such as `x == 0.1` or `x ~= 1e-3`,
+//! which rarely do what's meant because of rounding.
+//! [`Ast::float_comparisons`](../struct.Ast.html#method.float_comparisons) gives back each one as a
+//! [`FloatComparison`](struct.FloatComparison.html).
+use super::{
+    constant::{Operand, Operation},
+    *,
+};
+use crate::{
+    node::Node,
+    visitors::{Visit, Visitor},
+};
+use std::collections::HashSet;
+
+/// A comparison between a value and a float, such as `x == 0.1`
+#[derive(Clone, Debug, PartialEq)]
+pub struct FloatComparison<'a> {
+    float: Value<'a>,
+    other: Option<Value<'a>>,
+    bin_op: BinOp<'a>,
+    start_position: Option<Position>,
+    end_position: Option<Position>,
+}
+
+impl<'a> FloatComparison<'a> {
+    /// The float, the `0.1` part of `x == 0.1`, without any `-` before it
+    pub fn float(&self) -> &Value<'a> {
+        &self.float
+    }
+
+    /// What the float is compared to, the `x` part of `x == 0.1`.
+    /// Returns `None` if it isn't a single value, such as `x + 1` in `x + 1 == 0.1`.
+    pub fn other(&self) -> Option<&Value<'a>> {
+        self.other.as_ref()
+    }
+
+    /// The operator used, either [`BinOp::TwoEqual`](../enum.BinOp.html#variant.TwoEqual) or
+    /// [`BinOp::TildeEqual`](../enum.BinOp.html#variant.TildeEqual)
+    pub fn bin_op(&self) -> &BinOp<'a> {
+        &self.bin_op
+    }
+
+    /// The position the comparison starts at, which is the start of either side
+    pub fn start_position(&self) -> Option<Position> {
+        self.start_position
+    }
+
+    /// The position the comparison ends at, which is the end of either side
+    pub fn end_position(&self) -> Option<Position> {
+        self.end_position
+    }
+}
+
+// The float of an operand that's just a float, or a negated float such as `-0.1`
+fn float_value<'a, 'b>(operation: &Operation<'a, 'b>) -> Option<&'b Value<'a>> {
+    match operation {
+        Operation::Operand(Operand::Value(value)) if value.is_float() => Some(value),
+        Operation::Unary(UnOp::Minus(_), operand) => float_value(operand),
+        _ => None,
+    }
+}
+
+// Where an operation starts and ends, from its first token to its last
+fn operation_span(operation: &Operation) -> (Option<Position>, Option<Position>) {
+    match operation {
+        Operation::Operand(Operand::Value(value)) => (value.start_position(), value.end_position()),
+        Operation::Operand(Operand::Parentheses(expression)) => {
+            (expression.start_position(), expression.end_position())
+        }
+        Operation::Unary(unop, operand) => (unop.start_position(), operation_span(operand).1),
+        Operation::Binary(lhs, _, rhs) => (operation_span(lhs).0, operation_span(rhs).1),
+    }
+}
+
+pub(crate) struct FloatComparisonVisitor<'a> {
+    // The expressions that continue a chain of operators, such as the `y == 0.1` of `x + y == 0.1`.
+    // They're already part of the operation they continue, so aren't looked at again.
+    chained: HashSet<*const Expression<'a>>,
+    comparisons: Vec<FloatComparison<'a>>,
+}
+
+impl<'a> FloatComparisonVisitor<'a> {
+    pub(crate) fn new() -> Self {
+        Self {
+            chained: HashSet::new(),
+            comparisons: Vec::new(),
+        }
+    }
+
+    pub(crate) fn find(mut self, block: &Block<'a>) -> Vec<FloatComparison<'a>> {
+        block.visit(&mut self);
+        self.comparisons
+    }
+
+    fn check_operation(&mut self, operation: &Operation<'a, '_>) {
+        match operation {
+            Operation::Operand(_) => {}
+
+            Operation::Unary(_, operand) => self.check_operation(operand),
+
+            Operation::Binary(lhs, bin_op, rhs) => {
+                if let BinOp::TwoEqual(_) | BinOp::TildeEqual(_) = bin_op {
+                    let compared = match (float_value(lhs), float_value(rhs)) {
+                        (_, Some(float)) => Some((float, lhs)),
+                        (Some(float), None) => Some((float, rhs)),
+                        (None, None) => None,
+                    };
+
+                    if let Some((float, other)) = compared {
+                        let other = match &**other {
+                            Operation::Operand(Operand::Value(value)) => Some((*value).clone()),
+                            _ => None,
+                        };
+
+                        self.comparisons.push(FloatComparison {
+                            float: float.clone(),
+                            other,
+                            bin_op: (*bin_op).clone(),
+                            start_position: operation_span(lhs).0,
+                            end_position: operation_span(rhs).1,
+                        });
+                    }
+                }
+
+                self.check_operation(lhs);
+                self.check_operation(rhs);
+            }
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for FloatComparisonVisitor<'a> {
+    fn visit_expression(&mut self, expression: &Expression<'a>) {
+        if self.chained.remove(&(expression as *const _)) {
+            return;
+        }
+
+        let mut chain = expression;
+        loop {
+            chain = match chain {
+                Expression::UnaryOperator { expression, .. } => expression,
+                Expression::Value {
+                    binop: Some(binop), ..
+                } => binop.rhs(),
+                _ => break,
+            };
+
+            self.chained.insert(chain);
+        }
+
+        self.check_operation(&Operation::new(expression));
+    }
+}
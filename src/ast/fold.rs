@@ -0,0 +1,125 @@
+//! The events [`Ast::fold`](../struct.Ast.html#method.fold) passes to its function as it walks the code:
+//! entering and exiting each node, and each token, all in the order they appear.
+//! The state is threaded through the function, so nothing has to capture the tree or implement `Visitor`.
+use super::{span::ContainedSpan, *};
+use crate::visitors::{visited_nodes, Visitor};
+
+macro_rules! fold_nodes {
+    (ast: {
+        $($visit_name:ident => $ast_type:ident,)+
+
+        $(#[$meta:meta] {
+            $($meta_visit_name:ident => $meta_ast_type:ident,)+
+        })+
+    }) => {
+        /// A node being entered or exited while folding, refer to [`VisitEvent`](enum.VisitEvent.html)
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        pub enum NodeRef<'a, 'b> {
+            #[allow(missing_docs)]
+            ContainedSpan(&'b ContainedSpan<'a>),
+
+            $(
+                #[allow(missing_docs)]
+                $ast_type(&'b $ast_type<'a>),
+            )+
+
+            $(
+                $(
+                    #[$meta]
+                    #[allow(missing_docs)]
+                    $meta_ast_type(&'b $meta_ast_type<'a>),
+                )+
+            )+
+        }
+
+        impl<'ast, S, F> Visitor<'ast> for Fold<S, F>
+        where
+            F: for<'b> FnMut(S, VisitEvent<'ast, 'b>) -> S,
+        {
+            paste::item! {
+                $(
+                    fn $visit_name(&mut self, node: &$ast_type<'ast>) {
+                        self.emit(VisitEvent::Enter(NodeRef::$ast_type(node)));
+                    }
+
+                    fn [<$visit_name _end>](&mut self, node: &$ast_type<'ast>) {
+                        self.emit(VisitEvent::Exit(NodeRef::$ast_type(node)));
+                    }
+                )+
+
+                $(
+                    $(
+                        #[$meta]
+                        fn $meta_visit_name(&mut self, node: &$meta_ast_type<'ast>) {
+                            self.emit(VisitEvent::Enter(NodeRef::$meta_ast_type(node)));
+                        }
+
+                        #[$meta]
+                        fn [<$meta_visit_name _end>](&mut self, node: &$meta_ast_type<'ast>) {
+                            self.emit(VisitEvent::Exit(NodeRef::$meta_ast_type(node)));
+                        }
+                    )+
+                )+
+            }
+
+            // The tokens of a contained span aren't visited, so they're emitted here instead,
+            // on either side of what's inside of it
+            fn visit_contained_span(&mut self, node: &ContainedSpan<'ast>) {
+                self.emit(VisitEvent::Enter(NodeRef::ContainedSpan(node)));
+                self.emit(VisitEvent::Token(node.tokens().0));
+            }
+
+            fn visit_contained_span_end(&mut self, node: &ContainedSpan<'ast>) {
+                self.emit(VisitEvent::Token(node.tokens().1));
+                self.emit(VisitEvent::Exit(NodeRef::ContainedSpan(node)));
+            }
+
+            fn visit_token(&mut self, token: &TokenReference<'ast>) {
+                self.emit(VisitEvent::Token(token));
+            }
+        }
+    };
+}
+
+visited_nodes!(fold_nodes! {});
+
+/// Something reached while folding over code, passed to the function given to [`Ast::fold`](../struct.Ast.html#method.fold)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VisitEvent<'a, 'b> {
+    /// A node is being entered, before any of the nodes and tokens inside of it
+    Enter(NodeRef<'a, 'b>),
+    /// A node is being exited, after all of the nodes and tokens inside of it
+    Exit(NodeRef<'a, 'b>),
+    /// A token, such as `local` or `x`
+    Token(&'b TokenReference<'a>),
+}
+
+pub(crate) struct Fold<S, F> {
+    // Only ever `None` while the function is being called
+    state: Option<S>,
+    f: F,
+}
+
+impl<S, F> Fold<S, F> {
+    pub(crate) fn new(initial: S, f: F) -> Self {
+        Self {
+            state: Some(initial),
+            f,
+        }
+    }
+
+    pub(crate) fn into_state(self) -> S {
+        self.state.expect("fold state taken while not folding")
+    }
+
+    fn emit<'a, 'b>(&mut self, event: VisitEvent<'a, 'b>)
+    where
+        F: FnMut(S, VisitEvent<'a, 'b>) -> S,
+    {
+        let state = self
+            .state
+            .take()
+            .expect("fold state taken while not folding");
+        self.state = Some((self.f)(state, event));
+    }
+}
@@ -0,0 +1,152 @@
+//! The regions of code an editor can fold, such as function bodies, the branches of if statements,
+//! and tables, as found by [`Ast::foldable_regions`](../struct.Ast.html#method.foldable_regions).
+//! Only code spanning more than one line can be folded, and each region has a
+//! [`FoldingKind`](enum.FoldingKind.html) saying what it's made of.
+use super::*;
+use crate::{
+    node::{Node, Span},
+    visitors::{Visit, Visitor},
+};
+
+/// The kind of code a [`FoldingRegion`](struct.FoldingRegion.html) is made of
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FoldingKind {
+    /// The body of a function, from its parameters to its `end`, such as `() end` in `function x() end`
+    Function,
+    /// A loop, such as `while x do end`, `repeat until x`, or either kind of `for` loop
+    Loop,
+    /// A block made with `do end`
+    Do,
+    /// A branch of an if statement, such as `if x then` or `elseif y then` and the code in them,
+    /// up to where the next branch starts. The last branch includes the `end`.
+    Branch,
+    /// A table constructor, such as `{ 1, 2, 3 }`
+    Table,
+}
+
+/// A region of code that can be folded
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FoldingRegion {
+    kind: FoldingKind,
+    span: Span,
+}
+
+impl FoldingRegion {
+    /// The kind of code the region is made of
+    pub fn kind(&self) -> FoldingKind {
+        self.kind
+    }
+
+    /// The code the region covers
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+pub(crate) struct FoldingVisitor {
+    regions: Vec<FoldingRegion>,
+}
+
+impl FoldingVisitor {
+    pub(crate) fn new() -> Self {
+        Self {
+            regions: Vec::new(),
+        }
+    }
+
+    pub(crate) fn find(mut self, block: &Block) -> Vec<FoldingRegion> {
+        block.visit(&mut self);
+        // Every branch of an if statement is found at once, before the code inside of them
+        self.regions.sort_by_key(|region| region.span.start());
+        self.regions
+    }
+
+    // Only regions spanning more than one line can be folded
+    fn push(&mut self, kind: FoldingKind, start: &TokenReference, end: Option<Position>) {
+        if let Some(end) = end {
+            let start = start_position(start);
+            if start.line() < end.line() {
+                self.regions.push(FoldingRegion {
+                    kind,
+                    span: Span::new(start, end),
+                });
+            }
+        }
+    }
+
+    fn push_node(&mut self, kind: FoldingKind, start: &TokenReference, node: &impl Node) {
+        self.push(kind, start, node.end_position());
+    }
+}
+
+// The position a token without new lines in it starts at, worked out from where it ends,
+// as the start of the first token on a line is where the new line before it ends, which is on the line before
+fn start_position(token: &TokenReference) -> Position {
+    let end = (**token).end_position();
+    let text = (**token).to_string();
+
+    Position::new(
+        end.bytes() - text.len(),
+        end.line(),
+        end.character() - text.chars().count(),
+    )
+}
+
+impl<'a> Visitor<'a> for FoldingVisitor {
+    fn visit_function_body(&mut self, body: &FunctionBody<'a>) {
+        self.push_node(
+            FoldingKind::Function,
+            body.parameters_parantheses.tokens().0,
+            body,
+        );
+    }
+
+    fn visit_while(&mut self, r#while: &While<'a>) {
+        self.push_node(FoldingKind::Loop, r#while.while_token(), r#while);
+    }
+
+    fn visit_repeat(&mut self, repeat: &Repeat<'a>) {
+        self.push_node(FoldingKind::Loop, repeat.repeat_token(), repeat);
+    }
+
+    fn visit_numeric_for(&mut self, numeric_for: &NumericFor<'a>) {
+        self.push_node(FoldingKind::Loop, numeric_for.for_token(), numeric_for);
+    }
+
+    fn visit_generic_for(&mut self, generic_for: &GenericFor<'a>) {
+        self.push_node(FoldingKind::Loop, generic_for.for_token(), generic_for);
+    }
+
+    fn visit_do(&mut self, r#do: &Do<'a>) {
+        self.push_node(FoldingKind::Do, r#do.do_token(), r#do);
+    }
+
+    fn visit_table_constructor(&mut self, table_constructor: &TableConstructor<'a>) {
+        self.push_node(
+            FoldingKind::Table,
+            table_constructor.braces.tokens().0,
+            table_constructor,
+        );
+    }
+
+    fn visit_if(&mut self, r#if: &If<'a>) {
+        // The tokens each branch starts with, the last branch ending with the `end`
+        let mut starts = vec![r#if.if_token()];
+        starts.extend(
+            r#if.else_if()
+                .into_iter()
+                .flatten()
+                .map(ElseIf::else_if_token),
+        );
+        starts.extend(r#if.else_token());
+
+        for (index, start) in starts.iter().enumerate() {
+            let end = match starts.get(index + 1) {
+                Some(next) => Some(start_position(next)),
+                None => r#if.end_token().end_position(),
+            };
+
+            self.push(FoldingKind::Branch, start, end);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! Analyses of functions: which function a position in the code is inside of
+//! ([`Ast::enclosing_function`](../struct.Ast.html#method.enclosing_function)),
+//! parameters with the same name as another in the same function
+//! ([`Ast::duplicate_parameters`](../struct.Ast.html#method.duplicate_parameters)),
+//! and `return`s with no values at the end of a function, which do nothing
+//! ([`Ast::redundant_returns`](../struct.Ast.html#method.redundant_returns)).
+use super::*;
+use crate::{
+    node::Node,
+    visitors::{Visit, Visitor},
+};
+
+/// A function, along with the node that declares it
+#[derive(Clone, Debug, PartialEq)]
+pub enum EnclosingFunction<'a> {
+    /// A function declaration, such as `function x() end`
+    Declaration(FunctionDeclaration<'a>),
+    /// A local function declaration, such as `local function x() end`
+    Local(LocalFunction<'a>),
+    /// An anonymous function, such as `function() end` in `call(function() end)`.
+    /// The first item is the `function` token.
+    Anonymous(TokenReference<'a>, FunctionBody<'a>),
+}
+
+impl<'a> EnclosingFunction<'a> {
+    /// The body of the function, which has its parameters and code
+    pub fn body(&self) -> &FunctionBody<'a> {
+        match self {
+            EnclosingFunction::Declaration(declaration) => declaration.body(),
+            EnclosingFunction::Local(local_function) => local_function.func_body(),
+            EnclosingFunction::Anonymous(_, body) => body,
+        }
+    }
+}
+
+pub(crate) struct EnclosingFunctionVisitor<'a> {
+    position: Position,
+    function: Option<EnclosingFunction<'a>>,
+}
+
+impl<'a> EnclosingFunctionVisitor<'a> {
+    pub(crate) fn new(position: Position) -> Self {
+        Self {
+            position,
+            function: None,
+        }
+    }
+
+    pub(crate) fn find(mut self, block: &Block<'a>) -> Option<EnclosingFunction<'a>> {
+        block.visit(&mut self);
+        self.function
+    }
+
+    // Functions are visited from the outside in, so the last one containing the position is the innermost
+    fn check(&mut self, node: impl Node, function: impl FnOnce() -> EnclosingFunction<'a>) {
+        if let Some(span) = node.span() {
+            if span.contains(self.position) {
+                self.function = Some(function());
+            }
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for EnclosingFunctionVisitor<'a> {
+    fn visit_function_declaration(&mut self, declaration: &FunctionDeclaration<'a>) {
+        self.check(declaration, || {
+            EnclosingFunction::Declaration(declaration.clone())
+        });
+    }
+
+    fn visit_local_function(&mut self, local_function: &LocalFunction<'a>) {
+        self.check(local_function, || {
+            EnclosingFunction::Local(local_function.clone())
+        });
+    }
+
+    fn visit_value(&mut self, value: &Value<'a>) {
+        if let Value::Function((function_token, body)) = value {
+            self.check(value, || {
+                EnclosingFunction::Anonymous(function_token.clone(), body.clone())
+            });
+        }
+    }
+}
+
+pub(crate) struct DuplicateParameterVisitor<'a> {
+    duplicates: Vec<TokenReference<'a>>,
+}
+
+impl<'a> DuplicateParameterVisitor<'a> {
+    pub(crate) fn new() -> Self {
+        Self {
+            duplicates: Vec::new(),
+        }
+    }
+
+    pub(crate) fn find(mut self, block: &Block<'a>) -> Vec<TokenReference<'a>> {
+        block.visit(&mut self);
+        self.duplicates
+    }
+}
+
+impl<'a> Visitor<'a> for DuplicateParameterVisitor<'a> {
+    fn visit_function_body(&mut self, body: &FunctionBody<'a>) {
+        self.duplicates
+            .extend(body.duplicate_parameters().into_iter().cloned());
+    }
+}
+
+pub(crate) struct RedundantReturnVisitor<'a> {
+    returns: Vec<Return<'a>>,
+}
+
+impl<'a> RedundantReturnVisitor<'a> {
+    pub(crate) fn new() -> Self {
+        Self {
+            returns: Vec::new(),
+        }
+    }
+
+    pub(crate) fn find(mut self, block: &Block<'a>) -> Vec<Return<'a>> {
+        block.visit(&mut self);
+        self.returns
+    }
+}
+
+impl<'a> Visitor<'a> for RedundantReturnVisitor<'a> {
+    fn visit_function_body(&mut self, body: &FunctionBody<'a>) {
+        self.returns.extend(body.redundant_return().cloned());
+    }
+}
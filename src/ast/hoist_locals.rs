@@ -0,0 +1,205 @@
+// Moves local declarations to the top of their blocks, refer to `Ast::hoist_locals`
+use super::*;
+use crate::visitors::{Visit, VisitMut, Visitor, VisitorMut};
+use std::collections::HashSet;
+
+// Every identifier in a node, including names of fields and ones in nested functions
+#[derive(Default)]
+struct IdentifierCollector {
+    identifiers: HashSet<String>,
+}
+
+impl<'a> Visitor<'a> for IdentifierCollector {
+    fn visit_identifier(&mut self, token: &TokenReference<'a>) {
+        self.identifiers.insert(token.to_string());
+    }
+}
+
+// Makes sure nothing else can be put on the same line after a declaration without a value
+fn end_line<'a>(
+    local_assignment: &mut LocalAssignment<'a>,
+    semicolon: &mut Option<TokenReference<'a>>,
+) {
+    let last_token = match semicolon {
+        Some(semicolon) => semicolon,
+        None => match local_assignment.name_list.pairs_mut().last() {
+            Some(Pair::End(name)) => name,
+            _ => unreachable!("local assignment doesn't end with a name"),
+        },
+    };
+
+    if !last_token
+        .trailing_trivia()
+        .any(|trivia| trivia.to_string().ends_with('\n'))
+    {
+        let mut trailing_trivia: Vec<_> = last_token.trailing_trivia().cloned().collect();
+        trailing_trivia.push(whitespace("\n"));
+
+        *last_token = TokenReference::new(
+            last_token.leading_trivia().cloned().collect(),
+            (**last_token).clone(),
+            trailing_trivia,
+        );
+    }
+}
+
+// Splits `local x = 1` into a `local x` to put at the top of the block, and `x = 1` to leave where it was
+fn split<'a>(local_assignment: &LocalAssignment<'a>) -> (LocalAssignment<'a>, Stmt<'a>) {
+    let local_token = &local_assignment.local_token;
+    let names = &local_assignment.name_list;
+
+    let mut name_list = Punctuated::new();
+    for (index, name) in names.iter().enumerate() {
+        let pair = if index + 1 == names.len() {
+            Pair::End(TokenReference::new(
+                Vec::new(),
+                (**name).clone(),
+                vec![whitespace("\n")],
+            ))
+        } else {
+            Pair::Punctuated(
+                TokenReference::new(Vec::new(), (**name).clone(), Vec::new()),
+                TokenReference::new(
+                    Vec::new(),
+                    Token::new(TokenType::Symbol {
+                        symbol: Symbol::Comma,
+                    }),
+                    vec![whitespace(" ")],
+                ),
+            )
+        };
+
+        name_list.push(pair);
+    }
+
+    let declaration = LocalAssignment {
+        local_token: TokenReference::new(
+            indentation(local_token).into_iter().collect(),
+            (**local_token).clone(),
+            vec![whitespace(" ")],
+        ),
+        #[cfg(feature = "roblox")]
+        type_specifiers: names.iter().map(|_| None).collect(),
+        name_list,
+        equal_token: None,
+        expr_list: Punctuated::new(),
+    };
+
+    // The trivia before `local` goes before the first name instead
+    let mut var_list = Punctuated::new();
+    for (index, pair) in names.pairs().enumerate() {
+        let pair = if index == 0 {
+            pair.clone().map(|name| {
+                let leading_trivia = local_token
+                    .leading_trivia()
+                    .chain(name.leading_trivia())
+                    .cloned()
+                    .collect();
+
+                TokenReference::new(
+                    leading_trivia,
+                    (*name).clone(),
+                    name.trailing_trivia().cloned().collect(),
+                )
+            })
+        } else {
+            pair.clone()
+        };
+
+        var_list.push(pair.map(Var::Name));
+    }
+
+    let assignment = Assignment {
+        var_list,
+        equal_token: local_assignment
+            .equal_token
+            .clone()
+            .expect("split local assignment doesn't have an equal token"),
+        expr_list: local_assignment.expr_list.clone(),
+    };
+
+    (declaration, Stmt::Assignment(assignment))
+}
+
+// Types can't be written on the assignment left in place, so typed declarations aren't moved
+#[cfg(feature = "roblox")]
+fn has_types(local_assignment: &LocalAssignment) -> bool {
+    local_assignment
+        .type_specifiers()
+        .any(|type_specifier| type_specifier.is_some())
+}
+
+#[cfg(not(feature = "roblox"))]
+fn has_types(_: &LocalAssignment) -> bool {
+    false
+}
+
+fn hoist_block(block: &mut Block) {
+    let mut seen = IdentifierCollector::default();
+    // The declarations already at the top of the block are kept first
+    let mut top = Vec::new();
+    let mut declarations = Vec::new();
+    let mut stmts = Vec::new();
+
+    for (mut stmt, mut semicolon) in std::mem::take(&mut block.stmts) {
+        let mut collector = IdentifierCollector::default();
+        stmt.visit(&mut collector);
+
+        let local_assignment = match &mut stmt {
+            Stmt::LocalAssignment(local_assignment) if !stmts.is_empty() => Some(local_assignment),
+            _ => None,
+        };
+
+        let hoistable = local_assignment.filter(|local_assignment| {
+            let mut names = HashSet::new();
+            let mut expressions = IdentifierCollector::default();
+            local_assignment.expr_list.visit(&mut expressions);
+
+            // Moving the declaration would change what the name refers to if it's used before it,
+            // such as in `print(x) local x = 1` or `local x = x`, or if it's declared twice
+            local_assignment.name_list.iter().all(|name| {
+                let name = name.to_string();
+                !seen.identifiers.contains(&name)
+                    && !expressions.identifiers.contains(&name)
+                    && names.insert(name)
+            }) && !has_types(local_assignment)
+        });
+
+        match hoistable {
+            Some(local_assignment) if local_assignment.expr_list.is_empty() => {
+                end_line(local_assignment, &mut semicolon);
+                declarations.push((stmt, semicolon));
+            }
+
+            Some(local_assignment) => {
+                let (declaration, assignment) = split(local_assignment);
+                declarations.push((Stmt::LocalAssignment(declaration), None));
+                stmts.push((assignment, semicolon));
+            }
+
+            None => match stmt {
+                Stmt::LocalAssignment(_) if stmts.is_empty() => top.push((stmt, semicolon)),
+                _ => stmts.push((stmt, semicolon)),
+            },
+        }
+
+        seen.identifiers.extend(collector.identifiers);
+    }
+
+    top.extend(declarations);
+    top.extend(stmts);
+    block.stmts = top;
+}
+
+// Hoists the locals of every block in the node
+pub(crate) fn hoist_locals(block: &mut Block) {
+    block.visit_mut(&mut Hoister);
+}
+
+struct Hoister;
+
+impl<'a> VisitorMut<'a> for Hoister {
+    fn visit_block(&mut self, block: &mut Block<'a>) {
+        hoist_block(block);
+    }
+}
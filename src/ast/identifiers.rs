@@ -0,0 +1,26 @@
+// Collects every identifier in the code, used by Ast::identifiers
+use super::*;
+use crate::visitors::{Visit, Visitor};
+
+pub(crate) struct IdentifierVisitor<'a> {
+    identifiers: Vec<TokenReference<'a>>,
+}
+
+impl<'a> IdentifierVisitor<'a> {
+    pub(crate) fn new() -> Self {
+        Self {
+            identifiers: Vec::new(),
+        }
+    }
+
+    pub(crate) fn find(mut self, block: &Block<'a>) -> Vec<TokenReference<'a>> {
+        block.visit(&mut self);
+        self.identifiers
+    }
+}
+
+impl<'a> Visitor<'a> for IdentifierVisitor<'a> {
+    fn visit_identifier(&mut self, token: &TokenReference<'a>) {
+        self.identifiers.push(token.clone());
+    }
+}
@@ -0,0 +1,150 @@
+//! Every kind of [`Loop`](enum.Loop.html), `while`, `repeat`, numeric `for` and generic `for`, for loop-analysis tooling.
+//! Besides [`Ast::loops`](../struct.Ast.html#method.loops), loops that are likely to never stop, such as
+//! `while true do end` without a `break`, are found with [`Ast::infinite_loops`](../struct.Ast.html#method.infinite_loops).
+use super::*;
+use crate::{
+    node::Node,
+    visitors::{Visit, Visitor},
+};
+
+/// A loop of any kind
+#[derive(Clone, Debug, PartialEq)]
+pub enum Loop<'a> {
+    /// A numeric for loop, such as `for i = 1, 10 do end`
+    NumericFor(NumericFor<'a>),
+    /// A generic for loop, such as `for k, v in pairs(t) do end`
+    GenericFor(GenericFor<'a>),
+    /// A while loop, such as `while x do end`
+    While(While<'a>),
+    /// A repeat loop, such as `repeat until x`
+    Repeat(Repeat<'a>),
+}
+
+impl<'a> Loop<'a> {
+    /// The code run on every iteration of the loop
+    pub fn body(&self) -> &Block<'a> {
+        match self {
+            Loop::NumericFor(numeric_for) => numeric_for.block(),
+            Loop::GenericFor(generic_for) => generic_for.block(),
+            Loop::While(r#while) => r#while.block(),
+            Loop::Repeat(repeat) => repeat.block(),
+        }
+    }
+
+    /// The condition deciding whether the loop keeps going, the `x` part of `while x do end`.
+    /// For repeat loops this is the `until` condition, so the loop stops once it's true.
+    /// Returns `None` for for loops, which have no condition.
+    pub fn condition(&self) -> Option<&Expression<'a>> {
+        match self {
+            Loop::While(r#while) => Some(r#while.condition()),
+            Loop::Repeat(repeat) => Some(repeat.until()),
+            Loop::NumericFor(_) | Loop::GenericFor(_) => None,
+        }
+    }
+
+    /// Whether the loop is likely to never stop, such as `while true do end`.
+    /// This is the case when the condition is a constant that never stops the loop, as with `while true` or
+    /// `repeat until false`, and nothing in its body can leave it with `break` or `return`.
+    /// A `break` inside a loop nested in this one only leaves the nested loop, so it isn't counted,
+    /// and neither is anything inside functions declared in the body. For loops are never infinite.
+    pub fn is_infinite(&self) -> bool {
+        let runs_forever = match self {
+            Loop::While(r#while) => r#while
+                .condition()
+                .eval_constant()
+                .map(|condition| condition.is_truthy()),
+            Loop::Repeat(repeat) => repeat
+                .until()
+                .eval_constant()
+                .map(|condition| !condition.is_truthy()),
+            Loop::NumericFor(_) | Loop::GenericFor(_) => None,
+        };
+
+        runs_forever.unwrap_or(false) && !can_exit(self.body(), false)
+    }
+
+    /// The position the loop starts at
+    pub fn start_position(&self) -> Option<Position> {
+        match self {
+            Loop::NumericFor(numeric_for) => numeric_for.start_position(),
+            Loop::GenericFor(generic_for) => generic_for.start_position(),
+            Loop::While(r#while) => r#while.start_position(),
+            Loop::Repeat(repeat) => repeat.start_position(),
+        }
+    }
+
+    /// The position the loop ends at
+    pub fn end_position(&self) -> Option<Position> {
+        match self {
+            Loop::NumericFor(numeric_for) => numeric_for.end_position(),
+            Loop::GenericFor(generic_for) => generic_for.end_position(),
+            Loop::While(r#while) => r#while.end_position(),
+            Loop::Repeat(repeat) => repeat.end_position(),
+        }
+    }
+}
+
+// Whether a `break` or `return` in the block can leave the loop it's in.
+// Inside a nested loop, only a `return` can.
+fn can_exit(block: &Block, nested_loop: bool) -> bool {
+    let last_stmt_exits = match block.last_stmts() {
+        Some(LastStmt::Return(_)) => true,
+        Some(LastStmt::Break(_)) => !nested_loop,
+        None => false,
+    };
+
+    last_stmt_exits
+        || block.iter_stmts().any(|stmt| match stmt {
+            Stmt::Do(r#do) => can_exit(r#do.block(), nested_loop),
+            Stmt::If(r#if) => {
+                can_exit(r#if.block(), nested_loop)
+                    || r#if
+                        .else_if()
+                        .into_iter()
+                        .flatten()
+                        .any(|else_if| can_exit(else_if.block(), nested_loop))
+                    || r#if
+                        .else_block()
+                        .map(|block| can_exit(block, nested_loop))
+                        .unwrap_or(false)
+            }
+            Stmt::NumericFor(numeric_for) => can_exit(numeric_for.block(), true),
+            Stmt::GenericFor(generic_for) => can_exit(generic_for.block(), true),
+            Stmt::While(r#while) => can_exit(r#while.block(), true),
+            Stmt::Repeat(repeat) => can_exit(repeat.block(), true),
+            _ => false,
+        })
+}
+
+pub(crate) struct LoopVisitor<'a> {
+    loops: Vec<Loop<'a>>,
+}
+
+impl<'a> LoopVisitor<'a> {
+    pub(crate) fn new() -> Self {
+        Self { loops: Vec::new() }
+    }
+
+    pub(crate) fn find(mut self, block: &Block<'a>) -> Vec<Loop<'a>> {
+        block.visit(&mut self);
+        self.loops
+    }
+}
+
+impl<'a> Visitor<'a> for LoopVisitor<'a> {
+    fn visit_numeric_for(&mut self, numeric_for: &NumericFor<'a>) {
+        self.loops.push(Loop::NumericFor(numeric_for.clone()));
+    }
+
+    fn visit_generic_for(&mut self, generic_for: &GenericFor<'a>) {
+        self.loops.push(Loop::GenericFor(generic_for.clone()));
+    }
+
+    fn visit_while(&mut self, r#while: &While<'a>) {
+        self.loops.push(Loop::While(r#while.clone()));
+    }
+
+    fn visit_repeat(&mut self, repeat: &Repeat<'a>) {
+        self.loops.push(Loop::Repeat(repeat.clone()));
+    }
+}
@@ -0,0 +1,91 @@
+// Rewrites calls such as `t.insert(t, 1)` into method calls, refer to `FunctionCall::to_method_call`
+use super::*;
+use crate::visitors::{VisitMut, VisitorMut};
+
+impl<'a> FunctionCall<'a> {
+    /// The call written as a method call, turning `t.insert(t, 1)` into `t:insert(1)`.
+    /// This is only done when the function is indexed from a name with a dot and the first argument is that same name,
+    /// so that the name is only evaluated once either way and nothing else about the call changes.
+    /// Returns `None` for anything else, such as `t.insert(u, 1)`, `a.b.c(a.b)`, or `t.insert(t--[[list]], 1)`,
+    /// as comments around the removed argument would be lost.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::Stmt;
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("t.insert(t, 1)")?;
+    /// let call = match ast.nodes().iter_stmts().next() {
+    ///     Some(Stmt::FunctionCall(call)) => call,
+    ///     _ => unreachable!(),
+    /// };
+    ///
+    /// assert_eq!(call.to_method_call().unwrap().to_string(), "t:insert(1)");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_method_call(&self) -> Option<FunctionCall<'a>> {
+        let receiver = match &self.prefix {
+            Prefix::Name(name) => name,
+            Prefix::Expression(_) => return None,
+        };
+
+        let (dot, name, parentheses, arguments) = match self.suffixes.get(..2)? {
+            [Suffix::Index(Index::Dot { dot, name }), Suffix::Call(Call::AnonymousCall(FunctionArgs::Parentheses {
+                parentheses,
+                arguments,
+            }))] => (dot, name, parentheses, arguments),
+            _ => return None,
+        };
+
+        let mut pairs = arguments.pairs();
+        let first = pairs.next()?;
+        let first_name = as_name(first.value())?;
+
+        if *first_name.token_type() != *receiver.token_type()
+            || has_comments(first_name)
+            || first.punctuation().map(has_comments).unwrap_or(false)
+        {
+            return None;
+        }
+
+        let mut arguments = Punctuated::new();
+        arguments.extend(pairs.cloned());
+
+        let method_call = MethodCall {
+            colon_token: TokenReference::new(
+                dot.leading_trivia().cloned().collect(),
+                Token::new(TokenType::Symbol {
+                    symbol: Symbol::Colon,
+                }),
+                dot.trailing_trivia().cloned().collect(),
+            ),
+            name: name.clone(),
+            args: FunctionArgs::Parentheses {
+                parentheses: parentheses.clone(),
+                arguments,
+            },
+        };
+
+        let mut suffixes = vec![Suffix::Call(Call::MethodCall(method_call))];
+        suffixes.extend(self.suffixes[2..].iter().cloned());
+
+        Some(FunctionCall {
+            prefix: self.prefix.clone(),
+            suffixes,
+        })
+    }
+}
+
+// Rewrites every call in the block that can be a method call
+pub(crate) fn use_method_calls(block: &mut Block) {
+    block.visit_mut(&mut MethodCallRewriter);
+}
+
+struct MethodCallRewriter;
+
+impl<'a> VisitorMut<'a> for MethodCallRewriter {
+    fn visit_function_call(&mut self, call: &mut FunctionCall<'a>) {
+        if let Some(method_call) = call.to_method_call() {
+            *call = method_call;
+        }
+    }
+}
@@ -1,21 +1,53 @@
+mod align_assignments;
+pub mod assignments;
+pub mod branches;
+pub mod builders;
+pub mod code_loading;
+pub mod comment_style;
+pub mod constant;
+pub mod count_mismatches;
+#[cfg(feature = "roblox")]
+pub mod directives;
+mod dot_index;
+mod empty_blocks;
+pub mod enclosing;
+pub mod float_comparisons;
+pub mod fold;
+pub mod folding;
+pub mod functions;
+mod hoist_locals;
+mod identifiers;
+pub mod loops;
+mod method_calls;
+pub mod nil_comparisons;
 pub mod owned;
 #[macro_use]
 mod parser_util;
 mod parsers;
 pub mod punctuated;
+pub mod requires;
+pub mod scopes;
+mod sexpr;
 pub mod span;
-
-use crate::tokenizer::{Symbol, Token, TokenKind, TokenReference, TokenType};
+mod split_assignments;
+mod statements;
+mod table_constructors;
+mod table_style;
+
+use crate::{
+    node::{Node, Span},
+    tokenizer::{Position, Symbol, Token, TokenKind, TokenReference, TokenType},
+    util::*,
+};
+use derive_more::Display;
 use full_moon_derive::{Node, Owned, Visit};
 use generational_arena::Arena;
 use itertools::Itertools;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use std::{borrow::Cow, fmt, iter::FromIterator, sync::Arc};
+use std::{borrow::Cow, fmt, sync::Arc};
 
-use parser_util::{
-    InternalAstError, OneOrMore, Parser, ParserState, ZeroOrMore, ZeroOrMoreDelimited,
-};
+use parser_util::{parse_to_eof, OneOrMore, ZeroOrMore, ZeroOrMoreDelimited};
 
 use punctuated::{Pair, Punctuated};
 use span::ContainedSpan;
@@ -26,7 +58,12 @@ pub mod types;
 use types::*;
 
 /// A block of statements, such as in if/do/etc block
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Default, Display, PartialEq, Owned, Node, Visit)]
+#[display(
+    fmt = "{}{}",
+    "display_optional_punctuated_vec(stmts)",
+    "display_option(last_stmt.as_ref().map(display_optional_punctuated))"
+)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Block<'a> {
     #[cfg_attr(feature = "serde", serde(borrow))]
@@ -45,21 +82,98 @@ impl<'a> Block<'a> {
     pub fn last_stmts(&self) -> Option<&LastStmt<'a>> {
         Some(&self.last_stmt.as_ref()?.0)
     }
+
+    /// Whether the block has no statements in it, including no [last statement](#method.last_stmts),
+    /// such as the body of `do end`. Comments inside the block aren't statements, so don't count.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::Stmt;
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("do -- nothing\nend")?;
+    /// match ast.nodes().iter_stmts().next() {
+    ///     Some(Stmt::Do(r#do)) => assert!(r#do.block().is_empty()),
+    ///     _ => unreachable!(),
+    /// }
+    ///
+    /// assert!(!ast.nodes().is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.stmts.is_empty() && self.last_stmt.is_none()
+    }
+
+    /// The [statements](enum.Stmt.html) in the block with any of their code inside the span, in the order they appear.
+    /// Useful to find the statements in a selection made in an editor. Statements nested inside others,
+    /// such as inside a `do` block, aren't included, nor is the [last statement](#method.last_stmts),
+    /// which can be checked with [`Node::intersects`](../node/trait.Node.html#method.intersects).
+    ///
+    /// ```rust
+    /// # use full_moon::{node::Span, tokenizer::Position};
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("local x = 1\nlocal y = 2\nlocal z = 3")?;
+    /// // From the `1` to the `y`
+    /// let selection = Span::new(Position::new(10, 1, 11), Position::new(19, 2, 8));
+    /// let stmts: Vec<_> = ast.nodes().stmts_intersecting(selection).map(ToString::to_string).collect();
+    /// assert_eq!(stmts, vec!["local x = 1\n", "local y = 2\n"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stmts_intersecting(&self, span: Span) -> impl Iterator<Item = &Stmt<'a>> {
+        self.iter_stmts().filter(move |stmt| stmt.intersects(span))
+    }
+
+    /// Wraps the block in a new scope, `do ... end`, keeping the whitespace and comments of its statements.
+    /// The `do` and `end` tokens are put on their own lines.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("local x = 1\n")?;
+    /// let block = ast.nodes().clone().wrap_in_do();
+    /// assert_eq!(block.to_string(), "do\nlocal x = 1\nend");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn wrap_in_do(self) -> Do<'a> {
+        let end_leading_trivia = if self.to_string().ends_with('\n') {
+            Vec::new()
+        } else {
+            vec![whitespace("\n")]
+        };
+
+        Do {
+            do_token: TokenReference::new(
+                Vec::new(),
+                Token::new(TokenType::Symbol { symbol: Symbol::Do }),
+                vec![whitespace("\n")],
+            ),
+            block: self,
+            end_token: TokenReference::new(
+                end_leading_trivia,
+                Token::new(TokenType::Symbol {
+                    symbol: Symbol::End,
+                }),
+                Vec::new(),
+            ),
+        }
+    }
 }
 
 /// The last statement of a [`Block`](struct.Block.html)
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum LastStmt<'a> {
     #[cfg_attr(feature = "serde", serde(borrow))]
     /// A `break` statement
+    #[display(fmt = "{:#}", "_0")]
     Break(TokenReference<'a>),
     /// A `return` statement
     Return(Return<'a>),
 }
 
 /// A `return` statement
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[display(fmt = "{:#}{}", "token", "returns")]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Return<'a> {
     #[cfg_attr(feature = "serde", serde(borrow))]
@@ -73,20 +187,88 @@ impl<'a> Return<'a> {
         &self.token
     }
 
+    /// The `return` token, but mutable
+    pub fn token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.token
+    }
+
     /// The values being returned
     pub fn returns(&self) -> &Punctuated<'a, Expression<'a>> {
         &self.returns
     }
+
+    /// The values being returned, but mutable.
+    /// When adding values to an empty return, use [`set_returns`](#method.set_returns) instead,
+    /// which makes sure they're separated from the `return` token.
+    pub fn returns_mut(&mut self) -> &mut Punctuated<'a, Expression<'a>> {
+        &mut self.returns
+    }
+
+    /// Replaces the values being returned.
+    /// If there are values but nothing after the `return` token to separate them from it,
+    /// as with `return` at the end of a file, a space is added after it so the code stays valid.
+    /// Likewise, a new line is added if it's followed by a comment running to the end of the file.
+    pub fn set_returns(&mut self, returns: Punctuated<'a, Expression<'a>>) {
+        let separator = match self.token.trailing_trivia().last().map(Token::token_type) {
+            None => Some(" "),
+            Some(token_type) => match &*token_type {
+                TokenType::SingleLineComment { .. } => Some("\n"),
+                _ => None,
+            },
+        };
+
+        if let (Some(separator), false) = (separator, returns.is_empty()) {
+            let mut trailing_trivia: Vec<_> = self.token.trailing_trivia().cloned().collect();
+            trailing_trivia.push(whitespace(separator));
+
+            self.token = TokenReference::new(
+                self.token.leading_trivia().cloned().collect(),
+                (*self.token).clone(),
+                trailing_trivia,
+            );
+        }
+
+        self.returns = returns;
+    }
+
+    /// Whether the return is a proper tail call, such as `return call(x)`.
+    /// Returning more than one value, or doing anything with the result of the call
+    /// (such as `return call(x) + 1` or `return (call(x))`) is not a tail call.
+    pub fn is_tail_call(&self) -> bool {
+        if self.returns.len() != 1 {
+            return false;
+        }
+
+        if let Some(Expression::Value {
+            value, binop: None, ..
+        }) = self.returns.iter().next()
+        {
+            if let Value::FunctionCall(_) = **value {
+                return true;
+            }
+        }
+
+        false
+    }
 }
 
 /// Fields of a [`TableConstructor`](struct.TableConstructor.html)
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum Field<'a> {
     /// A key in the format of `[expression] = value`
+    #[display(
+        fmt = "{:#}{}{:#}{:#}{}",
+        "brackets.tokens().0",
+        "key",
+        "brackets.tokens().1",
+        "equal",
+        "value"
+    )]
     ExpressionKey {
         /// The `[...]` part of `[expression] = value`
         #[cfg_attr(feature = "serde", serde(borrow))]
+        #[visit(contains = "key")]
         brackets: ContainedSpan<'a>,
         /// The `expression` part of `[expression] = value`
         key: Expression<'a>,
@@ -97,6 +279,7 @@ pub enum Field<'a> {
     },
 
     /// A key in the format of `name = value`
+    #[display(fmt = "{:#}{:#}{}", "key", "equal", "value")]
     NameKey {
         #[cfg_attr(feature = "serde", serde(borrow))]
         /// The `name` part of `name = value`
@@ -116,12 +299,56 @@ pub enum Field<'a> {
 /// Second parameter is the separator used (`,` or `;`) if one exists
 pub type TableConstructorField<'a> = (Field<'a>, Option<TokenReference<'a>>);
 
+/// The separator after a [field](type.TableConstructorField.html) of a table, which Lua allows to be either `,` or `;`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldSeparator {
+    /// `,`, such as in `{ 1, 2 }`
+    Comma,
+    /// `;`, such as in `{ 1; 2 }`
+    Semicolon,
+}
+
+/// Which separator comes after the field, `None` if there isn't one, such as after the last field of `{ 1, 2 }`.
+/// Also `None` if the separator is any other token, which can only happen if the field was changed by hand.
+///
+/// ```rust
+/// # use full_moon::ast::{field_separator_kind, FieldSeparator};
+/// # fn main() -> Result<(), Box<std::error::Error>> {
+/// let ast = full_moon::parse("local t = { 1; 2, 3 }")?;
+/// let table = &ast.table_constructors()[0];
+/// let separators: Vec<_> = table.iter_fields().map(field_separator_kind).collect();
+/// assert_eq!(
+///     separators,
+///     vec![Some(FieldSeparator::Semicolon), Some(FieldSeparator::Comma), None],
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub fn field_separator_kind(field: &TableConstructorField) -> Option<FieldSeparator> {
+    match &*field.1.as_ref()?.token_type() {
+        TokenType::Symbol {
+            symbol: Symbol::Comma,
+        } => Some(FieldSeparator::Comma),
+        TokenType::Symbol {
+            symbol: Symbol::Semicolon,
+        } => Some(FieldSeparator::Semicolon),
+        _ => None,
+    }
+}
+
 /// A table being constructed, such as `{ 1, 2, 3 }` or `{ a = 1 }`
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[display(
+    fmt = "{:#}{}{:#}",
+    "braces.tokens().0",
+    "display_optional_punctuated_vec(fields)",
+    "braces.tokens().1"
+)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct TableConstructor<'a> {
     #[cfg_attr(feature = "serde", serde(borrow))]
     #[node(full_range)]
+    #[visit(contains = "fields")]
     braces: ContainedSpan<'a>,
     fields: Vec<TableConstructorField<'a>>,
 }
@@ -138,8 +365,22 @@ impl<'a> TableConstructor<'a> {
     }
 }
 
+impl<'a> Default for TableConstructor<'a> {
+    /// An empty table constructor, `{}`
+    fn default() -> Self {
+        Self {
+            braces: ContainedSpan::new(
+                TokenReference::symbol(Symbol::LeftBrace),
+                TokenReference::symbol(Symbol::RightBrace),
+            ),
+            fields: Vec::new(),
+        }
+    }
+}
+
 /// A binary operation, such as (`+ 3`)
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[display(fmt = "{}{}", "bin_op", "rhs")]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[visit(visit_as = "bin_op")]
 pub struct BinOpRhs<'a> {
@@ -170,6 +411,7 @@ pub enum Expression<'a> {
         #[cfg_attr(feature = "serde", serde(borrow))]
         /// The parentheses of the `ParenExpression`
         #[node(full_range)]
+        #[visit(contains = "expression")]
         contained: ContainedSpan<'a>,
         /// The expression inside the parentheses
         expression: Box<Expression<'a>>,
@@ -200,31 +442,525 @@ pub enum Expression<'a> {
     },
 }
 
+// Written by hand rather than derived so that long chains of operators, such as `a .. b .. c ..`,
+// are written in a loop instead of recursing for every operator
+impl<'a> fmt::Display for Expression<'a> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let mut expression = self;
+
+        loop {
+            expression = match expression {
+                Expression::Parentheses {
+                    contained,
+                    expression,
+                } => {
+                    let (start, end) = contained.tokens();
+                    return write!(formatter, "{:#}{}{:#}", start, expression, end);
+                }
+
+                Expression::UnaryOperator { unop, expression } => {
+                    write!(formatter, "{}", unop)?;
+                    expression
+                }
+
+                Expression::Value {
+                    value,
+                    binop,
+                    #[cfg(feature = "roblox")]
+                    as_assertion,
+                } => {
+                    write!(formatter, "{}", value)?;
+                    #[cfg(feature = "roblox")]
+                    write!(formatter, "{}", display_option(as_assertion))?;
+
+                    match binop {
+                        Some(binop) => {
+                            write!(formatter, "{}", binop.bin_op())?;
+                            binop.rhs()
+                        }
+
+                        None => return Ok(()),
+                    }
+                }
+            };
+        }
+    }
+}
+
+impl<'a> Expression<'a> {
+    /// An iterator over every [binary operator](enum.BinOp.html) used in the expression,
+    /// including those inside parentheses, such as the `+`, `*`, and `-` in `a + (b * c) - d`.
+    /// Operators are yielded in the order they appear in the code.
+    pub fn binops(&self) -> impl Iterator<Item = &BinOp<'a>> {
+        let mut binops = Vec::new();
+        self.collect_binops(&mut binops);
+        binops.into_iter()
+    }
+
+    /// The value of the expression if it's made only of constants, such as `1 + 2` or `"a" .. "b"`.
+    /// Returns `None` if the expression uses anything that isn't constant, such as a variable or a function call,
+    /// or if evaluating it would be an error, such as `1 + true`.
+    ///
+    /// Like in Lua, `and` and `or` give back one of their operands rather than a boolean,
+    /// and the right hand side is only needed if the left hand side doesn't decide the result.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::{constant::Constant, LastStmt};
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("return 1 + 2 * 3, nil or 'default', false and call()")?;
+    /// let returns = match ast.nodes().last_stmts() {
+    ///     Some(LastStmt::Return(r#return)) => r#return.returns(),
+    ///     _ => unreachable!(),
+    /// };
+    ///
+    /// let values = returns.iter().map(|expression| expression.eval_constant()).collect::<Vec<_>>();
+    /// assert_eq!(values[0], Some(Constant::Number(7.0)));
+    /// assert_eq!(values[1], Some(Constant::String("default".into())));
+    /// assert_eq!(values[2], Some(Constant::Boolean(false)));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn eval_constant(&self) -> Option<constant::Constant<'a>> {
+        constant::Operation::new(self).eval()
+    }
+
+    /// Whether the expression is a function call or `...` in parentheses, such as `(f())`.
+    /// The parentheses make it give back only its first value, so removing them would change the code's behavior:
+    /// in `local a, b = (f())`, `b` is always `nil`.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::LastStmt;
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("return (f()), (1)")?;
+    /// let returns = match ast.nodes().last_stmts() {
+    ///     Some(LastStmt::Return(r#return)) => r#return.returns(),
+    ///     _ => unreachable!(),
+    /// };
+    ///
+    /// let mut returns = returns.iter();
+    /// assert!(returns.next().unwrap().truncates_multi_return());
+    /// assert!(!returns.next().unwrap().truncates_multi_return());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn truncates_multi_return(&self) -> bool {
+        match self {
+            Expression::Parentheses { expression, .. } => {
+                expression.is_multiple_values() || expression.truncates_multi_return()
+            }
+
+            Expression::Value {
+                value, binop: None, ..
+            } => {
+                if let Value::ParseExpression(expression) = &**value {
+                    expression.truncates_multi_return()
+                } else {
+                    false
+                }
+            }
+
+            _ => false,
+        }
+    }
+
+    // Whether the expression is a function call or `...`, which can give back any number of values
+    fn is_multiple_values(&self) -> bool {
+        if let Expression::Value {
+            value, binop: None, ..
+        } = self
+        {
+            match &**value {
+                Value::FunctionCall(_) => return true,
+                Value::Symbol(token) => {
+                    return *token.token_type()
+                        == TokenType::Symbol {
+                            symbol: Symbol::Ellipse,
+                        }
+                }
+                _ => {}
+            }
+        }
+
+        false
+    }
+
+    /// An iterator over every value in the expression in the order Lua evaluates them, which is the order
+    /// their side effects happen in. Values inside another, such as the arguments of a function call,
+    /// come before the value containing them. The bodies of anonymous functions aren't evaluated, so
+    /// nothing inside them is included.
+    ///
+    /// Values that might be skipped by `and` or `or` are yielded with the operator that can skip them,
+    /// refer to [`Evaluation::short_circuit`](struct.Evaluation.html#method.short_circuit).
+    ///
+    /// ```rust
+    /// # use full_moon::ast::LastStmt;
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("return f(x) + g()")?;
+    /// let expression = match ast.nodes().last_stmts() {
+    ///     Some(LastStmt::Return(r#return)) => r#return.returns().iter().next().unwrap(),
+    ///     _ => unreachable!(),
+    /// };
+    ///
+    /// let values: Vec<_> = expression
+    ///     .eval_order()
+    ///     .map(|evaluation| evaluation.value().to_string().trim().to_owned())
+    ///     .collect();
+    /// assert_eq!(values, vec!["x", "f(x)", "g()"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn eval_order(&self) -> impl Iterator<Item = Evaluation<'a, '_>> {
+        let mut evaluations = Vec::new();
+        collect_eval_order(&constant::Operation::new(self), None, &mut evaluations);
+        evaluations.into_iter()
+    }
+
+    /// The expression inside any parentheses around it, such as `x` for `((x))`.
+    /// Every layer of parentheses is removed, the number of layers is given by
+    /// [`paren_depth`](#method.paren_depth). Returns the expression itself if it isn't in parentheses.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::LastStmt;
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("return ((x + 1))")?;
+    /// let expression = match ast.nodes().last_stmts() {
+    ///     Some(LastStmt::Return(r#return)) => r#return.returns().iter().next().unwrap(),
+    ///     _ => unreachable!(),
+    /// };
+    ///
+    /// assert_eq!(expression.unwrap_parens().to_string(), "x + 1");
+    /// assert_eq!(expression.paren_depth(), 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn unwrap_parens(&self) -> &Expression<'a> {
+        self.peel_parens().0
+    }
+
+    /// How many layers of parentheses are around the expression, such as 2 for `((x))`.
+    /// Refer to [`unwrap_parens`](#method.unwrap_parens).
+    pub fn paren_depth(&self) -> usize {
+        self.peel_parens().1
+    }
+
+    // The innermost expression inside parentheses, and how many layers were around it.
+    // Parentheses are parsed as a value holding an `Expression::Parentheses`, so both are looked through.
+    fn peel_parens(&self) -> (&Expression<'a>, usize) {
+        let mut expression = self;
+        let mut depth = 0;
+
+        loop {
+            expression = match expression {
+                Expression::Parentheses { expression, .. } => {
+                    depth += 1;
+                    expression
+                }
+
+                Expression::Value {
+                    value,
+                    binop: None,
+                    #[cfg(feature = "roblox")]
+                        as_assertion: None,
+                } => {
+                    if let Value::ParseExpression(inner) = &**value {
+                        inner
+                    } else {
+                        break;
+                    }
+                }
+
+                _ => break,
+            };
+        }
+
+        (expression, depth)
+    }
+
+    fn collect_binops<'b>(&'b self, binops: &mut Vec<&'b BinOp<'a>>) {
+        match self {
+            Expression::Parentheses { expression, .. }
+            | Expression::UnaryOperator { expression, .. } => expression.collect_binops(binops),
+
+            Expression::Value { value, binop, .. } => {
+                if let Value::ParseExpression(expression) = &**value {
+                    expression.collect_binops(binops);
+                }
+
+                if let Some(binop) = binop {
+                    binops.push(binop.bin_op());
+                    binop.rhs().collect_binops(binops);
+                }
+            }
+        }
+    }
+}
+
+/// A value being evaluated, yielded by [`Expression::eval_order`](enum.Expression.html#method.eval_order)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Evaluation<'a, 'b> {
+    value: &'b Value<'a>,
+    short_circuit: Option<&'b BinOp<'a>>,
+}
+
+impl<'a, 'b> Evaluation<'a, 'b> {
+    /// The value being evaluated
+    pub fn value(&self) -> &'b Value<'a> {
+        self.value
+    }
+
+    /// The closest `and` or `or` that might skip evaluating the value, such as the `and` in `x and f()` for `f()`.
+    /// Returns `None` if the value is always evaluated.
+    pub fn short_circuit(&self) -> Option<&'b BinOp<'a>> {
+        self.short_circuit
+    }
+}
+
+fn collect_eval_order<'a, 'b>(
+    operation: &constant::Operation<'a, 'b>,
+    short_circuit: Option<&'b BinOp<'a>>,
+    evaluations: &mut Vec<Evaluation<'a, 'b>>,
+) {
+    use constant::{Operand, Operation};
+
+    match operation {
+        Operation::Operand(Operand::Value(value)) => {
+            collect_value_eval_order(value, short_circuit, evaluations)
+        }
+
+        Operation::Operand(Operand::Parentheses(expression)) => {
+            collect_expression_eval_order(expression, short_circuit, evaluations)
+        }
+
+        Operation::Unary(_, operand) => collect_eval_order(operand, short_circuit, evaluations),
+
+        Operation::Binary(lhs, bin_op, rhs) => {
+            collect_eval_order(lhs, short_circuit, evaluations);
+
+            // The right hand side of `and` and `or` is only evaluated if the left hand side doesn't decide the result
+            let rhs_short_circuit = match bin_op {
+                BinOp::And(_) | BinOp::Or(_) => Some(*bin_op),
+                _ => short_circuit,
+            };
+
+            collect_eval_order(rhs, rhs_short_circuit, evaluations);
+        }
+    }
+}
+
+fn collect_expression_eval_order<'a, 'b>(
+    expression: &'b Expression<'a>,
+    short_circuit: Option<&'b BinOp<'a>>,
+    evaluations: &mut Vec<Evaluation<'a, 'b>>,
+) {
+    collect_eval_order(
+        &constant::Operation::new(expression),
+        short_circuit,
+        evaluations,
+    );
+}
+
+fn collect_value_eval_order<'a, 'b>(
+    value: &'b Value<'a>,
+    short_circuit: Option<&'b BinOp<'a>>,
+    evaluations: &mut Vec<Evaluation<'a, 'b>>,
+) {
+    let mut expressions = Vec::new();
+
+    match value {
+        Value::FunctionCall(call) => {
+            push_call_expressions(call.prefix(), call.iter_suffixes(), &mut expressions)
+        }
+        Value::Var(Var::Expression(var)) => {
+            push_call_expressions(var.prefix(), var.iter_suffixes(), &mut expressions)
+        }
+        Value::TableConstructor(table) => push_table_expressions(table, &mut expressions),
+        Value::ParseExpression(expression) => expressions.push(expression),
+        _ => {}
+    }
+
+    for expression in expressions {
+        collect_expression_eval_order(expression, short_circuit, evaluations);
+    }
+
+    evaluations.push(Evaluation {
+        value,
+        short_circuit,
+    });
+}
+
+// The expressions of a prefix and its suffixes, such as the arguments of a call, in the order they're evaluated
+fn push_call_expressions<'a, 'b>(
+    prefix: &'b Prefix<'a>,
+    suffixes: impl Iterator<Item = &'b Suffix<'a>>,
+    expressions: &mut Vec<&'b Expression<'a>>,
+) {
+    if let Prefix::Expression(expression) = prefix {
+        expressions.push(expression);
+    }
+
+    for suffix in suffixes {
+        let arguments = match suffix {
+            Suffix::Index(Index::Brackets { expression, .. }) => {
+                expressions.push(expression);
+                continue;
+            }
+            Suffix::Index(Index::Dot { .. }) => continue,
+            Suffix::Call(Call::AnonymousCall(arguments)) => arguments,
+            Suffix::Call(Call::MethodCall(method_call)) => method_call.args(),
+        };
+
+        match arguments {
+            FunctionArgs::Parentheses { arguments, .. } => expressions.extend(arguments.iter()),
+            FunctionArgs::TableConstructor(table) => push_table_expressions(table, expressions),
+            FunctionArgs::String(_) => {}
+        }
+    }
+}
+
+// The expressions of a table constructor's fields, in the order they're evaluated
+fn push_table_expressions<'a, 'b>(
+    table: &'b TableConstructor<'a>,
+    expressions: &mut Vec<&'b Expression<'a>>,
+) {
+    for (field, _) in table.iter_fields() {
+        match field {
+            Field::ExpressionKey { key, value, .. } => {
+                expressions.push(key);
+                expressions.push(value);
+            }
+            Field::NameKey { value, .. } | Field::NoKey(value) => expressions.push(value),
+        }
+    }
+}
+
+/// The number of values a list of expressions gives back, such as the right hand side of an assignment.
+/// Refer to [`Punctuated::value_count`](punctuated/struct.Punctuated.html#method.value_count) for more details.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueCount {
+    /// Exactly this many values, such as 2 for `1, 2`
+    Exact(usize),
+    /// At least this many values, such as 1 for `1, f()`
+    AtLeast(usize),
+}
+
+impl<'a> Punctuated<'a, Expression<'a>> {
+    /// The number of values the expressions give back.
+    /// If the last expression is a function call or `...`, it can give back any number of values,
+    /// including none, so the count is only [`AtLeast`](enum.ValueCount.html#variant.AtLeast) the other expressions.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::{LastStmt, ValueCount};
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("return 1, f()")?;
+    /// let returns = match ast.nodes().last_stmts() {
+    ///     Some(LastStmt::Return(r#return)) => r#return.returns(),
+    ///     _ => unreachable!(),
+    /// };
+    ///
+    /// assert_eq!(returns.value_count(), ValueCount::AtLeast(1));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn value_count(&self) -> ValueCount {
+        match self.iter().last() {
+            Some(last) if last.is_multiple_values() => ValueCount::AtLeast(self.len() - 1),
+            _ => ValueCount::Exact(self.len()),
+        }
+    }
+}
+
 /// Values that cannot be used standalone, but as part of things such as [statements](enum.Stmt.html)
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum Value<'a> {
     #[cfg_attr(feature = "serde", serde(borrow))]
     /// An anonymous function, such as `function() end)`
+    #[display(fmt = "{:#}{}", "_0.0", "_0.1")]
     Function((TokenReference<'a>, FunctionBody<'a>)),
     /// A call of a function, such as `call()`
     FunctionCall(FunctionCall<'a>),
     /// A table constructor, such as `{ 1, 2, 3 }`
     TableConstructor(TableConstructor<'a>),
     /// A number token, such as `3.3`
+    #[display(fmt = "{:#}", "_0")]
     Number(TokenReference<'a>),
     /// An expression between parentheses, such as `(3 + 2)`
     ParseExpression(Expression<'a>),
     /// A string token, such as `"hello"`
+    #[display(fmt = "{:#}", "_0")]
     String(TokenReference<'a>),
     /// A symbol, such as `true`
+    #[display(fmt = "{:#}", "_0")]
     Symbol(TokenReference<'a>),
     /// A more complex value, such as `call().x`
     Var(Var<'a>),
 }
 
+impl<'a> Value<'a> {
+    /// The number as it's written in the code, such as `0xFF` or `1e3`, if the value is a number.
+    /// Use [`parse_number`](#method.parse_number) to get the number it represents.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::{Expression, LastStmt};
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("return 0xFF")?;
+    /// # let value = match ast.nodes().last_stmts() {
+    /// #     Some(LastStmt::Return(r#return)) => match r#return.returns().iter().next() {
+    /// #         Some(Expression::Value { value, .. }) => value,
+    /// #         _ => unreachable!(),
+    /// #     },
+    /// #     _ => unreachable!(),
+    /// # };
+    /// // `value` is the `0xFF`
+    /// assert_eq!(value.spelling().as_deref(), Some("0xFF"));
+    /// assert_eq!(value.parse_number(), Some(255.0));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn spelling(&self) -> Option<Cow<'a, str>> {
+        match self {
+            Value::Number(number) => number.as_number(),
+            _ => None,
+        }
+    }
+
+    /// The number the value represents, such as `255` for `0xFF`, if the value is a number.
+    /// Returns `None` for anything else, including expressions that evaluate to numbers such as `-1` or `1 + 2`,
+    /// which can be evaluated with [`Expression::eval_constant`](enum.Expression.html#method.eval_constant).
+    pub fn parse_number(&self) -> Option<f64> {
+        constant::parse_number(&self.spelling()?)
+    }
+
+    /// Whether the value is a number written with a fractional part or an exponent, such as `0.1`, `1.0` or `1e3`,
+    /// or a hexadecimal number with a fraction or a binary exponent, such as `0x1.8p1`, which can be written since Lua 5.2,
+    /// rather than as a whole number, such as `1` or `0xFF`
+    pub fn is_float(&self) -> bool {
+        self.spelling()
+            .map(|spelling| {
+                let spelling = spelling.to_ascii_lowercase();
+                if spelling.starts_with("0x") {
+                    // `e` is a hexadecimal digit, so the exponent is written with `p` instead
+                    spelling.contains(&['.', 'p'][..])
+                } else {
+                    !spelling.starts_with("0b") && spelling.contains(&['.', 'e'][..])
+                }
+            })
+            .unwrap_or(false)
+    }
+
+    /// Parses a value on its own from tokens, such as `{ a = 1 }`.
+    /// You probably want [`parse_value`](../fn.parse_value.html)
+    ///
+    /// # Errors
+    /// If the tokens passed are not a single valid value, an [`AstError`](enum.AstError.html) will be returned.
+    pub fn from_tokens(tokens: Vec<Token<'a>>) -> Result<Value<'a>, AstError<'a>> {
+        let (_, value) = parse_to_eof(parsers::ParseValue, tokens)?;
+        Ok(value)
+    }
+}
+
 /// A statement that stands alone
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum Stmt<'a> {
     #[cfg_attr(feature = "serde", serde(borrow))]
@@ -254,35 +990,184 @@ pub enum Stmt<'a> {
     /// Only available when the "roblox" feature flag is enabled.
     #[cfg(feature = "roblox")]
     TypeDeclaration(TypeDeclaration<'a>),
+    /// A compound assignment, such as `x += 1`
+    /// Only available when the "roblox" feature flag is enabled.
+    #[cfg(feature = "roblox")]
+    CompoundAssignment(CompoundAssignment<'a>),
+    /// A goto statement, such as `goto continue`
+    /// Only available when the "lua52" feature flag is enabled.
+    #[cfg(feature = "lua52")]
+    Goto(Goto<'a>),
+    /// A label that can be jumped to with `goto`, such as `::continue::`
+    /// Only available when the "lua52" feature flag is enabled.
+    #[cfg(feature = "lua52")]
+    Label(Label<'a>),
 }
 
-/// A node used before another in cases such as function calling
-/// The `("foo")` part of `("foo"):upper()`
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
-#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
-pub enum Prefix<'a> {
-    #[cfg_attr(feature = "serde", serde(borrow))]
-    /// A complicated expression, such as `("foo")`
-    Expression(Expression<'a>),
-    /// Just a name, such as `foo`
-    Name(TokenReference<'a>),
-}
+impl<'a> Stmt<'a> {
+    /// The indentation of the line the statement starts on, such as `"\t"`, taken from the trivia before it.
+    /// This is useful to indent new statements the same way as the ones around them.
+    /// Statements that don't start their line, such as the second one in `x = 1; y = 2`, have no indentation.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::Stmt;
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("do\n\tcall()\nend")?;
+    /// let call = match ast.nodes().iter_stmts().next() {
+    ///     Some(Stmt::Do(r#do)) => r#do.block().iter_stmts().next().unwrap(),
+    ///     _ => unreachable!(),
+    /// };
+    ///
+    /// assert_eq!(call.indentation(), "\t");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn indentation(&self) -> String {
+        use crate::visitors::Visit;
 
-/// The indexing of something, such as `x.y` or `x["y"]`
-/// Values of variants are the keys, such as `"y"`
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+        let mut first_token = FirstToken(None);
+        self.visit(&mut first_token);
+
+        let first_token = match first_token.0 {
+            Some(first_token) => first_token,
+            None => return String::new(),
+        };
+
+        let mut indentation = String::new();
+        // The trivia before a statement starts at the start of a line, or the start of the code
+        let mut line_start = true;
+
+        for trivia in first_token.leading_trivia() {
+            match &*trivia.token_type() {
+                TokenType::Whitespace { characters } => match characters.rfind('\n') {
+                    Some(new_line) => {
+                        indentation = characters[new_line + 1..].to_owned();
+                        line_start = true;
+                    }
+
+                    None if line_start => indentation.push_str(characters),
+                    None => {}
+                },
+
+                _ => {
+                    indentation.clear();
+                    line_start = false;
+                }
+            }
+        }
+
+        indentation
+    }
+}
+
+// Finds the first token of a node
+struct FirstToken<'a>(Option<TokenReference<'a>>);
+
+impl<'a> crate::visitors::Visitor<'a> for FirstToken<'a> {
+    fn visit_token(&mut self, token: &TokenReference<'a>) {
+        if self.0.is_none() {
+            self.0 = Some(token.clone());
+        }
+    }
+
+    // The tokens of a contained span aren't visited
+    fn visit_contained_span(&mut self, span: &ContainedSpan<'a>) {
+        if self.0.is_none() {
+            self.0 = Some(span.tokens().0.clone());
+        }
+    }
+}
+
+// The line comments directly above a node, with no blank line between them or after them, one line for each.
+// The `--` is taken off each comment, along with any more `-` after it, as in `--- summary`, and a space after those.
+fn doc_comment<'a, N: crate::visitors::Visit<'a>>(node: &N) -> Option<String> {
+    let mut first_token = FirstToken(None);
+    node.visit(&mut first_token);
+    let first_token = first_token.0?;
+
+    // Leading trivia always starts at the start of a line, and whatever's after the last new line is before the node
+    let mut lines = vec![Vec::new()];
+    for token in first_token.leading_trivia() {
+        lines.last_mut().unwrap().push(token);
+        if token.token_kind() == TokenKind::Whitespace && token.to_string().contains('\n') {
+            lines.push(Vec::new());
+        }
+    }
+
+    let before_node = lines.pop().unwrap();
+    if before_node
+        .iter()
+        .any(|token| token.token_kind() != TokenKind::Whitespace)
+    {
+        return None;
+    }
+
+    let mut comments = Vec::new();
+    for line in lines.iter().rev() {
+        let mut code = line
+            .iter()
+            .filter(|token| token.token_kind() != TokenKind::Whitespace);
+
+        match (code.next(), code.next()) {
+            (Some(comment), None) if comment.token_kind() == TokenKind::SingleLineComment => {
+                comments.push(comment.comment_text().unwrap())
+            }
+            _ => break,
+        }
+    }
+
+    if comments.is_empty() {
+        return None;
+    }
+
+    Some(
+        comments
+            .iter()
+            .rev()
+            .map(|comment| {
+                let text = comment.trim_start_matches('-');
+                text.strip_prefix(' ').unwrap_or(text).trim_end()
+            })
+            .join("\n"),
+    )
+}
+
+/// A node used before another in cases such as function calling
+/// The `("foo")` part of `("foo"):upper()`
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum Prefix<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    /// A complicated expression, such as `("foo")`
+    Expression(Expression<'a>),
+    /// Just a name, such as `foo`
+    #[display(fmt = "{:#}", "_0")]
+    Name(TokenReference<'a>),
+}
+
+/// The indexing of something, such as `x.y` or `x["y"]`
+/// Values of variants are the keys, such as `"y"`
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum Index<'a> {
     /// Indexing in the form of `x["y"]`
+    #[display(
+        fmt = "{:#}{}{:#}",
+        "brackets.tokens().0",
+        "expression",
+        "brackets.tokens().1"
+    )]
     Brackets {
         #[cfg_attr(feature = "serde", serde(borrow))]
         /// The `[...]` part of `["y"]`
+        #[visit(contains = "expression")]
         brackets: ContainedSpan<'a>,
         /// The `"y"` part of `["y"]`
         expression: Expression<'a>,
     },
 
     /// Indexing in the form of `x.y`
+    #[display(fmt = "{:#}{:#}", "dot", "name")]
     Dot {
         #[cfg_attr(feature = "serde", serde(borrow))]
         /// The `.` part of `.y`
@@ -293,27 +1178,49 @@ pub enum Index<'a> {
 }
 
 /// Arguments used for a function
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum FunctionArgs<'a> {
     /// Used when a function is called in the form of `call(1, 2, 3)`
+    #[display(
+        fmt = "{:#}{}{:#}",
+        "parentheses.tokens().0",
+        "arguments",
+        "parentheses.tokens().1"
+    )]
     Parentheses {
         /// The `1, 2, 3` part of `1, 2, 3`
         #[cfg_attr(feature = "serde", serde(borrow))]
         arguments: Punctuated<'a, Expression<'a>>,
         /// The `(...) part of (1, 2, 3)`
         #[node(full_range)]
+        #[visit(contains = "arguments")]
         parentheses: ContainedSpan<'a>,
     },
     /// Used when a function is called in the form of `call "foobar"`
     #[cfg_attr(feature = "serde", serde(borrow))]
+    #[display(fmt = "{:#}", "_0")]
     String(TokenReference<'a>),
     /// Used when a function is called in the form of `call { 1, 2, 3 }`
     TableConstructor(TableConstructor<'a>),
 }
 
 /// A numeric for loop, such as `for index = 1, 10 do end`
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[display(
+    fmt = "{:#}{:#}{:#}{}{:#}{}{}{}{:#}{}{:#}",
+    "for_token",
+    "index_variable",
+    "equal_token",
+    "start",
+    "start_end_comma",
+    "end",
+    "display_option(end_step_comma)",
+    "display_option(step)",
+    "do_token",
+    "block",
+    "end_token"
+)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct NumericFor<'a> {
     #[cfg_attr(feature = "serde", serde(borrow))]
@@ -336,6 +1243,11 @@ impl<'a> NumericFor<'a> {
         &self.for_token
     }
 
+    /// The `for` token, but mutable
+    pub fn for_token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.for_token
+    }
+
     /// The index identity, `index` in the initial example
     pub fn index_variable(&self) -> &TokenReference<'a> {
         &self.index_variable
@@ -346,6 +1258,11 @@ impl<'a> NumericFor<'a> {
         &self.equal_token
     }
 
+    /// The `=` token, but mutable
+    pub fn equal_token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.equal_token
+    }
+
     /// The starting point, `1` in the initial example
     pub fn start(&self) -> &Expression<'a> {
         &self.start
@@ -380,6 +1297,11 @@ impl<'a> NumericFor<'a> {
         &self.do_token
     }
 
+    /// The `do` token, but mutable
+    pub fn do_token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.do_token
+    }
+
     /// The code inside the for loop
     pub fn block(&self) -> &Block<'a> {
         &self.block
@@ -389,10 +1311,25 @@ impl<'a> NumericFor<'a> {
     pub fn end_token(&self) -> &TokenReference<'a> {
         &self.end_token
     }
+
+    /// The `end` token, but mutable
+    pub fn end_token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.end_token
+    }
 }
 
 /// A generic for loop, such as `for index, value in pairs(list) do end`
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[display(
+    fmt = "{:#}{}{:#}{}{:#}{}{:#}",
+    "for_token",
+    "names",
+    "in_token",
+    "expr_list",
+    "do_token",
+    "block",
+    "end_token"
+)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct GenericFor<'a> {
     #[cfg_attr(feature = "serde", serde(borrow))]
@@ -411,6 +1348,11 @@ impl<'a> GenericFor<'a> {
         &self.for_token
     }
 
+    /// The `for` token, but mutable
+    pub fn for_token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.for_token
+    }
+
     /// Returns the [`Punctuated`](punctuated/struct.Punctuated.html) sequence of names
     /// In `for index, value in pairs(list) do`, iterates over `index` and `value`
     pub fn names(&self) -> &Punctuated<'a, TokenReference<'a>> {
@@ -422,6 +1364,11 @@ impl<'a> GenericFor<'a> {
         &self.in_token
     }
 
+    /// The `in` token, but mutable
+    pub fn in_token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.in_token
+    }
+
     /// Returns the [`Punctuated`](punctuated/struct.Punctuated.html) sequence of the expressions looped over
     /// In `for index, value in pairs(list) do`, iterates over `pairs(list)`
     pub fn expr_list(&self) -> &Punctuated<'a, Expression<'a>> {
@@ -433,6 +1380,11 @@ impl<'a> GenericFor<'a> {
         &self.do_token
     }
 
+    /// The `do` token, but mutable
+    pub fn do_token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.do_token
+    }
+
     /// The code inside the for loop
     pub fn block(&self) -> &Block<'a> {
         &self.block
@@ -442,10 +1394,26 @@ impl<'a> GenericFor<'a> {
     pub fn end_token(&self) -> &TokenReference<'a> {
         &self.end_token
     }
+
+    /// The `end` token, but mutable
+    pub fn end_token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.end_token
+    }
 }
 
 /// An if statement
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[display(
+    fmt = "{:#}{}{:#}{}{}{}{}{:#}",
+    "if_token",
+    "condition",
+    "then_token",
+    "block",
+    "display_option(else_if.as_ref().map(join_vec))",
+    "display_option(else_token)",
+    "display_option(r#else)",
+    "end_token"
+)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct If<'a> {
     #[cfg_attr(feature = "serde", serde(borrow))]
@@ -466,6 +1434,11 @@ impl<'a> If<'a> {
         &self.if_token
     }
 
+    /// The `if` token, but mutable
+    pub fn if_token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.if_token
+    }
+
     /// The condition of the if statement, `condition` in `if condition then`
     pub fn condition(&self) -> &Expression<'a> {
         &self.condition
@@ -476,6 +1449,11 @@ impl<'a> If<'a> {
         &self.then_token
     }
 
+    /// The `then` token, but mutable
+    pub fn then_token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.then_token
+    }
+
     /// The block inside the initial if statement
     pub fn block(&self) -> &Block<'a> {
         &self.block
@@ -486,6 +1464,11 @@ impl<'a> If<'a> {
         self.else_token.as_ref()
     }
 
+    /// The `else` token if one exists, but mutable
+    pub fn else_token_mut(&mut self) -> Option<&mut TokenReference<'a>> {
+        self.else_token.as_mut()
+    }
+
     /// If there are `elseif` conditions, returns a vector of them
     /// Expression is the condition, block is the code if the condition is true
     // TODO: Make this return an iterator, and remove Option part entirely?
@@ -502,10 +1485,22 @@ impl<'a> If<'a> {
     pub fn end_token(&self) -> &TokenReference<'a> {
         &self.end_token
     }
+
+    /// The `end` token, but mutable
+    pub fn end_token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.end_token
+    }
 }
 
 /// An elseif block in a bigger [`If`](struct.If.html) statement
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[display(
+    fmt = "{:#}{}{:#}{}",
+    "else_if_token",
+    "condition",
+    "then_token",
+    "block"
+)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct ElseIf<'a> {
     #[cfg_attr(feature = "serde", serde(borrow))]
@@ -521,6 +1516,11 @@ impl<'a> ElseIf<'a> {
         &self.else_if_token
     }
 
+    /// The `elseif` token, but mutable
+    pub fn else_if_token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.else_if_token
+    }
+
     /// The condition of the `elseif`, `condition` in `elseif condition then`
     pub fn condition(&self) -> &Expression<'a> {
         &self.condition
@@ -531,6 +1531,11 @@ impl<'a> ElseIf<'a> {
         &self.then_token
     }
 
+    /// The `then` token, but mutable
+    pub fn then_token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.then_token
+    }
+
     /// The body of the `elseif`
     pub fn block(&self) -> &Block<'a> {
         &self.block
@@ -538,7 +1543,15 @@ impl<'a> ElseIf<'a> {
 }
 
 /// A while loop
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[display(
+    fmt = "{:#}{}{:#}{}{:#}",
+    "while_token",
+    "condition",
+    "do_token",
+    "block",
+    "end_token"
+)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct While<'a> {
     #[cfg_attr(feature = "serde", serde(borrow))]
@@ -555,6 +1568,11 @@ impl<'a> While<'a> {
         &self.while_token
     }
 
+    /// The `while` token, but mutable
+    pub fn while_token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.while_token
+    }
+
     /// The `condition` part of `while condition do`
     pub fn condition(&self) -> &Expression<'a> {
         &self.condition
@@ -565,6 +1583,11 @@ impl<'a> While<'a> {
         &self.do_token
     }
 
+    /// The `do` token, but mutable
+    pub fn do_token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.do_token
+    }
+
     /// The code inside the while loop
     pub fn block(&self) -> &Block<'a> {
         &self.block
@@ -574,10 +1597,16 @@ impl<'a> While<'a> {
     pub fn end_token(&self) -> &TokenReference<'a> {
         &self.end_token
     }
+
+    /// The `end` token, but mutable
+    pub fn end_token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.end_token
+    }
 }
 
 /// A repeat loop
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[display(fmt = "{:#}{}{:#}{}", "repeat_token", "block", "until_token", "until")]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Repeat<'a> {
     #[cfg_attr(feature = "serde", serde(borrow))]
@@ -593,6 +1622,11 @@ impl<'a> Repeat<'a> {
         &self.repeat_token
     }
 
+    /// The `repeat` token, but mutable
+    pub fn repeat_token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.repeat_token
+    }
+
     /// The code inside the `repeat` block
     pub fn block(&self) -> &Block<'a> {
         &self.block
@@ -603,6 +1637,11 @@ impl<'a> Repeat<'a> {
         &self.until_token
     }
 
+    /// The `until` token, but mutable
+    pub fn until_token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.until_token
+    }
+
     /// The condition for the `until` part
     pub fn until(&self) -> &Expression<'a> {
         &self.until
@@ -610,7 +1649,8 @@ impl<'a> Repeat<'a> {
 }
 
 /// A method call, such as `x:y()`
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[display(fmt = "{:#}{:#}{}", "colon_token", "name", "args")]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct MethodCall<'a> {
     #[cfg_attr(feature = "serde", serde(borrow))]
@@ -625,6 +1665,11 @@ impl<'a> MethodCall<'a> {
         &self.colon_token
     }
 
+    /// The `:` in `x:y()`, but mutable
+    pub fn colon_token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.colon_token
+    }
+
     /// The arguments of a method call, the `x, y, z` part of `method:call(x, y, z)`
     pub fn args(&self) -> &FunctionArgs<'a> {
         &self.args
@@ -637,7 +1682,7 @@ impl<'a> MethodCall<'a> {
 }
 
 /// Something being called
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum Call<'a> {
     #[cfg_attr(feature = "serde", serde(borrow))]
@@ -648,10 +1693,34 @@ pub enum Call<'a> {
 }
 
 /// A function body, everything except `function x` in `function x(a, b, c) call() end`
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[cfg_attr(
+    feature = "roblox",
+    display(
+        fmt = "{:#}{}{:#}{}{}{:#}",
+        "parameters_parantheses.tokens().0",
+        "join_type_specifiers(parameters, type_specifiers)",
+        "parameters_parantheses.tokens().1",
+        "display_option(return_type)",
+        "block",
+        "end_token"
+    )
+)]
+#[cfg_attr(
+    not(feature = "roblox"),
+    display(
+        fmt = "{:#}{}{:#}{}{:#}",
+        "parameters_parantheses.tokens().0",
+        "parameters",
+        "parameters_parantheses.tokens().1",
+        "block",
+        "end_token"
+    )
+)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct FunctionBody<'a> {
     #[cfg_attr(feature = "serde", serde(borrow))]
+    #[visit(contains = "parameters")]
     parameters_parantheses: ContainedSpan<'a>,
     parameters: Punctuated<'a, Parameter<'a>>,
 
@@ -679,6 +1748,99 @@ impl<'a> FunctionBody<'a> {
         self.parameters.iter()
     }
 
+    /// The names of the parameters, such as `a` and `b` in `function(a, b, ...)`.
+    /// `...` isn't included, use [`is_variadic`](#method.is_variadic) to check for it.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::Stmt;
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("local function f(a, b, ...) end")?;
+    /// let body = match ast.nodes().iter_stmts().next() {
+    ///     Some(Stmt::LocalFunction(local_function)) => local_function.func_body(),
+    ///     _ => unreachable!(),
+    /// };
+    ///
+    /// assert_eq!(body.parameter_names(), vec!["a", "b"]);
+    /// assert!(body.is_variadic());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parameter_names(&self) -> Vec<Cow<'a, str>> {
+        self.parameters
+            .iter()
+            .filter_map(|parameter| match parameter {
+                Parameter::Name(name) => name.as_identifier(),
+                Parameter::Ellipse(_) => None,
+            })
+            .collect()
+    }
+
+    /// The parameters with the same name as one before them, such as the second `a` in `function(a, b, a)`.
+    /// This is allowed, with the last parameter of a name being the one used, but it's usually a mistake.
+    pub fn duplicate_parameters(&self) -> Vec<&TokenReference<'a>> {
+        let mut names = std::collections::HashSet::new();
+
+        self.parameters
+            .iter()
+            .filter_map(|parameter| match parameter {
+                Parameter::Name(name) if !names.insert(name.to_string()) => Some(name),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The `return` at the end of the function's body, if it doesn't give back any values, such as in
+    /// `function() print(1) return end`. The function would end there anyway, so the `return` does nothing.
+    /// A `return` with values, or one inside another block such as `if x then return end`, isn't redundant.
+    pub fn redundant_return(&self) -> Option<&Return<'a>> {
+        match self.block.last_stmts() {
+            Some(LastStmt::Return(r#return)) if r#return.returns().is_empty() => Some(r#return),
+            _ => None,
+        }
+    }
+
+    /// Whether the last parameter is `...`, so the function takes any number of arguments
+    pub fn is_variadic(&self) -> bool {
+        match self.parameters.iter().last() {
+            Some(Parameter::Ellipse(_)) => true,
+            Some(Parameter::Name(_)) => false,
+            None => false,
+        }
+    }
+
+    /// The number of arguments the function takes, such as [`Arity::Fixed(2)`](enum.Arity.html#variant.Fixed)
+    /// for `function(a, b)` and [`Arity::Variadic(1)`](enum.Arity.html#variant.Variadic) for `function(a, ...)`.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::{Arity, Stmt};
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("local function f(a, ...) end")?;
+    /// let body = match ast.nodes().iter_stmts().next() {
+    ///     Some(Stmt::LocalFunction(local_function)) => local_function.func_body(),
+    ///     _ => unreachable!(),
+    /// };
+    ///
+    /// assert_eq!(body.arity(), Arity::Variadic(1));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn arity(&self) -> Arity {
+        let fixed = self
+            .parameters
+            .iter()
+            .filter(|parameter| match parameter {
+                Parameter::Name(_) => true,
+                Parameter::Ellipse(_) => false,
+            })
+            .count();
+
+        if self.is_variadic() {
+            Arity::Variadic(fixed)
+        } else {
+            Arity::Fixed(fixed)
+        }
+    }
+
     /// The code of a function body
     pub fn block(&self) -> &Block<'a> {
         &self.block
@@ -689,6 +1851,11 @@ impl<'a> FunctionBody<'a> {
         &self.end_token
     }
 
+    /// The `end` token, but mutable
+    pub fn end_token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.end_token
+    }
+
     /// The type specifiers of the variables, in the order that they were assigned.
     /// `(foo: number, bar, baz: boolean)` returns an iterator containing:
     /// `Some(TypeSpecifier(number)), None, Some(TypeSpecifier(boolean))`
@@ -706,20 +1873,31 @@ impl<'a> FunctionBody<'a> {
     }
 }
 
+/// The number of arguments a function takes. Refer to [`FunctionBody::arity`](struct.FunctionBody.html#method.arity).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Arity {
+    /// Exactly this many arguments, such as 2 for `function(a, b)`
+    Fixed(usize),
+    /// This many named arguments followed by `...`, such as 1 for `function(a, ...)`
+    Variadic(usize),
+}
+
 /// A parameter in a function declaration
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum Parameter<'a> {
     #[cfg_attr(feature = "serde", serde(borrow))]
     /// The `...` vararg syntax, such as `function x(...)`
+    #[display(fmt = "{:#}", "_0")]
     Ellipse(TokenReference<'a>),
     /// A name parameter, such as `function x(a, b, c)`
+    #[display(fmt = "{:#}", "_0")]
     Name(TokenReference<'a>),
 }
 
 /// A suffix in certain cases, such as `:y()` in `x:y()`
 /// Can be stacked on top of each other, such as in `x()()()`
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum Suffix<'a> {
     #[cfg_attr(feature = "serde", serde(borrow))]
@@ -730,7 +1908,8 @@ pub enum Suffix<'a> {
 }
 
 /// A complex expression used by [`Var`](enum.Var.html), consisting of both a prefix and suffixes
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[display(fmt = "{}{}", "prefix", "join_vec(suffixes)")]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct VarExpression<'a> {
     #[cfg_attr(feature = "serde", serde(borrow))]
@@ -751,18 +1930,20 @@ impl<'a> VarExpression<'a> {
 }
 
 /// Used in [`Assignment`s](struct.Assignment.html) and [`Value`s](enum.Value.html)
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum Var<'a> {
     #[cfg_attr(feature = "serde", serde(borrow))]
     /// An expression, such as `x.y.z` or `x()`
     Expression(VarExpression<'a>),
     /// A literal identifier, such as `x`
+    #[display(fmt = "{:#}", "_0")]
     Name(TokenReference<'a>),
 }
 
 /// An assignment, such as `x = y`. Not used for [`LocalAssignment`s](struct.LocalAssignment.html)
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[display(fmt = "{}{:#}{}", "var_list", "equal_token", "expr_list")]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Assignment<'a> {
     #[cfg_attr(feature = "serde", serde(borrow))]
@@ -783,6 +1964,11 @@ impl<'a> Assignment<'a> {
         &self.equal_token
     }
 
+    /// The `=` token in between `x = y`, but mutable
+    pub fn equal_token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.equal_token
+    }
+
     /// Returns the [`Punctuated`](punctuated/struct.Punctuated.html) sequence over the variables being assigned to.
     /// This is the `x, y["a"]` part of `x, y["a"] = 1, 2`
     pub fn var_list(&self) -> &Punctuated<'a, Var<'a>> {
@@ -791,9 +1977,33 @@ impl<'a> Assignment<'a> {
 }
 
 /// A declaration of a local function, such as `local function x() end`
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[cfg_attr(
+    feature = "roblox",
+    display(
+        fmt = "{}{:#}{:#}{:#}{}",
+        "join_vec(attributes)",
+        "local_token",
+        "function_token",
+        "name",
+        "func_body"
+    )
+)]
+#[cfg_attr(
+    not(feature = "roblox"),
+    display(
+        fmt = "{:#}{:#}{:#}{}",
+        "local_token",
+        "function_token",
+        "name",
+        "func_body"
+    )
+)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct LocalFunction<'a> {
+    #[cfg(feature = "roblox")]
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    attributes: Vec<Attribute<'a>>,
     #[cfg_attr(feature = "serde", serde(borrow))]
     local_token: TokenReference<'a>,
     function_token: TokenReference<'a>,
@@ -802,16 +2012,33 @@ pub struct LocalFunction<'a> {
 }
 
 impl<'a> LocalFunction<'a> {
+    /// The attributes on the function, such as `@native` in `@native local function x() end`.
+    /// Only available when the "roblox" feature flag is enabled.
+    #[cfg(feature = "roblox")]
+    pub fn attributes(&self) -> &[Attribute<'a>] {
+        &self.attributes
+    }
+
     /// The `local` token
     pub fn local_token(&self) -> &TokenReference<'a> {
         &self.local_token
     }
 
+    /// The `local` token, but mutable
+    pub fn local_token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.local_token
+    }
+
     /// The `function` token
     pub fn function_token(&self) -> &TokenReference<'a> {
         &self.function_token
     }
 
+    /// The `function` token, but mutable
+    pub fn function_token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.function_token
+    }
+
     /// The function body, everything except `local function x` in `local function x(a, b, c) call() end`
     pub fn func_body(&self) -> &FunctionBody<'a> {
         &self.func_body
@@ -821,10 +2048,36 @@ impl<'a> LocalFunction<'a> {
     pub fn name(&self) -> &TokenReference<'a> {
         &self.name
     }
+
+    /// The comment documenting the function, made of the line comments directly above it.
+    /// Refer to [`FunctionDeclaration::doc_comment`](struct.FunctionDeclaration.html#method.doc_comment) for more details.
+    pub fn doc_comment(&self) -> Option<String> {
+        doc_comment(self)
+    }
 }
 
 /// An assignment to a local variable, such as `local x = 1`
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[cfg_attr(
+    feature = "roblox",
+    display(
+        fmt = "{:#}{}{}{}",
+        "local_token",
+        "join_type_specifiers(name_list, type_specifiers)",
+        "display_option(equal_token)",
+        "expr_list"
+    )
+)]
+#[cfg_attr(
+    not(feature = "roblox"),
+    display(
+        fmt = "{:#}{}{}{}",
+        "local_token",
+        "name_list",
+        "display_option(equal_token)",
+        "expr_list"
+    )
+)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct LocalAssignment<'a> {
     #[cfg_attr(feature = "serde", serde(borrow))]
@@ -843,11 +2096,21 @@ impl<'a> LocalAssignment<'a> {
         &self.local_token
     }
 
+    /// The `local` token, but mutable
+    pub fn local_token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.local_token
+    }
+
     /// The `=` token in between `local x = y`, if one exists
     pub fn equal_token(&self) -> Option<&TokenReference<'a>> {
         self.equal_token.as_ref()
     }
 
+    /// The `=` token in between `local x = y`, if one exists, but mutable
+    pub fn equal_token_mut(&mut self) -> Option<&mut TokenReference<'a>> {
+        self.equal_token.as_mut()
+    }
+
     /// Returns the [`Punctuated`](punctuated/struct.Punctuated.html) sequence of the expressions being assigned.
     /// This is the `1, 2` part of `local x, y = 1, 2`
     pub fn expr_list(&self) -> &Punctuated<'a, Expression<'a>> {
@@ -878,7 +2141,8 @@ impl<'a> LocalAssignment<'a> {
 
 /// A `do` block, such as `do ... end`
 /// This is not used for things like `while true do end`, only those on their own
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[display(fmt = "{:#}{}{:#}", "do_token", "block", "end_token")]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Do<'a> {
     #[cfg_attr(feature = "serde", serde(borrow))]
@@ -893,6 +2157,11 @@ impl<'a> Do<'a> {
         &self.do_token
     }
 
+    /// The `do` token, but mutable
+    pub fn do_token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.do_token
+    }
+
     /// The code inside the `do ... end`
     pub fn block(&self) -> &Block<'a> {
         &self.block
@@ -902,10 +2171,21 @@ impl<'a> Do<'a> {
     pub fn end_token(&self) -> &TokenReference<'a> {
         &self.end_token
     }
+
+    /// The `end` token, but mutable
+    pub fn end_token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.end_token
+    }
+
+    /// Takes the code out of the `do ... end`, the reverse of [`Block::wrap_in_do`](struct.Block.html#method.wrap_in_do)
+    pub fn unwrap(self) -> Block<'a> {
+        self.block
+    }
 }
 
 /// A function being called, such as `call()`
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[display(fmt = "{}{}", "prefix", "join_vec(suffixes)")]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct FunctionCall<'a> {
     #[cfg_attr(feature = "serde", serde(borrow))]
@@ -923,10 +2203,90 @@ impl<'a> FunctionCall<'a> {
     pub fn iter_suffixes(&self) -> impl Iterator<Item = &Suffix<'a>> {
         self.suffixes.iter()
     }
+
+    /// The arguments of every call in the chain, in the order they're called, skipping indexes.
+    /// For `builder:a(1).b:c(2)`, this is `(1)` and then `(2)`. Method calls and calls made
+    /// directly, such as `f(1)(2)`, are both included.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::Stmt;
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("builder:a(1):b{2}")?;
+    /// let call = match ast.nodes().iter_stmts().next() {
+    ///     Some(Stmt::FunctionCall(call)) => call,
+    ///     _ => unreachable!(),
+    /// };
+    ///
+    /// let arguments: Vec<_> = call.calls().map(|args| args.to_string()).collect();
+    /// assert_eq!(arguments, vec!["(1)", "{2}"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn calls(&self) -> impl Iterator<Item = &FunctionArgs<'a>> {
+        self.suffixes.iter().filter_map(|suffix| match suffix {
+            Suffix::Call(Call::AnonymousCall(args)) => Some(args),
+            Suffix::Call(Call::MethodCall(method_call)) => Some(method_call.args()),
+            Suffix::Index(_) => None,
+        })
+    }
+
+    /// The name of the function being called, if it's only names separated by dots, such as `table.insert`
+    /// in `table.insert(t, 1)`. Method calls include the method name, such as `a:b` in `a:b()`.
+    /// Returns `None` for anything else, such as `(x)()`, `t[1]()`, or `f()()`,
+    /// and for calls put together by hand that don't end in a call.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::Stmt;
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("table.insert(t, 1)")?;
+    /// let call = match ast.nodes().iter_stmts().next() {
+    ///     Some(Stmt::FunctionCall(call)) => call,
+    ///     _ => unreachable!(),
+    /// };
+    ///
+    /// assert_eq!(call.simple_name(), Some("table.insert".to_owned()));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn simple_name(&self) -> Option<String> {
+        let mut name = match &self.prefix {
+            Prefix::Name(name) => name.to_string(),
+            Prefix::Expression(_) => return None,
+        };
+
+        let (call, indexes) = self.suffixes.split_last()?;
+
+        for index in indexes {
+            match index {
+                Suffix::Index(Index::Dot { name: index, .. }) => {
+                    name.push('.');
+                    name.push_str(&index.to_string());
+                }
+
+                _ => return None,
+            }
+        }
+
+        match call {
+            Suffix::Call(Call::AnonymousCall(_)) => {}
+            Suffix::Call(Call::MethodCall(method_call)) => {
+                name.push(':');
+                name.push_str(&method_call.name().to_string());
+            }
+            Suffix::Index(_) => return None,
+        }
+
+        Some(name)
+    }
 }
 
 /// A function name when being [declared](struct.FunctionDeclaration.html)
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[display(
+    fmt = "{}{}",
+    "names",
+    "display_option(colon_name.as_ref().map(display_tuple))"
+)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct FunctionName<'a> {
     #[cfg_attr(feature = "serde", serde(borrow))]
@@ -935,6 +2295,11 @@ pub struct FunctionName<'a> {
 }
 
 impl<'a> FunctionName<'a> {
+    /// The colon between the name and the method name if one exists, the `:` part of `function x:y() end`
+    pub fn method_colon(&self) -> Option<&TokenReference<'a>> {
+        Some(&self.colon_name.as_ref()?.0)
+    }
+
     /// A method name if one exists, the `y` part of `function x:y() end`
     pub fn method_name(&self) -> Option<&TokenReference<'a>> {
         Some(&self.colon_name.as_ref()?.1)
@@ -949,9 +2314,26 @@ impl<'a> FunctionName<'a> {
 
 /// A normal function declaration, supports simple declarations like `function x() end`
 /// as well as complicated declarations such as `function x.y.z:a() end`
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[cfg_attr(
+    feature = "roblox",
+    display(
+        fmt = "{}{:#}{}{}",
+        "join_vec(attributes)",
+        "function_token",
+        "name",
+        "body"
+    )
+)]
+#[cfg_attr(
+    not(feature = "roblox"),
+    display(fmt = "{:#}{}{}", "function_token", "name", "body")
+)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct FunctionDeclaration<'a> {
+    #[cfg(feature = "roblox")]
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    attributes: Vec<Attribute<'a>>,
     #[cfg_attr(feature = "serde", serde(borrow))]
     function_token: TokenReference<'a>,
     name: FunctionName<'a>,
@@ -959,11 +2341,23 @@ pub struct FunctionDeclaration<'a> {
 }
 
 impl<'a> FunctionDeclaration<'a> {
+    /// The attributes on the function, such as `@native` in `@native function x() end`.
+    /// Only available when the "roblox" feature flag is enabled.
+    #[cfg(feature = "roblox")]
+    pub fn attributes(&self) -> &[Attribute<'a>] {
+        &self.attributes
+    }
+
     /// The `function` token
     pub fn function_token(&self) -> &TokenReference<'a> {
         &self.function_token
     }
 
+    /// The `function` token, but mutable
+    pub fn function_token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.function_token
+    }
+
     /// The body of the function
     pub fn body(&self) -> &FunctionBody<'a> {
         &self.body
@@ -973,11 +2367,33 @@ impl<'a> FunctionDeclaration<'a> {
     pub fn name(&self) -> &FunctionName<'a> {
         &self.name
     }
+
+    /// The comment documenting the function, made of the line comments directly above it, one line for each.
+    /// A blank line, or any other code or comment, ends the comment, and a blank line between it and the function
+    /// means there's no comment. The `--` is taken off each line, along with any more `-` after it, as in `--- summary`,
+    /// and a space after those.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::Stmt;
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("-- Not this\n\n--- Adds two numbers\n-- @return number\nfunction add(a, b) end")?;
+    /// let add = match ast.nodes().iter_stmts().next() {
+    ///     Some(Stmt::FunctionDeclaration(declaration)) => declaration,
+    ///     _ => unreachable!(),
+    /// };
+    ///
+    /// assert_eq!(add.doc_comment().as_deref(), Some("Adds two numbers\n@return number"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn doc_comment(&self) -> Option<String> {
+        doc_comment(self)
+    }
 }
 
 macro_rules! make_op {
-    ($enum:ident, $(#[$outer:meta])* { $($operator:ident,)+ }) => {
-        #[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+    ($enum:ident, $(#[$outer:meta])* { $($(#[$inner:meta])* $operator:ident,)+ }) => {
+        #[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
         #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
         #[visit(skip_visit_self)]
         $(#[$outer])*
@@ -985,6 +2401,8 @@ macro_rules! make_op {
             #[cfg_attr(feature = "serde", serde(borrow))]
             $(
                 #[allow(missing_docs)]
+                #[display(fmt = "{:#}", "_0")]
+                $(#[$inner])*
                 $operator(TokenReference<'a>),
             )+
         }
@@ -1009,6 +2427,24 @@ make_op!(BinOp,
         TildeEqual,
         TwoDots,
         TwoEqual,
+        #[doc = "Only available when the \"lua53\" feature flag is enabled."]
+        #[cfg(feature = "lua53")]
+        Ampersand,
+        #[doc = "Only available when the \"lua53\" feature flag is enabled."]
+        #[cfg(feature = "lua53")]
+        DoubleSlash,
+        #[doc = "Only available when the \"lua53\" feature flag is enabled."]
+        #[cfg(feature = "lua53")]
+        Pipe,
+        #[doc = "Only available when the \"lua53\" feature flag is enabled."]
+        #[cfg(feature = "lua53")]
+        Tilde,
+        #[doc = "Only available when the \"lua53\" feature flag is enabled."]
+        #[cfg(feature = "lua53")]
+        TwoGreaterThan,
+        #[doc = "Only available when the \"lua53\" feature flag is enabled."]
+        #[cfg(feature = "lua53")]
+        TwoLessThan,
     }
 );
 
@@ -1018,9 +2454,108 @@ make_op!(UnOp,
         Minus,
         Not,
         Hash,
+        #[doc = "Only available when the \"lua53\" feature flag is enabled."]
+        #[cfg(feature = "lua53")]
+        Tilde,
+    }
+);
+
+make_op!(CompoundOp,
+    #[doc = "Operators used by a [compound assignment](types/struct.CompoundAssignment.html), such as `+=`"]
+    #[doc = "Only available when the \"roblox\" feature flag is enabled."]
+    #[cfg(feature = "roblox")]
+    {
+        PlusEqual,
+        MinusEqual,
+        StarEqual,
+        SlashEqual,
+        PercentEqual,
+        CaretEqual,
+        TwoDotsEqual,
     }
 );
 
+/// A goto statement, such as `goto continue`, jumping to the [label](struct.Label.html) with the same name.
+/// Only available when the "lua52" feature flag is enabled.
+#[cfg(feature = "lua52")]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[display(fmt = "{:#}{:#}", "goto_token", "label_name")]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct Goto<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    goto_token: TokenReference<'a>,
+    label_name: TokenReference<'a>,
+}
+
+#[cfg(feature = "lua52")]
+impl<'a> Goto<'a> {
+    /// The `goto` token. `goto` is only a keyword in a goto statement, so it's an identifier rather than a symbol.
+    pub fn goto_token(&self) -> &TokenReference<'a> {
+        &self.goto_token
+    }
+
+    /// The `goto` token, but mutable
+    pub fn goto_token_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.goto_token
+    }
+
+    /// The name of the label to jump to, `continue` in `goto continue`
+    pub fn label_name(&self) -> &TokenReference<'a> {
+        &self.label_name
+    }
+
+    /// The name of the label to jump to, but mutable
+    pub fn label_name_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.label_name
+    }
+}
+
+/// A label that can be jumped to with a [goto statement](struct.Goto.html), such as `::continue::`
+/// Only available when the "lua52" feature flag is enabled.
+#[cfg(feature = "lua52")]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[display(fmt = "{:#}{:#}{:#}", "left_colons", "name", "right_colons")]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct Label<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    left_colons: TokenReference<'a>,
+    name: TokenReference<'a>,
+    right_colons: TokenReference<'a>,
+}
+
+#[cfg(feature = "lua52")]
+impl<'a> Label<'a> {
+    /// The `::` token before the name
+    pub fn left_colons(&self) -> &TokenReference<'a> {
+        &self.left_colons
+    }
+
+    /// The `::` token before the name, but mutable
+    pub fn left_colons_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.left_colons
+    }
+
+    /// The name of the label, `continue` in `::continue::`
+    pub fn name(&self) -> &TokenReference<'a> {
+        &self.name
+    }
+
+    /// The name of the label, but mutable
+    pub fn name_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.name
+    }
+
+    /// The `::` token after the name
+    pub fn right_colons(&self) -> &TokenReference<'a> {
+        &self.right_colons
+    }
+
+    /// The `::` token after the name, but mutable
+    pub fn right_colons_mut(&mut self) -> &mut TokenReference<'a> {
+        &mut self.right_colons
+    }
+}
+
 /// An error that occurs when creating the ast *after* tokenizing
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
@@ -1082,63 +2617,9 @@ impl<'a> Ast<'a> {
     /// More likely, if the tokens pass are invalid Lua 5.1 code, an
     /// UnexpectedToken error will be returned.
     pub fn from_tokens(tokens: Vec<Token<'a>>) -> Result<Ast<'a>, AstError<'a>> {
-        if *tokens.last().ok_or(AstError::Empty)?.token_type() != TokenType::Eof {
-            Err(AstError::NoEof)
-        } else {
-            let tokens = Arc::new(Arena::from_iter(tokens));
-
-            let mut state = ParserState::new(Arc::clone(&tokens));
-
-            if tokens
-                .iter()
-                .filter(|token| !token.1.token_type().ignore())
-                .count()
-                == 1
-            {
-                // Entirely comments/whitespace
-                return Ok(Ast {
-                    nodes: Block {
-                        stmts: Vec::new(),
-                        last_stmt: None,
-                    },
-                    tokens,
-                });
-            }
-
-            // ParserState has to have at least 2 tokens, the last being an EOF, thus unwrap() can't fail
-            if state.peek().token_type().ignore() {
-                state = state.advance().unwrap();
-            }
-
-            match parsers::ParseBlock.parse(state.clone()) {
-                Ok((state, block)) => {
-                    if state.index == tokens.len() - 1 {
-                        Ok(Ast {
-                            tokens,
-                            nodes: block,
-                        })
-                    } else {
-                        Err(AstError::UnexpectedToken {
-                            token: (*state.peek()).to_owned(),
-                            additional: Some(Cow::Borrowed("leftover token")),
-                        })
-                    }
-                }
-
-                Err(InternalAstError::NoMatch) => Err(AstError::UnexpectedToken {
-                    token: (*state.peek()).to_owned(),
-                    additional: None,
-                }),
-
-                Err(InternalAstError::UnexpectedToken { token, additional }) => {
-                    Err(AstError::UnexpectedToken {
-                        token: (*token).to_owned(),
-                        additional: additional.map(Cow::Borrowed),
-                    })
-                }
-            }
-        }
-    }
+        let (tokens, nodes) = parse_to_eof(parsers::ParseBlock, tokens)?;
+        Ok(Ast { nodes, tokens })
+    }
 
     /// The entire code of the function
     ///
@@ -1157,21 +2638,835 @@ impl<'a> Ast<'a> {
         &mut self.nodes
     }
 
+    /// The end of file token, whose [leading trivia](../tokenizer/enum.TokenReference.html#method.leading_trivia)
+    /// is the whitespace and comments after the last statement.
+    pub fn eof(&self) -> TokenReference<'a> {
+        let (_, index) = self
+            .tokens
+            .get_unknown_gen(self.tokens.len() - 1)
+            .expect("arena doesn't have an eof token?");
+
+        TokenReference::Borrowed {
+            arena: Arc::clone(&self.tokens),
+            index,
+        }
+    }
+
+    // Where the code starts, which is only after the start of the file if it was parsed with an offset
+    fn start_position(&self) -> Position {
+        let (first, _) = self
+            .tokens
+            .get_unknown_gen(0)
+            .expect("arena doesn't have an eof token?");
+
+        first.start_position()
+    }
+
+    /// An indented [S-expression](https://en.wikipedia.org/wiki/S-expression) of the nodes in the Ast,
+    /// with the text of their tokens. Meant for debugging, as it is much shorter than the `Debug` output.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("x = 1")?;
+    /// assert_eq!(ast.to_sexpr(), r#"(Block
+    ///   (Stmt
+    ///     (Assignment
+    ///       (Var "x")
+    ///       "="
+    ///       (Expression
+    ///         (Value "1")))))"#);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_sexpr(&self) -> String {
+        use crate::visitors::Visit;
+
+        let mut sexpr = sexpr::SExpr::default();
+        self.nodes.visit(&mut sexpr);
+        sexpr.into_string()
+    }
+
+    /// Folds over the nodes and tokens of the code in the order they appear, starting with `initial`
+    /// and passing what the function returned for each [event](fold/enum.VisitEvent.html) on to the next one.
+    /// Every node is entered before and exited after the nodes and tokens inside of it,
+    /// and the end of file token comes last.
+    /// This is an alternative to implementing [`Visitor`](../visitors/trait.Visitor.html) for simple analyses.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::fold::{NodeRef, VisitEvent};
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("local x = 1 local y = 2")?;
+    /// let locals = ast.fold(0, |count, event| match event {
+    ///     VisitEvent::Enter(NodeRef::LocalAssignment(_)) => count + 1,
+    ///     _ => count,
+    /// });
+    ///
+    /// assert_eq!(locals, 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn fold<S, F>(&self, initial: S, f: F) -> S
+    where
+        F: for<'b> FnMut(S, fold::VisitEvent<'a, 'b>) -> S,
+    {
+        use crate::visitors::{Visit, Visitor};
+
+        let mut fold = fold::Fold::new(initial, f);
+        self.nodes.visit(&mut fold);
+        fold.visit_token(&self.eof());
+        fold.into_state()
+    }
+
+    /// Every call to `require` in the code, such as `require("module")`, in the order they appear.
+    /// Calls that don't pass a single string literal, such as `require(script.Parent.module)`,
+    /// are included as [dynamic](requires/struct.Require.html#method.is_dynamic).
+    /// To find calls to other module loaders, use [`requires_with`](#method.requires_with).
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("local a = require('a') local b = require(path)")?;
+    /// let requires = ast.requires();
+    /// assert_eq!(requires[0].module_name().unwrap(), "a");
+    /// assert!(requires[1].is_dynamic());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn requires(&self) -> Vec<requires::Require<'a>> {
+        self.requires_with(&["require"])
+    }
+
+    /// Every call to any of the given module loaders in the code, such as `import("module")`.
+    /// Refer to [`requires`](#method.requires) for more details.
+    pub fn requires_with(&self, loaders: &[&str]) -> Vec<requires::Require<'a>> {
+        requires::RequireVisitor::new(loaders).find(&self.nodes)
+    }
+
+    /// Every call in the code that loads code while it's running, in the order they appear,
+    /// for auditing code that should be sandboxed. This looks for the [default loaders](code_loading/constant.DEFAULT_LOADERS.html):
+    /// `load`, `loadstring`, `dofile`, and `require` when it isn't passed a single string literal.
+    /// To look for other loaders or change their severity, use [`code_loads_with`](#method.code_loads_with).
+    ///
+    /// ```rust
+    /// # use full_moon::ast::code_loading::Severity;
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("local a = require('a') local f = loadstring(code)")?;
+    /// let code_loads = ast.code_loads();
+    /// assert_eq!(code_loads.len(), 1);
+    /// assert_eq!(code_loads[0].loader().to_string(), "loadstring");
+    /// assert_eq!(code_loads[0].severity(), Severity::High);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn code_loads(&self) -> Vec<code_loading::CodeLoad<'a>> {
+        self.code_loads_with(code_loading::DEFAULT_LOADERS)
+    }
+
+    /// Every call in the code to any of the given loaders.
+    /// Refer to [`code_loads`](#method.code_loads) for more details.
+    pub fn code_loads_with(
+        &self,
+        loaders: &[code_loading::Loader],
+    ) -> Vec<code_loading::CodeLoad<'a>> {
+        code_loading::find_code_loads(&self.nodes, loaders)
+    }
+
+    /// The innermost [statement](enum.Stmt.html) the code at the position is part of, such as to select it in an editor.
+    /// Code inside the body of a loop gives the statement in the body, not the loop.
+    /// Returns `None` if the position isn't inside any statement, such as when it's in the whitespace between them
+    /// or in a [last statement](enum.LastStmt.html) of the outermost block.
+    /// To find the outermost statement instead, use [`enclosing_statement_with`](#method.enclosing_statement_with).
+    ///
+    /// ```rust
+    /// # use full_moon::tokenizer::Position;
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("while true do\n\tprint(1)\nend")?;
+    /// // The `1`
+    /// let stmt = ast.enclosing_statement(Position::new(21, 2, 8)).unwrap();
+    /// assert_eq!(stmt.to_string().trim(), "print(1)");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn enclosing_statement(&self, position: Position) -> Option<Stmt<'a>> {
+        self.enclosing_statement_with(position, enclosing::Depth::Innermost)
+    }
+
+    /// The statement the code at the position is part of, either the innermost or outermost one.
+    /// Refer to [`enclosing_statement`](#method.enclosing_statement) for more details.
+    pub fn enclosing_statement_with(
+        &self,
+        position: Position,
+        depth: enclosing::Depth,
+    ) -> Option<Stmt<'a>> {
+        enclosing::EnclosingStatementVisitor::new(position, depth).find(&self.nodes)
+    }
+
+    /// Every statement that writes to a variable with the given name, in the order they appear.
+    /// This includes assignments such as `x = 1` or `x, y = 1, 2`, local assignments such as `local x = 1`,
+    /// the variables of `for` loops, and function declarations such as `local function x() end`.
+    /// Assigning to a field, such as `t.x = 1`, isn't a write to `x`.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("local x = 1\nx, y = 2, 3")?;
+    /// let writes = ast.assignments_to("x");
+    /// assert_eq!(writes.len(), 2);
+    /// assert_eq!(writes[1].name().start_position().bytes(), 12);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn assignments_to(&self, name: &str) -> Vec<assignments::Write<'a>> {
+        assignments::WriteVisitor::new(name).find(&self.nodes)
+    }
+
+    /// Every use of a variable name in the code, such as `x` in `print(x)`, in the order they appear,
+    /// along with the local variable it refers to. Names that don't refer to a local are
+    /// [globals](scopes/struct.Reference.html#method.is_global).
+    /// Names used in the `until` condition of a `repeat` can refer to locals declared in its body.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("local x = 1 print(x, y)")?;
+    /// let references = ast.references();
+    /// assert_eq!(references[0].name().to_string(), "print");
+    /// assert!(references[0].is_global());
+    /// assert_eq!(references[1].local().unwrap().start_position().bytes(), 6);
+    /// assert!(references[2].is_global());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn references(&self) -> Vec<scopes::Reference<'a>> {
+        scopes::ScopeVisitor::new().resolve(&self.nodes)
+    }
+
+    /// Every local variable declared with the same name as one already in scope from an enclosing block,
+    /// along with the local it shadows, in the order they're declared.
+    /// Parameters, for loop variables and local functions count as locals too, but redeclaring a local
+    /// in the same block, such as `local x = 1 local x = 2`, doesn't count.
+    /// Shadows made on purpose can be told apart with [`Shadow::is_intentional`](scopes/struct.Shadow.html#method.is_intentional).
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("local x, _ = 1, 2 do local x, _ = 3, 4 end")?;
+    /// let shadows: Vec<_> = ast
+    ///     .shadowed_locals()
+    ///     .into_iter()
+    ///     .filter(|shadow| !shadow.is_intentional())
+    ///     .collect();
+    ///
+    /// assert_eq!(shadows.len(), 1);
+    /// assert_eq!(shadows[0].local().start_position().bytes(), 27);
+    /// assert_eq!(shadows[0].shadowed().start_position().bytes(), 6);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn shadowed_locals(&self) -> Vec<scopes::Shadow<'a>> {
+        scopes::ScopeVisitor::new().find_shadows(&self.nodes)
+    }
+
+    /// Every assignment to a global that isn't [known to exist](scopes/constant.STANDARD_GLOBALS.html),
+    /// which in Lua 5.1 creates the global, often by accident when `local` was forgotten.
+    /// This is the name assigned to, such as `x` in `x = 1` or `function x() end`, where `x` isn't a local in scope.
+    /// Assigning to fields, such as `x.y = 1` or `function x.y() end`, doesn't count.
+    /// To allow other globals, use [`implicit_globals_with`](#method.implicit_globals_with).
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("local x x = 1 y = 2 print(y)")?;
+    /// let globals = ast.implicit_globals();
+    /// assert_eq!(globals.len(), 1);
+    /// assert_eq!(globals[0].to_string(), "y");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn implicit_globals(&self) -> Vec<TokenReference<'a>> {
+        self.implicit_globals_with(scopes::STANDARD_GLOBALS)
+    }
+
+    /// Every assignment to a global that isn't one of the given ones, such as `x` in `x = 1`.
+    /// Refer to [`implicit_globals`](#method.implicit_globals) for more details.
+    pub fn implicit_globals_with(&self, allowed: &[&str]) -> Vec<TokenReference<'a>> {
+        scopes::ScopeVisitor::new().find_implicit_globals(&self.nodes, allowed)
+    }
+
+    /// The innermost function that contains the position, such as a position inside the body of `function x() end`.
+    /// The function's whole declaration counts, including its name and parameters.
+    /// Returns `None` if the position isn't inside any function.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::functions::EnclosingFunction;
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("local function outer()\n\treturn function() return 1 end\nend")?;
+    /// let one = ast.iter_tokens().find(|token| token.to_string() == "1").unwrap();
+    ///
+    /// match ast.enclosing_function(one.start_position()) {
+    ///     Some(EnclosingFunction::Anonymous(..)) => {}
+    ///     _ => panic!("expected the anonymous function"),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn enclosing_function(
+        &self,
+        position: Position,
+    ) -> Option<functions::EnclosingFunction<'a>> {
+        functions::EnclosingFunctionVisitor::new(position).find(&self.nodes)
+    }
+
+    /// Every comparison of a value to `nil`, such as `x == nil` or `x ~= nil`, in the order they appear.
+    /// Operator precedence is followed, so `x + 1 == nil` isn't included, as `x + 1` is what's compared.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("if x == nil then return end")?;
+    /// let comparisons = ast.nil_comparisons();
+    /// assert_eq!(comparisons.len(), 1);
+    /// assert_eq!(comparisons[0].var().unwrap().to_string(), "x ");
+    /// assert!(comparisons[0].is_equal());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn nil_comparisons(&self) -> Vec<nil_comparisons::NilComparison<'a>> {
+        nil_comparisons::NilComparisonVisitor::new().find(&self.nodes)
+    }
+
+    /// Every comparison of a value to a float, such as `x == 0.1` or `x ~= 1e-3`, in the order they appear.
+    /// Floats can't represent most fractions exactly, so `0.1 + 0.2 == 0.3` is false, and comparing them for equality is fragile.
+    /// A float is a number written with a fractional part or an exponent, refer to [`Value::is_float`](enum.Value.html#method.is_float).
+    /// Operator precedence is followed, so in `x + 1 == 0.1`, `x + 1` is what's compared.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("if x == 0.1 or y == 1 then end")?;
+    /// let comparisons = ast.float_comparisons();
+    /// assert_eq!(comparisons.len(), 1);
+    /// assert_eq!(comparisons[0].float().to_string(), "0.1 ");
+    /// assert_eq!(comparisons[0].other().unwrap().to_string(), "x ");
+    /// assert_eq!(comparisons[0].start_position().unwrap().bytes(), 3);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn float_comparisons(&self) -> Vec<float_comparisons::FloatComparison<'a>> {
+        float_comparisons::FloatComparisonVisitor::new().find(&self.nodes)
+    }
+
+    /// Every assignment where the number of values doesn't match the number of names, in the order they appear.
+    /// Lua allows these, giving `nil` to the extra names of `a, b = 1` and throwing away the extra values of `a = 1, 2`,
+    /// but they're often mistakes.
+    ///
+    /// If the last value is a function call or `...`, it can give back any number of values, so the assignment
+    /// is only included if there are more values than names without it. `local` declarations with no values,
+    /// such as `local a, b`, aren't included.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::ValueCount;
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("local a, b = f()\nlocal c = 1, 2")?;
+    /// let mismatches = ast.count_mismatches();
+    /// assert_eq!(mismatches.len(), 1);
+    /// assert_eq!(mismatches[0].name_count(), 1);
+    /// assert_eq!(mismatches[0].value_count(), ValueCount::Exact(2));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn count_mismatches(&self) -> Vec<count_mismatches::CountMismatch<'a>> {
+        count_mismatches::CountMismatchVisitor::new().find(&self.nodes)
+    }
+
+    /// Every identifier in the code, in the order they appear, such as `x`, `y` and `z` in `local x = y.z`.
+    /// This includes the names of variables, fields, parameters and functions, without working out what they refer to,
+    /// so the same variable used in several places is included each time, and different variables with the same name are all included.
+    /// With the "roblox" feature flag, the names of types are included too.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("local function f(a) return a.b end")?;
+    /// let names: Vec<_> = ast.identifiers().iter().map(|identifier| identifier.to_string()).collect();
+    /// assert_eq!(names, vec!["f", "a", "a", "b"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn identifiers(&self) -> Vec<TokenReference<'a>> {
+        identifiers::IdentifierVisitor::new().find(&self.nodes)
+    }
+
+    /// Every table constructor in the code, such as `{1, 2}`, in the order they appear, including tables inside other tables.
+    /// Use [`Node::start_position`](../node/trait.Node.html#tymethod.start_position) to get where they are.
+    ///
+    /// ```rust
+    /// # use full_moon::node::Node;
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("local t = {1, {2}}")?;
+    /// let tables = ast.table_constructors();
+    /// assert_eq!(tables.len(), 2);
+    /// assert_eq!(tables[1].to_string(), "{2}");
+    /// assert_eq!(tables[1].start_position().unwrap().bytes(), 14);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn table_constructors(&self) -> Vec<TableConstructor<'a>> {
+        table_constructors::TableConstructorVisitor::new().find(&self.nodes)
+    }
+
+    /// How many statements there are in the code, including ones nested inside blocks and functions,
+    /// such as 3 for `if x then call() end return`. `return` and `break` count as statements.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("local function f()\n\tif x then call() end\nend")?;
+    /// assert_eq!(ast.statement_count(), 3);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn statement_count(&self) -> usize {
+        statements::StatementCounter::new()
+            .count(&self.nodes)
+            .statements
+    }
+
+    /// How many lines the code would take up with every statement on a line of its own, no matter how it's formatted.
+    /// This is the [number of statements](#method.statement_count), plus one for every `elseif`, `else`, `end`
+    /// and `until`, which would start lines of their own too. Blank lines and comments don't count.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let one_line = full_moon::parse("if x then call() else call() end")?;
+    /// let spread_out = full_moon::parse("-- calls\nif x then\n\tcall()\n\nelse\n\tcall()\nend")?;
+    /// assert_eq!(one_line.logical_lines(), 5);
+    /// assert_eq!(spread_out.logical_lines(), 5);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn logical_lines(&self) -> usize {
+        let counter = statements::StatementCounter::new().count(&self.nodes);
+        counter.statements + counter.block_keywords
+    }
+
+    /// The parameters of every function with the same name as one before them in the same function,
+    /// such as the second `a` in `function(a, a)`, in the order they appear.
+    /// Refer to [`FunctionBody::duplicate_parameters`](struct.FunctionBody.html#method.duplicate_parameters).
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("local function f(a, b, a) end")?;
+    /// let duplicates = ast.duplicate_parameters();
+    /// assert_eq!(duplicates.len(), 1);
+    /// assert_eq!(duplicates[0].start_position().bytes(), 23);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn duplicate_parameters(&self) -> Vec<TokenReference<'a>> {
+        functions::DuplicateParameterVisitor::new().find(&self.nodes)
+    }
+
+    /// The condition of every branching point in the code, in the order they appear, along with the kind of code it's from.
+    /// This includes the conditions of if statements, elseifs, while loops, and the `until` conditions of repeat loops.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::branches::BranchKind;
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("if x then elseif y then end")?;
+    /// let conditions = ast.branch_conditions();
+    /// assert_eq!(conditions[0].kind(), BranchKind::If);
+    /// assert_eq!(conditions[1].kind(), BranchKind::ElseIf);
+    /// assert_eq!(conditions[1].start_position().unwrap().bytes(), 17);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn branch_conditions(&self) -> Vec<branches::BranchCondition<'a>> {
+        branches::BranchConditionVisitor::new().find(&self.nodes)
+    }
+
+    /// Every region of code that spans more than one line and can be folded in an editor, ordered by where they start.
+    /// This includes function bodies, loops, `do end` blocks, each branch of an if statement, and table constructors.
+    /// Refer to [`FoldingKind`](folding/enum.FoldingKind.html) for what each region covers.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::folding::FoldingKind;
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("if x then\n    call()\nelse\n    call()\nend")?;
+    /// let regions = ast.foldable_regions();
+    /// assert_eq!(regions.len(), 2);
+    /// assert_eq!(regions[1].kind(), FoldingKind::Branch);
+    /// assert_eq!(regions[1].span().start().line(), 3);
+    /// assert_eq!(regions[1].span().end().line(), 5);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn foldable_regions(&self) -> Vec<folding::FoldingRegion> {
+        folding::FoldingVisitor::new().find(&self.nodes)
+    }
+
+    /// Every `return` without values at the end of a function, which does nothing, in the order they appear.
+    /// Refer to [`FunctionBody::redundant_return`](struct.FunctionBody.html#method.redundant_return).
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("function f() print(1) return end")?;
+    /// let returns = ast.redundant_returns();
+    /// assert_eq!(returns.len(), 1);
+    /// assert_eq!(returns[0].token().start_position().bytes(), 22);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn redundant_returns(&self) -> Vec<Return<'a>> {
+        functions::RedundantReturnVisitor::new().find(&self.nodes)
+    }
+
+    /// Every loop in the code, in the order they appear, including loops inside other loops.
+    /// For loops, while loops and repeat loops are all included.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("while x do\n\tfor i = 1, 10 do end\nend")?;
+    /// let loops = ast.loops();
+    /// assert_eq!(loops.len(), 2);
+    /// assert_eq!(loops[0].condition().unwrap().to_string(), "x ");
+    /// assert!(loops[1].condition().is_none());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn loops(&self) -> Vec<loops::Loop<'a>> {
+        loops::LoopVisitor::new().find(&self.nodes)
+    }
+
+    /// Every loop that's likely to never stop, such as `while true do end`, in the order they appear.
+    /// Refer to [`Loop::is_infinite`](loops/enum.Loop.html#method.is_infinite) for what counts as infinite.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("while true do end\nwhile true do break end\nrepeat until false")?;
+    /// let loops = ast.infinite_loops();
+    /// assert_eq!(loops.len(), 2);
+    /// assert_eq!(loops[0].condition().unwrap().to_string(), "true ");
+    /// assert_eq!(loops[1].condition().unwrap().to_string(), "false");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn infinite_loops(&self) -> Vec<loops::Loop<'a>> {
+        self.loops()
+            .into_iter()
+            .filter(loops::Loop::is_infinite)
+            .collect()
+    }
+
+    /// The positions of every line whose indentation mixes tabs and spaces, such as a tab followed by spaces.
+    /// Lines with nothing but whitespace on them aren't included.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("if x then\n\t  call()\nend")?;
+    /// let lines = ast.mixed_indentation();
+    /// assert_eq!(lines.len(), 1);
+    /// assert_eq!(lines[0].line(), 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn mixed_indentation(&self) -> Vec<Position> {
+        let mut positions = Vec::new();
+        let mut line_start = true;
+
+        for (_, token) in self.tokens.iter() {
+            if let Some(characters) = whitespace_characters(token) {
+                if line_start
+                    && !characters.ends_with('\n')
+                    && characters.contains('\t')
+                    && characters.contains(' ')
+                {
+                    // The start position of a token at the start of a line is still on the previous line
+                    positions.push(Position {
+                        bytes: token.start_position().bytes(),
+                        character: 1,
+                        line: token.end_position().line(),
+                    });
+                }
+
+                line_start = characters.ends_with('\n');
+            } else {
+                line_start = false;
+            }
+        }
+
+        positions
+    }
+
+    /// The ranges of code turned off by comments, such as `-- stylua: off` and `-- stylua: on`, in the order they appear.
+    /// This is for tools such as formatters to leave the code in them alone, with the name of the tool being the `marker`.
+    /// A range starts at the start of a comment saying `<marker>: off`, and ends at the end of the next comment
+    /// saying `<marker>: on`, or at the end of the file if there isn't one.
+    /// The spacing around the marker and the `:` doesn't matter, and either line or block comments can be used.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let code = "local a = 1\n-- stylua: off\nlocal b   =   2\n-- stylua: on\nlocal c = 3";
+    /// let ast = full_moon::parse(code)?;
+    /// let ranges = ast.disabled_ranges("stylua");
+    /// assert_eq!(ranges.len(), 1);
+    /// assert_eq!(
+    ///     &code[ranges[0].start().bytes()..ranges[0].end().bytes()],
+    ///     "-- stylua: off\nlocal b   =   2\n-- stylua: on",
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn disabled_ranges(&self, marker: &str) -> Vec<Span> {
+        let toggle = |token: &Token| {
+            let comment = token.comment_text()?;
+            let mut parts = comment.splitn(2, ':');
+            if parts.next()?.trim() != marker {
+                return None;
+            }
+
+            match parts.next()?.trim() {
+                "off" => Some(false),
+                "on" => Some(true),
+                _ => None,
+            }
+        };
+
+        let mut ranges = Vec::new();
+        let mut disabled_start = None;
+
+        for (_, token) in self.tokens.iter() {
+            match (toggle(token), disabled_start) {
+                (Some(false), None) => disabled_start = Some(token.start_position()),
+                (Some(true), Some(start)) => {
+                    ranges.push(Span::new(start, token.end_position()));
+                    disabled_start = None;
+                }
+
+                _ => {}
+            }
+        }
+
+        if let Some(start) = disabled_start {
+            ranges.push(Span::new(start, Token::end_position(&self.eof())));
+        }
+
+        ranges
+    }
+
+    /// The Luau directives at the top of the file, such as `--!strict` or `--!native`, in the order they appear.
+    /// Directives are only read before any code, so comments starting with `--!` after that aren't included.
+    /// Only available when the "roblox" feature flag is enabled.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("--!strict\n--!optimize 2\nlocal x = 1")?;
+    /// let directives = ast.luau_directives();
+    /// assert_eq!(directives[0].name(), "strict");
+    /// assert_eq!(directives[1].name(), "optimize");
+    /// assert_eq!(directives[1].argument(), Some("2"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "roblox")]
+    pub fn luau_directives(&self) -> Vec<directives::LuauDirective<'a>> {
+        self.tokens
+            .iter()
+            .map(|(_, token)| token)
+            .take_while(|token| token.token_type().ignore())
+            .filter_map(directives::LuauDirective::from_comment)
+            .collect()
+    }
+
+    /// Collapses every run of more than 2 blank lines down to 2 blank lines.
+    /// To use a different number of blank lines, use [`collapse_blank_lines_to`](#method.collapse_blank_lines_to).
+    pub fn collapse_blank_lines(&mut self) {
+        self.collapse_blank_lines_to(2);
+    }
+
+    /// Collapses every run of more than the given number of blank lines down to that many, such as
+    /// turning four blank lines between two statements into two.
+    /// Only whitespace is changed, so blank lines inside multi line strings and comments are kept.
+    /// The positions of the tokens are updated afterwards.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let mut ast = full_moon::parse("call()\n\n\n\ncall()")?;
+    /// ast.collapse_blank_lines_to(1);
+    /// assert_eq!(full_moon::print(&ast), "call()\n\ncall()");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn collapse_blank_lines_to(&mut self, max_blank_lines: usize) {
+        let mut blank_lines = 0;
+        let mut line_has_code = false;
+
+        for (_, token) in self.tokens.iter() {
+            let mut token_type = token.token_type.borrow_mut();
+
+            match &*token_type {
+                // Whitespace ends at the end of a line, so each new line is the end of a different token
+                TokenType::Whitespace { characters } if characters.ends_with('\n') => {
+                    if line_has_code {
+                        blank_lines = 0;
+                        line_has_code = false;
+                    } else {
+                        blank_lines += 1;
+
+                        if blank_lines > max_blank_lines {
+                            *token_type = TokenType::Whitespace {
+                                characters: Cow::Borrowed(""),
+                            };
+                        }
+                    }
+                }
+
+                TokenType::Whitespace { .. } | TokenType::Eof => {}
+
+                _ => line_has_code = true,
+            }
+        }
+
+        self.update_positions();
+    }
+
+    /// Rewrites comments in the given [`CommentStyle`](comment_style/enum.CommentStyle.html).
+    ///
+    /// With [`CommentStyle::Block`](comment_style/enum.CommentStyle.html#variant.Block), every line comment becomes a block comment,
+    /// and line comments on consecutive lines with nothing but whitespace before them are joined into one,
+    /// keeping the new lines and indentation in between them.
+    /// The block comments use as many equals signs as needed for the text in them not to close them, such as `--[=[ a]] ]=]`.
+    ///
+    /// With [`CommentStyle::Line`](comment_style/enum.CommentStyle.html#variant.Line), every block comment that fits on one line
+    /// and has nothing after it on that line becomes a line comment.
+    /// Block comments over several lines are left alone, as each comment is one token and can't be split into several,
+    /// as are ones that would start a block comment as a line comment, such as `--[=[[[x]=]`.
+    ///
+    /// Only the tokens the Ast was created with are changed, and their positions are updated afterwards.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::comment_style::CommentStyle;
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let mut ast = full_moon::parse("-- a\n-- b\nlocal x = 1")?;
+    /// ast.convert_comments(CommentStyle::Block);
+    /// assert_eq!(full_moon::print(&ast), "--[[ a\n b]]\nlocal x = 1");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn convert_comments(&mut self, style: comment_style::CommentStyle) {
+        comment_style::convert_comments(&self.tokens, style);
+        self.update_positions();
+    }
+
+    /// Moves the `local` declarations in every block to the top of it, keeping them in the same order.
+    /// Declarations with values, such as `local x = 1`, are split in two: `local x` at the top of the block,
+    /// and `x = 1` where the declaration was.
+    /// Declarations already at the top of their block are left alone, as are ones whose names are used
+    /// or declared before them in the block, such as `local x = x`, since moving them would change what the names refer to.
+    /// With the "roblox" feature flag, declarations with types are left alone too.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let mut ast = full_moon::parse("call()\nlocal x = 1\nlocal y\n")?;
+    /// ast.hoist_locals();
+    /// assert_eq!(full_moon::print(&ast), "local x\nlocal y\ncall()\nx = 1\n");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn hoist_locals(&mut self) {
+        hoist_locals::hoist_locals(&mut self.nodes);
+    }
+
+    /// Removes `do end` blocks with nothing in them, and simplifies `if` statements with empty branches.
+    /// Empty `else` branches are removed, as are empty `elseif` branches at the end whose conditions
+    /// can't call any functions, such as `elseif x then`. An `if` statement with no branches left that have something in them
+    /// is removed if its condition can't call any functions either, and is replaced with its condition if that's a call,
+    /// turning `if f() then end` into `f()`, since it still needs to be called. Anything else is left alone,
+    /// including empty blocks with comments in them, which would otherwise be lost.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let mut ast = full_moon::parse("do end\nif x then end\nif f() then end\nif x.y then end\n")?;
+    /// ast.remove_empty_blocks();
+    /// assert_eq!(full_moon::print(&ast), "f()\nif x.y then end\n");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn remove_empty_blocks(&mut self) {
+        empty_blocks::remove_empty_blocks(&mut self.nodes);
+    }
+
+    /// Rewrites every call that passes the table its function is indexed from as the first argument into a method call,
+    /// such as turning `t.insert(t, 1)` into `t:insert(1)`.
+    /// Refer to [`FunctionCall::to_method_call`](struct.FunctionCall.html#method.to_method_call) for which calls are rewritten.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let mut ast = full_moon::parse("t.insert(t, 1)\nt.insert(u, 1)")?;
+    /// ast.use_method_calls();
+    /// assert_eq!(full_moon::print(&ast), "t:insert(1)\nt.insert(u, 1)");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn use_method_calls(&mut self) {
+        method_calls::use_method_calls(&mut self.nodes);
+    }
+
+    /// Rewrites every index with a string key that's a valid name to use a dot, such as turning `t["foo"]` into `t.foo`.
+    /// Refer to [`Index::to_dot`](enum.Index.html#method.to_dot) for which indexes are rewritten.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let mut ast = full_moon::parse(r#"print(t["foo"], t["end"], t["2x"])"#)?;
+    /// ast.use_dot_indexing();
+    /// assert_eq!(full_moon::print(&ast), r#"print(t.foo, t["end"], t["2x"])"#);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn use_dot_indexing(&mut self) {
+        dot_index::use_dot_indexing(&mut self.nodes);
+    }
+
+    /// Splits every assignment of several values that can be split into one assignment per variable,
+    /// such as turning `a, b = 1, 2` into `a = 1` and `b = 2` on separate lines.
+    /// Refer to [`Assignment::split`](struct.Assignment.html#method.split) for which assignments are split.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let mut ast = full_moon::parse("a, b = 1, 2\na, b = f()\n")?;
+    /// ast.split_assignments();
+    /// assert_eq!(full_moon::print(&ast), "a = 1\nb = 2\na, b = f()\n");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn split_assignments(&mut self) {
+        split_assignments::split_assignments(&mut self.nodes);
+    }
+
+    /// Lines up the `=` of assignments on consecutive lines by padding the whitespace before them,
+    /// so that they're all at the same column as the one furthest along the line.
+    /// Both assignments, such as `x = 1`, and local assignments with values, such as `local x = 1`, are aligned.
+    /// Only assignments on lines of their own are, and a blank line, a comment, or any other statement between them
+    /// starts a new run of assignments that's aligned separately.
+    /// Nothing is ever moved to the left, so aligning code that's already aligned doesn't change it.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let mut ast = full_moon::parse("local x = 1\nlocal longer = 2\n\ny = 3\nz.w = 4\n")?;
+    /// ast.align_assignments();
+    /// assert_eq!(
+    ///     full_moon::print(&ast),
+    ///     "local x      = 1\nlocal longer = 2\n\ny   = 3\nz.w = 4\n",
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn align_assignments(&mut self) {
+        align_assignments::align_assignments(&mut self.nodes);
+    }
+
     /// An iterator over the tokens used to create the Ast
     pub fn iter_tokens(&self) -> impl Iterator<Item = &Token<'a>> {
         self.tokens.iter().map(|(_, token)| token).sorted()
     }
 
     /// Will update the positions of all the tokens in the tree
-    /// Necessary if you are both mutating the tree and need the positions of the tokens
+    /// Necessary if you are both mutating the tree and need the positions of the tokens.
+    /// Positions start from where the first token started, so an Ast from
+    /// [`parse_with_offset`](../fn.parse_with_offset.html) keeps its offset.
     pub fn update_positions(&mut self) {
-        use crate::tokenizer::Position;
-
-        let mut start_position = Position {
-            bytes: 0,
-            character: 1,
-            line: 1,
-        };
+        let mut start_position = self.start_position();
 
         let mut next_is_new_line = false;
 
@@ -1217,3 +3512,62 @@ impl<'a> Ast<'a> {
         }
     }
 }
+
+/// The version of the format an [`Ast`](struct.Ast.html) is serialized in.
+/// It's increased whenever serialized Asts from a previous version can't be read anymore,
+/// and deserializing an Ast with a different version will give an error.
+#[cfg(feature = "serde")]
+pub const SERDE_VERSION: u32 = 1;
+
+// Asts are serialized as the tokens of the code they print, which are parsed again when deserializing.
+// The tokens the Ast was parsed from aren't used, as they don't have any changes made to the nodes since.
+#[cfg(feature = "serde")]
+impl<'a> Serialize for Ast<'a> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{Error, SerializeStruct};
+
+        let code = crate::print(self);
+        let tokens = crate::tokenizer::tokens_with_offset(&code, self.start_position())
+            .map_err(S::Error::custom)?;
+
+        let mut state = serializer.serialize_struct("Ast", 2)?;
+        state.serialize_field("version", &SERDE_VERSION)?;
+        state.serialize_field("tokens", &tokens)?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+#[serde(rename = "Ast")]
+struct SerializedAst<'a> {
+    // Checked as soon as it's read, so that tokens from another version aren't mistaken for an error in them
+    #[serde(rename = "version", deserialize_with = "deserialize_serde_version")]
+    _version: u32,
+    #[serde(borrow)]
+    tokens: Vec<Token<'a>>,
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_serde_version<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<u32, D::Error> {
+    let version = u32::deserialize(deserializer)?;
+
+    if version == SERDE_VERSION {
+        Ok(version)
+    } else {
+        Err(serde::de::Error::custom(format!(
+            "ast was serialized with version {}, but only version {} is supported",
+            version, SERDE_VERSION
+        )))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de: 'a, 'a> Deserialize<'de> for Ast<'a> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let serialized = SerializedAst::deserialize(deserializer)?;
+        Ast::from_tokens(serialized.tokens).map_err(serde::de::Error::custom)
+    }
+}
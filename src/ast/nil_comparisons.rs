@@ -0,0 +1,160 @@
+//! Comparisons of a value to `nil` with `==` or `~=`, such as `x == nil` or `nil ~= x`, for linting nil checks.
+//! Each [`NilComparison`](struct.NilComparison.html) holds the value, the operator and the `nil` token,
+//! and they're all found with [`Ast::nil_comparisons`](../struct.Ast.html#method.nil_comparisons).
+use super::{
+    constant::{Operand, Operation},
+    *,
+};
+use crate::{
+    node::Node,
+    visitors::{Visit, Visitor},
+};
+use std::collections::HashSet;
+
+/// A comparison between a value and `nil`, such as `x == nil`
+#[derive(Clone, Debug, PartialEq)]
+pub struct NilComparison<'a> {
+    value: Value<'a>,
+    bin_op: BinOp<'a>,
+    nil: TokenReference<'a>,
+}
+
+impl<'a> NilComparison<'a> {
+    /// The value being compared to `nil`, the `x` part of `x == nil`
+    pub fn value(&self) -> &Value<'a> {
+        &self.value
+    }
+
+    /// The variable being compared to `nil`, the `x` part of `x == nil`.
+    /// Returns `None` if the value isn't a variable, such as in `f() == nil`.
+    pub fn var(&self) -> Option<&Var<'a>> {
+        match &self.value {
+            Value::Var(var) => Some(var),
+            _ => None,
+        }
+    }
+
+    /// The operator used, either [`BinOp::TwoEqual`](../enum.BinOp.html#variant.TwoEqual) or
+    /// [`BinOp::TildeEqual`](../enum.BinOp.html#variant.TildeEqual)
+    pub fn bin_op(&self) -> &BinOp<'a> {
+        &self.bin_op
+    }
+
+    /// The `nil` token
+    pub fn nil_token(&self) -> &TokenReference<'a> {
+        &self.nil
+    }
+
+    /// Whether the comparison checks that the value is `nil`, as `x == nil` does, rather than not `nil`
+    pub fn is_equal(&self) -> bool {
+        match self.bin_op {
+            BinOp::TwoEqual(_) => true,
+            BinOp::TildeEqual(_) => false,
+            _ => unreachable!("nil comparison doesn't use == or ~="),
+        }
+    }
+
+    /// The position the comparison starts at, which is the start of either the value or `nil`
+    pub fn start_position(&self) -> Option<Position> {
+        (&self.value, &self.nil).start_position()
+    }
+
+    /// The position the comparison ends at, which is the end of either the value or `nil`
+    pub fn end_position(&self) -> Option<Position> {
+        (&self.value, &self.nil).end_position()
+    }
+}
+
+// The `nil` token of an operand that's just `nil`
+fn nil_token<'a, 'b>(operation: &Operation<'a, 'b>) -> Option<&'b TokenReference<'a>> {
+    if let Operation::Operand(Operand::Value(Value::Symbol(token))) = operation {
+        if let TokenType::Symbol {
+            symbol: Symbol::Nil,
+        } = &*token.token_type()
+        {
+            return Some(token);
+        }
+    }
+
+    None
+}
+
+// The value and `nil` token of a comparison, if one side is a single value and the other is `nil`
+fn compared_to_nil<'a, 'b>(
+    value: &Operation<'a, 'b>,
+    nil: &Operation<'a, 'b>,
+) -> Option<(&'b Value<'a>, &'b TokenReference<'a>)> {
+    match value {
+        Operation::Operand(Operand::Value(value)) => Some((value, nil_token(nil)?)),
+        _ => None,
+    }
+}
+
+pub(crate) struct NilComparisonVisitor<'a> {
+    // The expressions that continue a chain of operators, such as the `y == nil` of `x + y == nil`.
+    // They're already part of the operation they continue, so aren't looked at again.
+    chained: HashSet<*const Expression<'a>>,
+    comparisons: Vec<NilComparison<'a>>,
+}
+
+impl<'a> NilComparisonVisitor<'a> {
+    pub(crate) fn new() -> Self {
+        Self {
+            chained: HashSet::new(),
+            comparisons: Vec::new(),
+        }
+    }
+
+    pub(crate) fn find(mut self, block: &Block<'a>) -> Vec<NilComparison<'a>> {
+        block.visit(&mut self);
+        self.comparisons
+    }
+
+    fn check_operation(&mut self, operation: &Operation<'a, '_>) {
+        match operation {
+            Operation::Operand(_) => {}
+
+            Operation::Unary(_, operand) => self.check_operation(operand),
+
+            Operation::Binary(lhs, bin_op, rhs) => {
+                if let BinOp::TwoEqual(_) | BinOp::TildeEqual(_) = bin_op {
+                    let compared = compared_to_nil(lhs, rhs).or_else(|| compared_to_nil(rhs, lhs));
+
+                    if let Some((value, nil)) = compared {
+                        self.comparisons.push(NilComparison {
+                            value: (*value).clone(),
+                            bin_op: (*bin_op).clone(),
+                            nil: nil.clone(),
+                        });
+                    }
+                }
+
+                self.check_operation(lhs);
+                self.check_operation(rhs);
+            }
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for NilComparisonVisitor<'a> {
+    fn visit_expression(&mut self, expression: &Expression<'a>) {
+        if self.chained.remove(&(expression as *const _)) {
+            return;
+        }
+
+        let mut chain = expression;
+        loop {
+            chain = match chain {
+                Expression::UnaryOperator { expression, .. } => expression,
+                Expression::Value {
+                    binop: Some(binop), ..
+                } => binop.rhs(),
+                _ => break,
+            };
+
+            self.chained.insert(chain);
+        }
+
+        self.check_operation(&Operation::new(expression));
+    }
+}
@@ -1,6 +1,7 @@
 //! Exposes the [`Owned`](trait.Owned.html) that nodes implement to produce an owned version of themselves.
 //! Owned versions are represented as the node with a lifetime of `'static`. For example, if you have
 //! an [`Ast<'a>`](../struct.Ast.html), calling `ast.owned()` on it will produce an owned `Ast<'static>`.
+//! To parse code straight into an owned Ast, use [`OwnedAst`](struct.OwnedAst.html).
 use super::*;
 use crate::tokenizer::*;
 
@@ -20,6 +21,57 @@ where
     fn owned(&self) -> Self::Owned;
 }
 
+/// An [`Ast`](../struct.Ast.html) along with the code it was parsed from, which doesn't borrow anything.
+/// Useful to store a parsed tree, such as in a struct or a `Vec`, without keeping the code it came from alive separately.
+///
+/// ```rust
+/// # use full_moon::ast::owned::OwnedAst;
+/// # fn main() -> Result<(), Box<std::error::Error>> {
+/// let mut asts = Vec::new();
+/// asts.push(OwnedAst::parse(String::from("local x = 1"))?);
+/// assert_eq!(full_moon::print(asts[0].ast()), "local x = 1");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct OwnedAst {
+    source: String,
+    ast: Ast<'static>,
+}
+
+impl OwnedAst {
+    /// Parses the code, keeping it alongside the [`Ast`](../struct.Ast.html).
+    ///
+    /// # Errors
+    /// The same errors as [`parse`](../../fn.parse.html), but owned.
+    pub fn parse(source: String) -> Result<Self, crate::Error<'static>> {
+        let ast = crate::parse(&source)
+            .map_err(|error| error.owned())?
+            .owned();
+        Ok(Self { source, ast })
+    }
+
+    /// The code the Ast was parsed from
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The parsed Ast
+    pub fn ast(&self) -> &Ast<'static> {
+        &self.ast
+    }
+
+    /// The parsed Ast, but mutable
+    pub fn ast_mut(&mut self) -> &mut Ast<'static> {
+        &mut self.ast
+    }
+
+    /// Takes the Ast out, dropping the code it was parsed from
+    pub fn into_ast(self) -> Ast<'static> {
+        self.ast
+    }
+}
+
 impl Owned for Ast<'_> {
     type Owned = Ast<'static>;
 
@@ -69,7 +121,9 @@ where
 
     fn owned(&self) -> Self::Owned {
         let mut owned = Punctuated::new();
-        owned.extend(&mut self.pairs().map(Pair::owned));
+        for pair in self.pairs() {
+            owned.push(pair.owned());
+        }
         owned
     }
 }
@@ -98,7 +152,11 @@ impl Owned for TokenReference<'_> {
     type Owned = TokenReference<'static>;
 
     fn owned(&self) -> TokenReference<'static> {
-        TokenReference::Owned((**self).owned())
+        TokenReference::new(
+            self.leading_trivia().map(Token::owned).collect(),
+            (**self).owned(),
+            self.trailing_trivia().map(Token::owned).collect(),
+        )
     }
 }
 
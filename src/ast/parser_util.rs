@@ -1,15 +1,18 @@
 // Exported macros are documented since no amount of allow(missing_docs) silenced the lint
 
-use super::punctuated::{Pair, Punctuated};
+use super::{
+    punctuated::{Pair, Punctuated},
+    AstError,
+};
 use crate::{
     node::Node,
-    tokenizer::{Token, TokenReference},
+    tokenizer::{Token, TokenReference, TokenType},
     visitors::{Visit, VisitMut},
 };
 use generational_arena::Arena;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use std::{fmt, sync::Arc};
+use std::{borrow::Cow, fmt, iter::FromIterator, sync::Arc};
 
 // This is cloned everywhere, so make sure cloning is as inexpensive as possible
 #[derive(Clone)]
@@ -17,6 +20,9 @@ pub struct ParserState<'a> {
     pub index: usize,
     pub len: usize,
     pub tokens: Arc<Arena<Token<'a>>>,
+    // Whether the first `>` of the `>>` being peeked has already closed a generic type, as in `Array<Array<number>>`
+    #[cfg(all(feature = "roblox", feature = "lua53"))]
+    pub split_greater_than: bool,
 }
 
 impl<'a> ParserState<'a> {
@@ -25,6 +31,8 @@ impl<'a> ParserState<'a> {
             index: 0,
             len: tokens.len(),
             tokens,
+            #[cfg(all(feature = "roblox", feature = "lua53"))]
+            split_greater_than: false,
         }
     }
 
@@ -36,6 +44,8 @@ impl<'a> ParserState<'a> {
                 index: state.index + 1,
                 len: self.len,
                 tokens: Arc::clone(&self.tokens),
+                #[cfg(all(feature = "roblox", feature = "lua53"))]
+                split_greater_than: false,
             };
 
             if !state.peek().token_type().ignore() {
@@ -49,19 +59,17 @@ impl<'a> ParserState<'a> {
             panic!("peek failed, when there should always be an eof");
         }
 
-        // sorted_by is commented out because it had a extremely high performance cost
-        // Uncommenting the line changes one large file from being parsed in ~0.1s to **14 seconds**!!!
-        // Iteration of self.tokens is explicitly undefined, but it happens to work out
-        // TODO: How can we guarantee order without the performance cost? Create our own arena?
+        // Tokens are put into the arena in the order they appear in, and none are ever removed, so the raw index
+        // is the token's position. The order of an arena isn't defined by generational_arena, which is why
+        // test_arena_order checks it. Looking the token up directly keeps peeking constant time,
+        // where iterating up to the index every time made parsing quadratic in the number of tokens.
         TokenReference::Borrowed {
             arena: Arc::clone(&self.tokens),
             index: self
                 .tokens
-                .iter()
-                // .sorted_by(|left, right| left.1.cmp(&right.1))
-                .nth(self.index)
+                .get_unknown_gen(self.index)
                 .expect("couldn't peek, no eof?")
-                .0,
+                .1,
         }
     }
 }
@@ -190,6 +198,49 @@ pub enum InternalAstError<'a> {
     },
 }
 
+// Parses all of the tokens with the parser, which must be followed by nothing but the eof token
+pub(crate) fn parse_to_eof<'a, P: Parser<'a>>(
+    parser: P,
+    tokens: Vec<Token<'a>>,
+) -> Result<(Arc<Arena<Token<'a>>>, P::Item), AstError<'a>> {
+    if *tokens.last().ok_or(AstError::Empty)?.token_type() != TokenType::Eof {
+        return Err(AstError::NoEof);
+    }
+
+    let tokens = Arc::new(Arena::from_iter(tokens));
+    let mut state = ParserState::new(Arc::clone(&tokens));
+
+    // There's always an eof to advance to, as it isn't ignored
+    if state.peek().token_type().ignore() {
+        state = state.advance().unwrap();
+    }
+
+    match parser.parse(state.clone()) {
+        Ok((state, item)) => {
+            if state.index == tokens.len() - 1 {
+                Ok((tokens, item))
+            } else {
+                Err(AstError::UnexpectedToken {
+                    token: (*state.peek()).to_owned(),
+                    additional: Some(Cow::Borrowed("leftover token")),
+                })
+            }
+        }
+
+        Err(InternalAstError::NoMatch) => Err(AstError::UnexpectedToken {
+            token: (*state.peek()).to_owned(),
+            additional: None,
+        }),
+
+        Err(InternalAstError::UnexpectedToken { token, additional }) => {
+            Err(AstError::UnexpectedToken {
+                token: (*token).to_owned(),
+                additional: additional.map(Cow::Borrowed),
+            })
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct ZeroOrMore<P>(pub P);
 
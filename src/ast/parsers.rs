@@ -19,6 +19,14 @@ define_parser!(
         let expecting = TokenType::Symbol { symbol: this.0 };
         let token = state.peek();
 
+        // Only the second `>` of the `>>` is left, which only ParseClosingArrow takes
+        #[cfg(all(feature = "roblox", feature = "lua53"))]
+        {
+            if state.split_greater_than {
+                return Err(InternalAstError::NoMatch);
+            }
+        }
+
         if *token.token_type() == expecting {
             Ok((state.advance().ok_or(InternalAstError::NoMatch)?, token))
         } else {
@@ -227,61 +235,74 @@ struct ParseExpression;
 define_parser!(
     ParseExpression,
     Expression<'a>,
-    |_, state: ParserState<'a>| if let Ok((state, value)) =
-        keep_going!(ParseValue.parse(state.clone()))
-    {
-        let (state, as_assertion) =
-            if let Ok((state, as_assertion)) = keep_going!(ParseAsAssertion.parse(state.clone())) {
-                (state, Some(as_assertion))
-            } else {
-                (state, None)
-            };
+    |_, state: ParserState<'a>| {
+        // Binary operators are parsed in a loop rather than by parsing the right hand side as another expression,
+        // so that long chains such as `a .. b .. c ..` don't need a stack frame for every operator
+        let mut state = state;
+        let mut chain = Vec::new();
+
+        let last = loop {
+            if let Ok((new_state, value)) = keep_going!(ParseValue.parse(state.clone())) {
+                let (new_state, as_assertion) = if let Ok((new_state, as_assertion)) =
+                    keep_going!(ParseAsAssertion.parse(new_state.clone()))
+                {
+                    (new_state, Some(as_assertion))
+                } else {
+                    (new_state, None)
+                };
 
-        let (state, binop) = if as_assertion.is_none() {
-            if let Ok((state, bin_op)) = ParseBinOp.parse(state.clone()) {
-                let (state, rhs) = expect!(
-                    state,
-                    ParseExpression.parse(state.clone()),
+                if as_assertion.is_none() {
+                    if let Ok((new_state, bin_op)) = ParseBinOp.parse(new_state.clone()) {
+                        chain.push((value, bin_op));
+                        state = new_state;
+                        continue;
+                    }
+                }
+
+                state = new_state;
+                break Expression::Value {
+                    value: Box::new(value),
+                    binop: None,
+                    #[cfg(feature = "roblox")]
+                    as_assertion,
+                };
+            } else if let Ok((new_state, unop)) = keep_going!(ParseUnOp.parse(state.clone())) {
+                let (new_state, expression) = expect!(
+                    new_state,
+                    ParseExpression.parse(new_state.clone()),
                     "expected expression"
                 );
 
-                (
-                    state,
-                    Some(BinOpRhs {
-                        bin_op,
-                        rhs: Box::new(rhs),
-                    }),
-                )
+                state = new_state;
+                break Expression::UnaryOperator {
+                    unop,
+                    expression: Box::new(expression),
+                };
+            } else if chain.is_empty() {
+                return Err(InternalAstError::NoMatch);
             } else {
-                (state, None)
+                return Err(InternalAstError::UnexpectedToken {
+                    token: state.peek(),
+                    additional: Some("expected expression"),
+                });
             }
-        } else {
-            (state, None)
         };
 
-        let value = Box::new(value);
-
-        Ok((
-            state,
-            Expression::Value {
-                value,
-                binop,
-                #[cfg(feature = "roblox")]
-                as_assertion,
-            },
-        ))
-    } else if let Ok((state, unop)) = keep_going!(ParseUnOp.parse(state.clone())) {
-        let (state, expression) = expect!(
-            state,
-            ParseExpression.parse(state.clone()),
-            "expected expression"
-        );
-
-        let expression = Box::new(expression);
+        let expression =
+            chain
+                .into_iter()
+                .rev()
+                .fold(last, |rhs, (value, bin_op)| Expression::Value {
+                    value: Box::new(value),
+                    binop: Some(BinOpRhs {
+                        bin_op,
+                        rhs: Box::new(rhs),
+                    }),
+                    #[cfg(feature = "roblox")]
+                    as_assertion: None,
+                });
 
-        Ok((state, Expression::UnaryOperator { unop, expression }))
-    } else {
-        Err(InternalAstError::NoMatch)
+        Ok((state, expression))
     }
 );
 
@@ -348,7 +369,7 @@ define_parser!(
 );
 
 #[derive(Clone, Debug, PartialEq)]
-struct ParseValue;
+pub struct ParseValue;
 define_parser!(
     ParseValue,
     Value<'a>,
@@ -386,6 +407,12 @@ define_parser!(
         ParseLocalAssignment => Stmt::LocalAssignment,
         @#[cfg(feature = "roblox")]
         ParseTypeDeclaration => Stmt::TypeDeclaration,
+        @#[cfg(feature = "roblox")]
+        ParseCompoundAssignment => Stmt::CompoundAssignment,
+        @#[cfg(feature = "lua52")]
+        ParseGoto => Stmt::Goto,
+        @#[cfg(feature = "lua52")]
+        ParseLabel => Stmt::Label,
     })
 );
 
@@ -797,7 +824,10 @@ define_parser!(ParseFunctionBody, FunctionBody<'a>, |_, state: ParserState<'a>|
         if let Ok((new_state, comma)) = ParseSymbol(Symbol::Comma).parse(state.clone()) {
             if let Ok((new_state, ellipse)) = ParseSymbol(Symbol::Ellipse).parse(new_state) {
                 state = new_state;
-                parameters.push(Pair::new(Parameter::Ellipse(ellipse), Some(comma)));
+                // The comma belongs after the last name, not after the ellipse
+                let last_name = parameters.pop().expect("name list can't be empty").into_value();
+                parameters.push(Pair::new(last_name, Some(comma)));
+                parameters.push(Pair::new(Parameter::Ellipse(ellipse), None));
             }
         }
     } else if let Ok((new_state, ellipse)) = ParseSymbol(Symbol::Ellipse).parse(state.clone()) {
@@ -950,6 +980,8 @@ define_parser!(
     ParseLocalFunction,
     LocalFunction<'a>,
     |_, state: ParserState<'a>| {
+        #[cfg(feature = "roblox")]
+        let (state, attributes) = ZeroOrMore(ParseAttribute).parse(state.clone())?;
         let (state, local_token) = ParseSymbol(Symbol::Local).parse(state.clone())?;
         let (state, function_token) = ParseSymbol(Symbol::Function).parse(state.clone())?;
         let (state, name) = expect!(state, ParseIdentifier.parse(state.clone()), "expected name");
@@ -957,6 +989,8 @@ define_parser!(
         Ok((
             state,
             LocalFunction {
+                #[cfg(feature = "roblox")]
+                attributes,
                 local_token,
                 function_token,
                 name,
@@ -1092,6 +1126,19 @@ define_parser!(ParseFunctionName, FunctionName<'a>, |_,
             (state, None)
         };
 
+    // A method name can only be the last part, `x:y.z` is not valid
+    if colon_name.is_some() {
+        if let Ok((_, token)) = ParseSymbol(Symbol::Dot)
+            .parse(state.clone())
+            .or_else(|_| ParseSymbol(Symbol::Colon).parse(state.clone()))
+        {
+            return Err(InternalAstError::UnexpectedToken {
+                token,
+                additional: Some("method name must be the last part of a function name"),
+            });
+        }
+    }
+
     Ok((state, FunctionName { names, colon_name }))
 });
 
@@ -1101,6 +1148,8 @@ define_parser!(
     ParseFunctionDeclaration,
     FunctionDeclaration<'a>,
     |_, state: ParserState<'a>| {
+        #[cfg(feature = "roblox")]
+        let (state, attributes) = ZeroOrMore(ParseAttribute).parse(state.clone())?;
         let (state, function_token) = ParseSymbol(Symbol::Function).parse(state.clone())?;
         let (state, name) = expect!(
             state,
@@ -1115,6 +1164,8 @@ define_parser!(
         Ok((
             state,
             FunctionDeclaration {
+                #[cfg(feature = "roblox")]
+                attributes,
                 function_token,
                 name,
                 body,
@@ -1129,6 +1180,9 @@ struct ParseIdentifier;
 define_parser!(ParseIdentifier, TokenReference<'a>, |_, state: ParserState<'a>| {
     let next_token = state.peek();
     match next_token.token_kind() {
+        // `goto` is a keyword from Lua 5.2, but it's tokenized as an identifier so that Lua 5.1 can use it as a name
+        #[cfg(feature = "lua52")]
+        TokenKind::Identifier if next_token.to_string() == "goto" => Err(InternalAstError::NoMatch),
         TokenKind::Identifier => Ok((
             state.advance().ok_or(InternalAstError::NoMatch)?,
             next_token,
@@ -1160,6 +1214,19 @@ define_roblox_parser!(
 
 cfg_if::cfg_if! {
     if #[cfg(feature = "roblox")] {
+        #[derive(Clone, Debug, PartialEq)]
+        struct ParseAttribute;
+        define_parser!(ParseAttribute, Attribute<'a>, |_, state: ParserState<'a>| {
+            let (state, at_token) = ParseSymbol(Symbol::At).parse(state.clone())?;
+            let (state, name) = expect!(
+                state,
+                ParseIdentifier.parse(state.clone()),
+                "expected attribute name after `@`"
+            );
+
+            Ok((state, Attribute { at_token, name }))
+        });
+
         #[derive(Clone, Debug, PartialEq)]
         struct ParseTypeDeclaration;
         define_parser!(
@@ -1221,8 +1288,38 @@ cfg_if::cfg_if! {
             }
         );
 
+        // The `>` closing the type parameters of a generic type.
+        // Lua 5.3 tokenizes `>>` as one symbol, so in `Array<Array<number>>` it's split in two,
+        // the first `>` closing `Array<number>` and the second closing the outer `Array<...>`.
+        #[derive(Clone, Debug, PartialEq)]
+        struct ParseClosingArrow;
+        define_parser!(ParseClosingArrow, TokenReference<'a>, |_, state: ParserState<'a>| {
+            #[cfg(feature = "lua53")]
+            {
+                let token = state.peek();
+                let two_greater_than = TokenType::Symbol {
+                    symbol: Symbol::TwoGreaterThan,
+                };
+
+                if *token.token_type() == two_greater_than {
+                    let (first, second) =
+                        token.split_symbol(Symbol::GreaterThan, Symbol::GreaterThan);
+
+                    return if state.split_greater_than {
+                        Ok((state.advance().ok_or(InternalAstError::NoMatch)?, second))
+                    } else {
+                        let mut state = state.clone();
+                        state.split_greater_than = true;
+                        Ok((state, first))
+                    };
+                }
+            }
+
+            ParseSymbol(Symbol::GreaterThan).parse(state)
+        });
+
         #[derive(Clone, Debug, PartialEq)]
-        struct ParseTypeInfo;
+        pub struct ParseTypeInfo;
         define_parser!(ParseTypeInfo, TypeInfo<'a>, |_, state: ParserState<'a>| {
             let (mut state, mut base_type) = if let Ok((state, identifier)) = {
                 ParseIdentifier
@@ -1266,7 +1363,7 @@ cfg_if::cfg_if! {
 
                     let (state, end_arrow) = expect!(
                         state,
-                        ParseSymbol(Symbol::GreaterThan).parse(state.clone()),
+                        ParseClosingArrow.parse(state.clone()),
                         "expected `>` to close `<`"
                     );
 
@@ -1284,9 +1381,9 @@ cfg_if::cfg_if! {
             } else if let Ok((state, start_parenthese)) =
                 ParseSymbol(Symbol::LeftParen).parse(state.clone())
             {
-                let (state, types) = expect!(
+                let (state, arguments) = expect!(
                     state,
-                    ZeroOrMoreDelimited(ParseTypeInfo, ParseSymbol(Symbol::Comma), false)
+                    ZeroOrMoreDelimited(ParseTypeArgument, ParseSymbol(Symbol::Comma), false)
                         .parse(state.clone()),
                     "expected types within parentheses"
                 );
@@ -1297,23 +1394,39 @@ cfg_if::cfg_if! {
                     "expected `)` to match `(`"
                 );
 
-                if let Ok((state, arrow)) = ParseSymbol(Symbol::FatArrow).parse(state.clone()) {
+                if let Ok((state, arrow)) = ParseSymbol(Symbol::ThinArrow)
+                    .parse(state.clone())
+                    .or_else(|_| ParseSymbol(Symbol::FatArrow).parse(state.clone()))
+                {
                     let (state, return_value) = expect!(
                         state,
                         ParseTypeInfo.parse(state.clone()),
-                        "expected return type after `=>`"
+                        "expected return type after arrow"
                     );
 
                     (
                         state,
                         TypeInfo::Callback {
-                            arguments: types,
+                            arguments,
                             parentheses: ContainedSpan::new(start_parenthese, end_parenthese),
                             arrow,
                             return_type: Box::new(return_value),
                         },
                     )
                 } else {
+                    // Only the arguments of a callback can have names, such as `(count: number) -> ()`
+                    if let Some(name) = arguments.iter().find_map(TypeArgument::name) {
+                        return Err(InternalAstError::UnexpectedToken {
+                            token: name.clone(),
+                            additional: Some("only callback arguments can be named"),
+                        });
+                    }
+
+                    let mut types = Punctuated::new();
+                    for pair in arguments.into_pairs() {
+                        types.push(pair.map(|argument| argument.type_info));
+                    }
+
                     (
                         state,
                         TypeInfo::Tuple {
@@ -1376,6 +1489,36 @@ cfg_if::cfg_if! {
             }
         });
 
+        #[derive(Clone, Debug, PartialEq)]
+        struct ParseTypeArgument;
+        define_parser!(
+            ParseTypeArgument,
+            TypeArgument<'a>,
+            |_, state: ParserState<'a>| {
+                // An identifier followed by a colon is a name, otherwise it's the start of the type
+                let (state, name) = match ParseIdentifier.parse(state.clone()) {
+                    Ok((name_state, name)) => match ParseSymbol(Symbol::Colon).parse(name_state) {
+                        Ok((state, colon)) => (state, Some((name, colon))),
+                        Err(_) => (state, None),
+                    },
+
+                    Err(_) => (state, None),
+                };
+
+                let (state, type_info) = if name.is_some() {
+                    expect!(
+                        state,
+                        ParseTypeInfo.parse(state.clone()),
+                        "expected type after argument name"
+                    )
+                } else {
+                    ParseTypeInfo.parse(state.clone())?
+                };
+
+                Ok((state, TypeArgument { name, type_info }))
+            }
+        );
+
         #[derive(Clone, Debug, PartialEq)]
         struct ParseTypeField;
         define_parser!(
@@ -1454,15 +1597,96 @@ cfg_if::cfg_if! {
                 ))
             }
         );
+
+        #[derive(Clone, Debug, PartialEq)]
+        struct ParseCompoundAssignment;
+        define_parser!(
+            ParseCompoundAssignment,
+            CompoundAssignment<'a>,
+            |_, state: ParserState<'a>| {
+                let (state, lhs) = ParseVar.parse(state.clone())?;
+                let (state, compound_operator) = ParseCompoundOp.parse(state.clone())?;
+                let (state, rhs) =
+                    expect!(state, ParseExpression.parse(state.clone()), "expected value");
+
+                Ok((
+                    state,
+                    CompoundAssignment {
+                        lhs,
+                        compound_operator,
+                        rhs,
+                    },
+                ))
+            }
+        );
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "lua52")] {
+        #[derive(Clone, Debug, PartialEq)]
+        struct ParseGoto;
+        define_parser!(ParseGoto, Goto<'a>, |_, state: ParserState<'a>| {
+            // `goto` is tokenized as an identifier, which ParseIdentifier doesn't accept as a name
+            let goto_token = state.peek();
+            if goto_token.token_kind() != TokenKind::Identifier
+                || goto_token.to_string() != "goto"
+            {
+                return Err(InternalAstError::NoMatch);
+            }
+
+            let state = state.advance().ok_or(InternalAstError::NoMatch)?;
+
+            let (state, label_name) = expect!(
+                state,
+                ParseIdentifier.parse(state.clone()),
+                "expected label name after `goto`"
+            );
+
+            Ok((
+                state,
+                Goto {
+                    goto_token,
+                    label_name,
+                },
+            ))
+        });
+
+        #[derive(Clone, Debug, PartialEq)]
+        struct ParseLabel;
+        define_parser!(ParseLabel, Label<'a>, |_, state: ParserState<'a>| {
+            let (state, left_colons) = ParseSymbol(Symbol::TwoColons).parse(state.clone())?;
+            let (state, name) = expect!(
+                state,
+                ParseIdentifier.parse(state.clone()),
+                "expected label name after `::`"
+            );
+
+            let (state, right_colons) = expect!(
+                state,
+                ParseSymbol(Symbol::TwoColons).parse(state.clone()),
+                "expected `::` after label name"
+            );
+
+            Ok((
+                state,
+                Label {
+                    left_colons,
+                    name,
+                    right_colons,
+                },
+            ))
+        });
     }
 }
 
 macro_rules! make_op_parser {
-	($enum:ident, $parser:ident, { $($operator:ident,)+ }) => {
+	($enum:ident, $parser:ident, { $($(#[$inner:meta])* $operator:ident,)+ }) => {
 		#[derive(Clone, Debug, PartialEq)]
         struct $parser;
         define_parser!($parser, $enum<'a>, |_, state: ParserState<'a>| {
             $(
+                $(#[$inner])*
                 if let Ok((state, operator)) = ParseSymbol(Symbol::$operator).parse(state.clone()) {
                     return Ok((state.clone(), $enum::$operator(operator)));
                 }
@@ -1473,6 +1697,7 @@ macro_rules! make_op_parser {
 			if let Some(x) = None {
 				match x {
 					$(
+						$(#[$inner])*
 						$enum::$operator(_) => {},
 					)+
 				}
@@ -1500,6 +1725,18 @@ make_op_parser!(BinOp, ParseBinOp,
         TildeEqual,
         TwoDots,
         TwoEqual,
+        #[cfg(feature = "lua53")]
+        Ampersand,
+        #[cfg(feature = "lua53")]
+        DoubleSlash,
+        #[cfg(feature = "lua53")]
+        Pipe,
+        #[cfg(feature = "lua53")]
+        Tilde,
+        #[cfg(feature = "lua53")]
+        TwoGreaterThan,
+        #[cfg(feature = "lua53")]
+        TwoLessThan,
     }
 );
 
@@ -1508,6 +1745,21 @@ make_op_parser!(UnOp, ParseUnOp,
         Minus,
         Not,
         Hash,
+        #[cfg(feature = "lua53")]
+        Tilde,
+    }
+);
+
+#[cfg(feature = "roblox")]
+make_op_parser!(CompoundOp, ParseCompoundOp,
+    {
+        PlusEqual,
+        MinusEqual,
+        StarEqual,
+        SlashEqual,
+        PercentEqual,
+        CaretEqual,
+        TwoDotsEqual,
     }
 );
 
@@ -1516,6 +1768,7 @@ mod tests {
     use super::*;
     use crate::tokenizer::tokens;
     use pretty_assertions::assert_eq;
+    use std::iter::FromIterator;
 
     macro_rules! assert_state_eq {
         ($state: expr, $index: expr, $tokens: ident) => {
@@ -1530,6 +1783,26 @@ mod tests {
         };
     }
 
+    #[test]
+    fn test_arena_order() {
+        let code = "local x = 1 -- one\nreturn x + f(\"two\", { 3 })";
+        let tokens_in_order = tokens(code).unwrap();
+        let arena = Arena::from_iter(tokens_in_order.clone());
+
+        let raw_indices = arena.iter().map(|(index, _)| index.into_raw_parts().0);
+        assert!(raw_indices.eq(0..tokens_in_order.len()));
+
+        let state = ParserState::new(Arc::new(arena));
+        for (index, token) in tokens_in_order.iter().enumerate() {
+            let state = ParserState {
+                index,
+                ..state.clone()
+            };
+
+            assert_eq!(*state.peek(), *token);
+        }
+    }
+
     #[test]
     fn test_zero_or_more_empty() {
         let tokens = tokens!("local x");
@@ -17,15 +17,17 @@
 use crate::{
     node::Node,
     private::Sealed,
-    tokenizer::{Position, TokenReference},
+    tokenizer::{Position, Symbol, Token, TokenReference, TokenType},
+    util::{whitespace, whitespace_characters},
     visitors::{Visit, VisitMut, Visitor, VisitorMut},
 };
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 /// A punctuated sequence of node `T` separated by [`TokenReference`](../tokenizer/enum.TokenReference.html).
 /// Refer to the [module documentation](index.html) for more details.
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Punctuated<'a, T> {
     #[cfg_attr(feature = "serde", serde(borrow))]
@@ -133,7 +135,8 @@ impl<'a, T> Punctuated<'a, T> {
         self.pairs.iter_mut()
     }
 
-    /// Pops off the last [`Pair`](enum.Pair.html), if it isn't empty
+    /// Pops off the last [`Pair`](enum.Pair.html), if it isn't empty.
+    /// The punctuation of the pairs before it is left as it is.
     /// ```rust
     /// # use full_moon::ast::punctuated::{Pair, Punctuated};
     /// let mut punctuated = Punctuated::new();
@@ -144,16 +147,157 @@ impl<'a, T> Punctuated<'a, T> {
         self.pairs.pop()
     }
 
-    /// Pushes a new [`Pair`](enum.Pair.html) onto the sequence
+    /// Pushes a new [`Pair`](enum.Pair.html) onto the sequence.
+    /// So that only the last pair can be without punctuation, the pair before it is given a copy of the
+    /// last punctuation in the sequence if it has none, such as the `.` in `a.b`, or `, ` if there isn't any.
     /// ```rust
     /// # use full_moon::ast::punctuated::{Pair, Punctuated};
     /// let mut punctuated = Punctuated::new();
     /// punctuated.push(Pair::new(1, None));
-    /// assert_eq!(punctuated.pop(), Some(Pair::new(1, None)));
+    /// punctuated.push(Pair::new(2, None));
+    /// assert_eq!(punctuated.to_string(), "1, 2");
+    /// assert_eq!(punctuated.pop(), Some(Pair::new(2, None)));
     /// ```
     pub fn push(&mut self, pair: Pair<'a, T>) {
+        self.punctuate_last();
         self.pairs.push(pair);
     }
+
+    /// Pushes a value onto the end of the sequence without any punctuation after it.
+    /// If the value before it has no punctuation, it's given a `, `.
+    /// ```rust
+    /// # use full_moon::ast::punctuated::Punctuated;
+    /// let mut punctuated = Punctuated::new();
+    /// punctuated.push_value(1);
+    /// punctuated.push_value(2);
+    /// assert_eq!(punctuated.to_string(), "1, 2");
+    /// ```
+    pub fn push_value(&mut self, value: T) {
+        if let Some(Pair::End(_)) = self.pairs.last() {
+            let last = self.pairs.pop().unwrap().into_value();
+            self.pairs.push(Pair::Punctuated(last, comma()));
+        }
+
+        self.pairs.push(Pair::End(value));
+    }
+
+    /// Inserts a value at the index, moving the values after it along.
+    /// The value is given a copy of the last punctuation in the sequence, such as the `.` in `a.b`,
+    /// or `, ` if there isn't any, to separate it from the value after it.
+    /// If it's put at the end, it's separated from the value before it as by [`push`](#method.push),
+    /// unless the sequence ends in punctuation, such as `1, 2,`, in which case the value is punctuated
+    /// so that it still does.
+    ///
+    /// # Panics
+    /// Panics if the index is greater than the number of values, as [`Vec::insert`](https://doc.rust-lang.org/std/vec/struct.Vec.html#method.insert) does.
+    /// ```rust
+    /// # use full_moon::ast::punctuated::Punctuated;
+    /// let mut punctuated = Punctuated::new();
+    /// punctuated.extend(vec![1, 3]);
+    /// punctuated.insert(1, 2);
+    /// punctuated.insert(3, 4);
+    /// assert_eq!(punctuated.to_string(), "1, 2, 3, 4");
+    /// ```
+    pub fn insert(&mut self, index: usize, value: T) {
+        assert!(
+            index <= self.len(),
+            "insertion index (is {}) should be <= len (is {})",
+            index,
+            self.len()
+        );
+
+        match self.pairs.last() {
+            Some(Pair::End(_)) | None if index == self.len() => self.push(Pair::End(value)),
+            _ => {
+                let separator = self.separator();
+                self.pairs.insert(index, Pair::Punctuated(value, separator));
+            }
+        }
+    }
+
+    /// Removes the value at the index and gives it back, or returns `None` if there isn't one.
+    /// The punctuation after the value is removed with it. If it's the last value and has no punctuation,
+    /// the punctuation after the value before it is removed instead, so that the sequence doesn't end in any.
+    /// ```rust
+    /// # use full_moon::ast::punctuated::Punctuated;
+    /// let mut punctuated = Punctuated::new();
+    /// punctuated.extend(vec![1, 2, 3]);
+    /// assert_eq!(punctuated.remove(2), Some(3));
+    /// assert_eq!(punctuated.remove(0), Some(1));
+    /// assert_eq!(punctuated.remove(1), None);
+    /// assert_eq!(punctuated.to_string(), "2");
+    /// ```
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        if index >= self.len() {
+            return None;
+        }
+
+        let pair = self.pairs.remove(index);
+        if let Pair::End(_) = pair {
+            if let Some(last) = self.pairs.pop() {
+                self.pairs.push(Pair::End(last.into_value()));
+            }
+        }
+
+        Some(pair.into_value())
+    }
+
+    // Punctuates the last pair if it has no punctuation, so that another pair can follow it
+    fn punctuate_last(&mut self) {
+        if let Some(Pair::End(_)) = self.pairs.last() {
+            let separator = self.separator();
+            let value = self.pairs.pop().unwrap().into_value();
+            self.pairs.push(Pair::Punctuated(value, separator));
+        }
+    }
+
+    // A new copy of the last punctuation in the sequence, keeping any spaces after it but not comments or new lines,
+    // or `, ` if there isn't any
+    fn separator(&self) -> TokenReference<'a> {
+        let last = match self.pairs.iter().rev().find_map(Pair::punctuation) {
+            Some(last) => last,
+            None => return comma(),
+        };
+
+        TokenReference::new(
+            Vec::new(),
+            Token::new(last.token_type().clone()),
+            last.trailing_trivia()
+                .filter(|trivia| match whitespace_characters(trivia) {
+                    Some(characters) => !characters.contains('\n'),
+                    None => false,
+                })
+                .cloned()
+                .collect(),
+        )
+    }
+}
+
+// `, `, separating one value from the next
+fn comma<'a>() -> TokenReference<'a> {
+    TokenReference::new(
+        Vec::new(),
+        Token::new(TokenType::Symbol {
+            symbol: Symbol::Comma,
+        }),
+        vec![whitespace(" ")],
+    )
+}
+
+impl<'a, T> Default for Punctuated<'a, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T: fmt::Display> fmt::Display for Punctuated<'a, T> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for pair in &self.pairs {
+            pair.fmt(formatter)?;
+        }
+
+        Ok(())
+    }
 }
 
 impl<'a, T> Sealed for Punctuated<'a, T> {}
@@ -172,6 +316,16 @@ impl<'a, T: Node> Node for Punctuated<'a, T> {
             .collect::<Vec<_>>()
             .similar(&other.into_iter().collect::<Vec<_>>())
     }
+
+    fn children(&self) -> Vec<&dyn Node> {
+        let mut children = Vec::new();
+        self.collect_as_children(&mut children);
+        children
+    }
+
+    fn collect_as_children<'s>(&'s self, children: &mut Vec<&'s dyn Node>) {
+        self.pairs.collect_as_children(children);
+    }
 }
 
 impl<'a, T: Visit<'a>> Visit<'a> for Punctuated<'a, T> {
@@ -186,9 +340,29 @@ impl<'a, T: VisitMut<'a>> VisitMut<'a> for Punctuated<'a, T> {
     }
 }
 
+/// Pushes every pair onto the sequence with [`push`](struct.Punctuated.html#method.push).
 impl<'a, T> std::iter::Extend<Pair<'a, T>> for Punctuated<'a, T> {
     fn extend<I: IntoIterator<Item = Pair<'a, T>>>(&mut self, iter: I) {
-        self.pairs.extend(iter);
+        for pair in iter {
+            self.push(pair);
+        }
+    }
+}
+
+/// Pushes every value onto the sequence with [`push`](struct.Punctuated.html#method.push),
+/// separating them with the sequence's punctuation, or `, ` if it has none.
+/// ```rust
+/// # use full_moon::ast::punctuated::{Pair, Punctuated};
+/// let mut punctuated = Punctuated::new();
+/// punctuated.push(Pair::new(1, None));
+/// punctuated.extend(vec![2, 3]);
+/// assert_eq!(punctuated.to_string(), "1, 2, 3");
+/// ```
+impl<'a, T> std::iter::Extend<T> for Punctuated<'a, T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(Pair::End(value));
+        }
     }
 }
 
@@ -282,6 +456,15 @@ pub enum Pair<'a, T> {
     ),
 }
 
+impl<'a, T: fmt::Display> fmt::Display for Pair<'a, T> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Pair::End(node) => write!(formatter, "{:#}", node),
+            Pair::Punctuated(node, punctuation) => write!(formatter, "{:#}{:#}", node, punctuation),
+        }
+    }
+}
+
 impl<'a, T> Pair<'a, T> {
     /// Creates a `Pair` with node `T` and optional punctuation
     /// ```rust
@@ -389,6 +572,19 @@ impl<'a, T: Node> Node for Pair<'a, T> {
     fn similar(&self, other: &Self) -> bool {
         self.value().similar(other.value())
     }
+
+    fn children(&self) -> Vec<&dyn Node> {
+        let mut children = Vec::new();
+        self.collect_as_children(&mut children);
+        children
+    }
+
+    fn collect_as_children<'s>(&'s self, children: &mut Vec<&'s dyn Node>) {
+        self.value().collect_as_children(children);
+        if let Some(punctuation) = self.punctuation() {
+            children.push(punctuation);
+        }
+    }
 }
 
 impl<'a, T: Visit<'a>> Visit<'a> for Pair<'a, T> {
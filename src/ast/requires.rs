@@ -0,0 +1,154 @@
+//! The modules code depends on through calls such as `require("module")`, found with
+//! [`Ast::requires`](../struct.Ast.html#method.requires), or with other loaders through
+//! [`Ast::requires_with`](../struct.Ast.html#method.requires_with).
+//! Module names written as paths, such as `a/b/c`, can be turned into the same form as `a.b.c`
+//! with [`normalize_module_name`](fn.normalize_module_name.html).
+use super::*;
+use crate::visitors::{Visit, Visitor};
+
+/// A call to a module loader, such as `require("module")`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Require<'a> {
+    loader: TokenReference<'a>,
+    module: Option<TokenReference<'a>>,
+}
+
+impl<'a> Require<'a> {
+    /// The name of the loader being called, the `require` part of `require("module")`
+    pub fn loader(&self) -> &TokenReference<'a> {
+        &self.loader
+    }
+
+    /// The string literal of the module being loaded, the `"module"` part of `require("module")`.
+    /// Returns `None` if the require is [dynamic](#method.is_dynamic).
+    pub fn module(&self) -> Option<&TokenReference<'a>> {
+        self.module.as_ref()
+    }
+
+    /// The name of the module being loaded, the `module` part of `require("module")`.
+    /// Returns `None` if the require is [dynamic](#method.is_dynamic).
+    pub fn module_name(&self) -> Option<Cow<'a, str>> {
+        match &*self.module.as_ref()?.token_type() {
+            TokenType::StringLiteral { literal, .. } => Some(literal.clone()),
+            _ => unreachable!("module of a require is not a string literal"),
+        }
+    }
+
+    /// The name of the module being loaded with its path separators normalized,
+    /// so that `require("a/b/c")`, `require("a\\b\\c")` and `require("a.b.c")` all give `a.b.c`.
+    /// Paths going up a directory, such as `require("../a")`, keep their `..` and give `../a`,
+    /// so they're never mistaken for a module of the same name in this directory.
+    /// Returns `None` if the require is [dynamic](#method.is_dynamic).
+    pub fn normalized_module_name(&self) -> Option<String> {
+        self.module_name()
+            .map(|module_name| normalize_module_name(&module_name))
+    }
+
+    /// Whether the module being loaded isn't a single string literal, such as `require(script.Parent.module)`
+    pub fn is_dynamic(&self) -> bool {
+        self.module.is_none()
+    }
+}
+
+/// Normalizes the name of a module, as passed to `require`, into its canonical form,
+/// which has its parts separated by dots, such as `a.b.c`.
+/// Both `/` and `\` are treated as separators the same way `.` is,
+/// and empty parts, such as those in `a//b` or `./a`, are dropped.
+/// Paths that go up a directory with `..`, such as `../a`, can't be written with dots,
+/// so they're kept as a path with `/` separators instead, such as `../a/b` for `..\a\b`.
+///
+/// ```rust
+/// # use full_moon::ast::requires::normalize_module_name;
+/// assert_eq!(normalize_module_name("a/b/c"), "a.b.c");
+/// assert_eq!(normalize_module_name("a.b.c"), "a.b.c");
+/// assert_eq!(normalize_module_name("./a\\b"), "a.b");
+/// assert_eq!(normalize_module_name("..\\a\\b"), "../a/b");
+/// ```
+pub fn normalize_module_name(module_name: &str) -> String {
+    let segments: Vec<_> = module_name
+        .split(&['/', '\\'][..])
+        .filter(|segment| !segment.is_empty() && *segment != ".")
+        .collect();
+
+    if segments.contains(&"..") {
+        return segments.join("/");
+    }
+
+    module_name
+        .split(&['.', '/', '\\'][..])
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+pub(crate) struct RequireVisitor<'a, 'b> {
+    loaders: &'b [&'b str],
+    pub(crate) requires: Vec<Require<'a>>,
+}
+
+impl<'a, 'b> RequireVisitor<'a, 'b> {
+    pub(crate) fn new(loaders: &'b [&'b str]) -> Self {
+        Self {
+            loaders,
+            requires: Vec::new(),
+        }
+    }
+
+    pub(crate) fn find(mut self, block: &Block<'a>) -> Vec<Require<'a>> {
+        block.visit(&mut self);
+        self.requires
+    }
+
+    fn check_call(&mut self, prefix: &Prefix<'a>, suffix: Option<&Suffix<'a>>) {
+        let loader = match prefix {
+            Prefix::Name(name) => name,
+            _ => return,
+        };
+
+        if !self.loaders.contains(&loader.to_string().as_str()) {
+            return;
+        }
+
+        let arguments = match suffix {
+            Some(Suffix::Call(Call::AnonymousCall(arguments))) => arguments,
+            _ => return,
+        };
+
+        let module = match arguments {
+            FunctionArgs::String(string) => Some(string.clone()),
+
+            FunctionArgs::Parentheses { arguments, .. } if arguments.len() == 1 => {
+                match arguments.iter().next() {
+                    Some(Expression::Value {
+                        value, binop: None, ..
+                    }) => match &**value {
+                        Value::String(string) => Some(string.clone()),
+                        _ => None,
+                    },
+
+                    _ => None,
+                }
+            }
+
+            _ => None,
+        };
+
+        self.requires.push(Require {
+            loader: loader.clone(),
+            module,
+        });
+    }
+}
+
+impl<'a, 'b> Visitor<'a> for RequireVisitor<'a, 'b> {
+    fn visit_function_call(&mut self, call: &FunctionCall<'a>) {
+        self.check_call(call.prefix(), call.iter_suffixes().next());
+    }
+
+    fn visit_var_expression(&mut self, var_expression: &VarExpression<'a>) {
+        self.check_call(
+            var_expression.prefix(),
+            var_expression.iter_suffixes().next(),
+        );
+    }
+}
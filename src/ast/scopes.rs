@@ -0,0 +1,311 @@
+//! Resolves names to the variables they refer to, following Lua's scoping rules.
+//! Every use of a name is a [`Reference`](struct.Reference.html) to either a local or a global
+//! ([`Ast::references`](../struct.Ast.html#method.references)), locals declared with the same name as one
+//! already in scope are a [`Shadow`](struct.Shadow.html) ([`Ast::shadowed_locals`](../struct.Ast.html#method.shadowed_locals)),
+//! and assignments to globals that aren't known to exist are often a forgotten `local`
+//! ([`Ast::implicit_globals`](../struct.Ast.html#method.implicit_globals)).
+use super::*;
+use crate::visitors::{Visit, Visitor};
+use std::collections::HashMap;
+
+/// A use of a variable name, such as `x` in `print(x)` or `x = 1`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Reference<'a> {
+    name: TokenReference<'a>,
+    local: Option<TokenReference<'a>>,
+}
+
+impl<'a> Reference<'a> {
+    /// The name being used
+    pub fn name(&self) -> &TokenReference<'a> {
+        &self.name
+    }
+
+    /// The name token that declared the local variable being used, such as the `x` in `local x = 1`.
+    /// For `self` inside a method, this is the `:` of the method's name.
+    /// Returns `None` if the name is a [global](#method.is_global).
+    pub fn local(&self) -> Option<&TokenReference<'a>> {
+        self.local.as_ref()
+    }
+
+    /// Whether the name doesn't refer to any local variable in scope
+    pub fn is_global(&self) -> bool {
+        self.local.is_none()
+    }
+}
+
+/// A local variable declared with the same name as one already in scope from an enclosing block,
+/// such as the second `x` in `local x = 1 do local x = 2 end`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Shadow<'a> {
+    local: TokenReference<'a>,
+    shadowed: TokenReference<'a>,
+}
+
+impl<'a> Shadow<'a> {
+    /// The name token that declared the new local, such as the `x` in `local x = 2`.
+    /// This can also be a parameter, a for loop variable, or the name of a local function.
+    pub fn local(&self) -> &TokenReference<'a> {
+        &self.local
+    }
+
+    /// The name token that declared the local being shadowed.
+    /// For `self` inside a method, this is the `:` of the method's name.
+    pub fn shadowed(&self) -> &TokenReference<'a> {
+        &self.shadowed
+    }
+
+    /// Whether the new local's name starts with `_`, such as `_` or `_x`,
+    /// which is commonly used to mark locals that are shadowed on purpose or unused
+    pub fn is_intentional(&self) -> bool {
+        self.local.to_string().starts_with('_')
+    }
+}
+
+/// The globals Lua 5.1 comes with, such as `print` and `table`,
+/// used by [`Ast::implicit_globals`](../struct.Ast.html#method.implicit_globals) as the globals known to exist
+pub const STANDARD_GLOBALS: &[&str] = &[
+    "_G",
+    "_VERSION",
+    "assert",
+    "collectgarbage",
+    "coroutine",
+    "debug",
+    "dofile",
+    "error",
+    "getfenv",
+    "getmetatable",
+    "io",
+    "ipairs",
+    "load",
+    "loadfile",
+    "loadstring",
+    "math",
+    "module",
+    "newproxy",
+    "next",
+    "os",
+    "package",
+    "pairs",
+    "pcall",
+    "print",
+    "rawequal",
+    "rawget",
+    "rawset",
+    "require",
+    "select",
+    "setfenv",
+    "setmetatable",
+    "string",
+    "table",
+    "tonumber",
+    "tostring",
+    "type",
+    "unpack",
+    "xpcall",
+];
+
+type Scope<'a> = Vec<(String, TokenReference<'a>)>;
+
+pub(crate) struct ScopeVisitor<'a> {
+    scopes: Vec<Scope<'a>>,
+    // Locals to declare when a block is entered, such as function parameters and loop variables.
+    // Blocks are keyed by their address, as they're only borrowed while visiting.
+    pending: HashMap<*const Block<'a>, Scope<'a>>,
+    // The body of a `repeat`, whose scope is kept open until after its `until` condition
+    repeat_bodies: Vec<*const Block<'a>>,
+    references: Vec<Reference<'a>>,
+    shadows: Vec<Shadow<'a>>,
+    // Names assigned to that don't refer to any local, such as `x` in `x = 1`
+    global_assignments: Vec<TokenReference<'a>>,
+}
+
+impl<'a> ScopeVisitor<'a> {
+    pub(crate) fn new() -> Self {
+        Self {
+            scopes: Vec::new(),
+            pending: HashMap::new(),
+            repeat_bodies: Vec::new(),
+            references: Vec::new(),
+            shadows: Vec::new(),
+            global_assignments: Vec::new(),
+        }
+    }
+
+    pub(crate) fn resolve(mut self, block: &Block<'a>) -> Vec<Reference<'a>> {
+        block.visit(&mut self);
+        self.references
+    }
+
+    pub(crate) fn find_shadows(mut self, block: &Block<'a>) -> Vec<Shadow<'a>> {
+        block.visit(&mut self);
+        self.shadows
+    }
+
+    pub(crate) fn find_implicit_globals(
+        mut self,
+        block: &Block<'a>,
+        allowed: &[&str],
+    ) -> Vec<TokenReference<'a>> {
+        block.visit(&mut self);
+        self.global_assignments
+            .into_iter()
+            .filter(|name| !allowed.contains(&name.to_string().as_str()))
+            .collect()
+    }
+
+    // Checks whether the name shadows a local in the given scopes, which are the ones enclosing where it's declared
+    fn check_shadowing(&mut self, name: &TokenReference<'a>, enclosing: usize) {
+        let text = name.to_string();
+        let shadowed = self.scopes[..enclosing]
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.iter().rev())
+            .find(|(local, _)| *local == text);
+
+        if let Some((_, shadowed)) = shadowed {
+            self.shadows.push(Shadow {
+                local: name.clone(),
+                shadowed: shadowed.clone(),
+            });
+        }
+    }
+
+    fn declare(&mut self, name: &TokenReference<'a>) {
+        self.check_shadowing(name, self.scopes.len().saturating_sub(1));
+        self.scopes
+            .last_mut()
+            .expect("declared a local outside of a block")
+            .push((name.to_string(), name.clone()));
+    }
+
+    // Declares a name written in the code, such as a parameter, in a block that isn't entered yet,
+    // so every scope encloses it
+    fn declare_name_in(&mut self, block: &Block<'a>, name: &TokenReference<'a>) {
+        self.check_shadowing(name, self.scopes.len());
+        self.declare_in(block, name.to_string(), name);
+    }
+
+    fn declare_in(&mut self, block: &Block<'a>, name: String, token: &TokenReference<'a>) {
+        self.pending
+            .entry(block as *const _)
+            .or_default()
+            .push((name, token.clone()));
+    }
+
+    // The name token that declared the local the name refers to, if any
+    fn resolve_local(&self, name: &TokenReference<'a>) -> Option<TokenReference<'a>> {
+        let text = name.to_string();
+        self.scopes
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.iter().rev())
+            .find(|(local, _)| *local == text)
+            .map(|(_, token)| token.clone())
+    }
+
+    fn reference(&mut self, name: &TokenReference<'a>) {
+        let local = self.resolve_local(name);
+        self.references.push(Reference {
+            name: name.clone(),
+            local,
+        });
+    }
+
+    fn assign(&mut self, name: &TokenReference<'a>) {
+        if self.resolve_local(name).is_none() {
+            self.global_assignments.push(name.clone());
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for ScopeVisitor<'a> {
+    fn visit_block(&mut self, block: &Block<'a>) {
+        let scope = self
+            .pending
+            .remove(&(block as *const _))
+            .unwrap_or_default();
+        self.scopes.push(scope);
+    }
+
+    fn visit_block_end(&mut self, block: &Block<'a>) {
+        if self.repeat_bodies.last() != Some(&(block as *const _)) {
+            self.scopes.pop();
+        }
+    }
+
+    fn visit_repeat(&mut self, repeat: &Repeat<'a>) {
+        self.repeat_bodies.push(repeat.block());
+    }
+
+    fn visit_repeat_end(&mut self, _repeat: &Repeat<'a>) {
+        self.repeat_bodies.pop();
+        self.scopes.pop();
+    }
+
+    fn visit_numeric_for(&mut self, numeric_for: &NumericFor<'a>) {
+        let index_variable = numeric_for.index_variable();
+        self.declare_name_in(numeric_for.block(), index_variable);
+    }
+
+    fn visit_generic_for(&mut self, generic_for: &GenericFor<'a>) {
+        for name in generic_for.names() {
+            self.declare_name_in(generic_for.block(), name);
+        }
+    }
+
+    fn visit_assignment(&mut self, assignment: &Assignment<'a>) {
+        for var in assignment.var_list() {
+            if let Var::Name(name) = var {
+                self.assign(name);
+            }
+        }
+    }
+
+    fn visit_function_declaration(&mut self, declaration: &FunctionDeclaration<'a>) {
+        if let Some(name) = declaration.name().names().iter().next() {
+            self.reference(name);
+
+            // `function x() end` assigns to `x`, while `function x.y() end` only indexes it
+            if declaration.name().names().len() == 1 && declaration.name().method_colon().is_none()
+            {
+                self.assign(name);
+            }
+        }
+
+        if let Some(colon) = declaration.name().method_colon() {
+            self.declare_in(declaration.body().block(), "self".to_owned(), colon);
+        }
+    }
+
+    fn visit_function_body(&mut self, body: &FunctionBody<'a>) {
+        for parameter in body.iter_parameters() {
+            if let Parameter::Name(name) = parameter {
+                self.declare_name_in(body.block(), name);
+            }
+        }
+    }
+
+    fn visit_local_function(&mut self, local_function: &LocalFunction<'a>) {
+        self.declare(local_function.name());
+    }
+
+    // The names of a local assignment are only in scope after its expressions
+    fn visit_local_assignment_end(&mut self, local_assignment: &LocalAssignment<'a>) {
+        for name in local_assignment.name_list() {
+            self.declare(name);
+        }
+    }
+
+    fn visit_prefix(&mut self, prefix: &Prefix<'a>) {
+        if let Prefix::Name(name) = prefix {
+            self.reference(name);
+        }
+    }
+
+    fn visit_var(&mut self, var: &Var<'a>) {
+        if let Var::Name(name) = var {
+            self.reference(name);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+// Builds the indented tree used by Ast::to_sexpr
+use super::{span::ContainedSpan, *};
+use crate::visitors::{visited_nodes, Visitor};
+use std::fmt::Write;
+
+#[derive(Default)]
+pub(crate) struct SExpr {
+    output: String,
+    depth: usize,
+    // Whether the last line written is still the opening line of the current node
+    line_open: bool,
+}
+
+impl SExpr {
+    pub(crate) fn into_string(self) -> String {
+        self.output
+    }
+
+    fn new_line(&mut self) {
+        if !self.output.is_empty() {
+            self.output.push('\n');
+        }
+
+        for _ in 0..self.depth {
+            self.output.push_str("  ");
+        }
+    }
+
+    fn open(&mut self, kind: &str) {
+        self.new_line();
+        self.output.push('(');
+        self.output.push_str(kind);
+        self.depth += 1;
+        self.line_open = true;
+    }
+
+    fn close(&mut self) {
+        self.output.push(')');
+        self.depth -= 1;
+        self.line_open = false;
+    }
+
+    fn token(&mut self, token: &TokenReference) {
+        if self.line_open {
+            self.output.push(' ');
+        } else {
+            self.new_line();
+        }
+
+        write!(self.output, "{:?}", token.to_string()).expect("writing to a String can't fail");
+    }
+}
+
+macro_rules! sexpr_nodes {
+    (ast: {
+        $($visit_name:ident => $ast_type:ident,)+
+
+        $(#[$meta:meta] {
+            $($meta_visit_name:ident => $meta_ast_type:ident,)+
+        })+
+    }) => {
+        paste::item! {
+            $(
+                fn $visit_name(&mut self, _node: &$ast_type<'ast>) {
+                    self.open(stringify!($ast_type));
+                }
+
+                fn [<$visit_name _end>](&mut self, _node: &$ast_type<'ast>) {
+                    self.close();
+                }
+            )+
+
+            $(
+                $(
+                    #[$meta]
+                    fn $meta_visit_name(&mut self, _node: &$meta_ast_type<'ast>) {
+                        self.open(stringify!($meta_ast_type));
+                    }
+
+                    #[$meta]
+                    fn [<$meta_visit_name _end>](&mut self, _node: &$meta_ast_type<'ast>) {
+                        self.close();
+                    }
+                )+
+            )+
+        }
+    };
+}
+
+impl<'ast> Visitor<'ast> for SExpr {
+    visited_nodes!(sexpr_nodes! {});
+
+    // The tokens of a contained span aren't visited, so they're written here instead,
+    // on either side of what's inside of it
+    fn visit_contained_span(&mut self, node: &ContainedSpan<'ast>) {
+        self.open("ContainedSpan");
+        self.token(node.tokens().0);
+    }
+
+    fn visit_contained_span_end(&mut self, node: &ContainedSpan<'ast>) {
+        self.token(node.tokens().1);
+        self.close();
+    }
+
+    fn visit_token(&mut self, token: &TokenReference<'ast>) {
+        self.token(token);
+    }
+}
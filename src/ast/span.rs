@@ -58,6 +58,10 @@ impl<'a> Node for ContainedSpan<'a> {
     fn similar(&self, other: &Self) -> bool {
         self.tokens.0.similar(&other.tokens.0) && self.tokens.1.similar(&other.tokens.1)
     }
+
+    fn children(&self) -> Vec<&dyn Node> {
+        vec![&self.tokens.0, &self.tokens.1]
+    }
 }
 
 impl<'a> Sealed for ContainedSpan<'a> {}
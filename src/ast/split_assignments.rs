@@ -0,0 +1,151 @@
+// Splits assignments such as `a, b = 1, 2` into one assignment per variable, refer to `Assignment::split`
+use super::*;
+use crate::visitors::{VisitMut, VisitorMut};
+use std::collections::HashSet;
+
+impl<'a> Assignment<'a> {
+    /// The assignment split into one assignment per variable, turning `a, b = 1, 2` into `a = 1` and `b = 2`,
+    /// each of which goes on its own line.
+    /// This is only done when every variable is a different name and there are as many values as variables,
+    /// each of which is a constant, such as `1` or `"x"`, or a name that isn't being assigned to,
+    /// so that assigning the values one at a time gives the same result.
+    /// Returns `None` for anything else, such as `a, b = f()`, `a, b = ...`, `a, b = b, a`, or `t.x, y = 1, 2`,
+    /// and when there are comments in between the variables or values, which would be lost,
+    /// such as in `a --[[x]], b = 1, 2` or `a, b = 1, -- one` followed by `2` on the next line.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::Stmt;
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("a, b = 1, 2")?;
+    /// let assignment = match ast.nodes().iter_stmts().next() {
+    ///     Some(Stmt::Assignment(assignment)) => assignment,
+    ///     _ => unreachable!(),
+    /// };
+    ///
+    /// let assignments = assignment.split().unwrap();
+    /// assert_eq!(assignments[0].to_string(), "a = 1");
+    /// assert_eq!(assignments[1].to_string(), "\nb = 2");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn split(&self) -> Option<Vec<Assignment<'a>>> {
+        if self.var_list.len() < 2 || self.var_list.len() != self.expr_list.len() {
+            return None;
+        }
+
+        let mut names = Vec::new();
+        let mut assigned = HashSet::new();
+        for var in &self.var_list {
+            match var {
+                Var::Name(name) if assigned.insert(name.to_string()) => names.push(name),
+                _ => return None,
+            }
+        }
+
+        let side_effect_free = self.expr_list.iter().all(|expression| {
+            expression.eval_constant().is_some()
+                || as_name(expression)
+                    .map(|name| !assigned.contains(&name.to_string()))
+                    .unwrap_or(false)
+        });
+
+        if !side_effect_free {
+            return None;
+        }
+
+        // Only the trivia before the first name, after the last one and around the values is kept
+        let last = names.len() - 1;
+        let mut commas = self
+            .var_list
+            .pairs()
+            .filter_map(Pair::punctuation)
+            .chain(self.expr_list.pairs().filter_map(Pair::punctuation));
+
+        let loses_comments = names.iter().enumerate().any(|(index, name)| {
+            (index > 0 && name.leading_trivia().any(is_comment))
+                || (index < last && name.trailing_trivia().any(is_comment))
+        }) || self.expr_list.iter().take(last).any(has_comments)
+            || commas.any(has_comments);
+
+        if loses_comments {
+            return None;
+        }
+
+        // Every name takes the trivia of the last one, which is what's in between it and the `=`
+        let trailing_trivia: Vec<_> = names[last].trailing_trivia().cloned().collect();
+        let indentation = indentation(names[0]);
+        let mut assignments = Vec::new();
+
+        // Every assignment after the first starts a new line, with the same indentation as the first
+        for (index, (name, expression)) in names.iter().zip(self.expr_list.iter()).enumerate() {
+            let leading_trivia = if index == 0 {
+                name.leading_trivia().cloned().collect()
+            } else {
+                let mut leading_trivia = vec![Token::new(TokenType::Whitespace {
+                    characters: "\n".into(),
+                })];
+                leading_trivia.extend(indentation.clone());
+                leading_trivia
+            };
+
+            let var = Var::Name(TokenReference::new(
+                leading_trivia,
+                (***name).clone(),
+                trailing_trivia.clone(),
+            ));
+
+            let mut var_list = Punctuated::new();
+            var_list.push(Pair::End(var));
+
+            let mut expr_list = Punctuated::new();
+            expr_list.push(Pair::End(expression.clone()));
+
+            assignments.push(Assignment {
+                var_list,
+                equal_token: self.equal_token.clone(),
+                expr_list,
+            });
+        }
+
+        Some(assignments)
+    }
+}
+
+// Splits every assignment in the block that can be split
+pub(crate) fn split_assignments(block: &mut Block) {
+    block.visit_mut(&mut AssignmentSplitter);
+}
+
+struct AssignmentSplitter;
+
+impl<'a> VisitorMut<'a> for AssignmentSplitter {
+    fn visit_block(&mut self, block: &mut Block<'a>) {
+        let mut stmts = Vec::new();
+
+        for (stmt, semicolon) in std::mem::take(&mut block.stmts) {
+            let assignments = match &stmt {
+                Stmt::Assignment(assignment) => assignment.split(),
+                _ => None,
+            };
+
+            match assignments {
+                Some(assignments) => {
+                    let last = assignments.len() - 1;
+                    for (index, assignment) in assignments.into_iter().enumerate() {
+                        let semicolon = if index == last {
+                            semicolon.clone()
+                        } else {
+                            None
+                        };
+
+                        stmts.push((Stmt::Assignment(assignment), semicolon));
+                    }
+                }
+
+                None => stmts.push((stmt, semicolon)),
+            }
+        }
+
+        block.stmts = stmts;
+    }
+}
@@ -0,0 +1,42 @@
+// Counts the statements in the code, and the keywords that end or split up the blocks in them,
+// used by Ast::statement_count and Ast::logical_lines
+use super::*;
+use crate::visitors::{Visit, Visitor};
+
+pub(crate) struct StatementCounter {
+    pub(crate) statements: usize,
+    pub(crate) block_keywords: usize,
+}
+
+impl StatementCounter {
+    pub(crate) fn new() -> Self {
+        Self {
+            statements: 0,
+            block_keywords: 0,
+        }
+    }
+
+    pub(crate) fn count(mut self, block: &Block) -> Self {
+        block.visit(&mut self);
+        self
+    }
+}
+
+impl<'a> Visitor<'a> for StatementCounter {
+    fn visit_stmt(&mut self, _: &Stmt<'a>) {
+        self.statements += 1;
+    }
+
+    fn visit_last_stmt(&mut self, _: &LastStmt<'a>) {
+        self.statements += 1;
+    }
+
+    fn visit_symbol(&mut self, token: &TokenReference<'a>) {
+        if let TokenType::Symbol {
+            symbol: Symbol::ElseIf | Symbol::Else | Symbol::End | Symbol::Until,
+        } = &*token.token_type()
+        {
+            self.block_keywords += 1;
+        }
+    }
+}
@@ -0,0 +1,26 @@
+// Collects every table constructor in the code, used by Ast::table_constructors
+use super::*;
+use crate::visitors::{Visit, Visitor};
+
+pub(crate) struct TableConstructorVisitor<'a> {
+    table_constructors: Vec<TableConstructor<'a>>,
+}
+
+impl<'a> TableConstructorVisitor<'a> {
+    pub(crate) fn new() -> Self {
+        Self {
+            table_constructors: Vec::new(),
+        }
+    }
+
+    pub(crate) fn find(mut self, block: &Block<'a>) -> Vec<TableConstructor<'a>> {
+        block.visit(&mut self);
+        self.table_constructors
+    }
+}
+
+impl<'a> Visitor<'a> for TableConstructorVisitor<'a> {
+    fn visit_table_constructor(&mut self, table_constructor: &TableConstructor<'a>) {
+        self.table_constructors.push(table_constructor.clone());
+    }
+}
@@ -0,0 +1,281 @@
+// Rewrites table constructors in a consistent style, refer to `TableConstructor::to_multi_line`
+use super::{owned::Owned, *};
+use crate::tokenizer::{self, TokenKind};
+
+fn comments<'a, 'b>(trivia: impl IntoIterator<Item = &'b Token<'a>>) -> Vec<String>
+where
+    'a: 'b,
+{
+    trivia
+        .into_iter()
+        .filter(|token| is_comment(token))
+        .map(ToString::to_string)
+        .collect()
+}
+
+// The code of a field, separated from the comments around it
+struct FieldText {
+    leading_comments: Vec<String>,
+    text: String,
+    trailing_comments: Vec<String>,
+}
+
+impl FieldText {
+    fn new(field: &Field, separator: Option<&TokenReference>) -> Self {
+        let code = field.to_string();
+        let tokens = tokenizer::tokens(&code).expect("fields are made of valid tokens");
+        let significant: Vec<usize> = (0..tokens.len())
+            .filter(|&index| {
+                !tokens[index].token_type().ignore() && tokens[index].token_kind() != TokenKind::Eof
+            })
+            .collect();
+
+        let first = significant[0];
+        let last = significant[significant.len() - 1];
+        let slice = |start: usize, end: usize| -> &str {
+            &code[tokens[start].start_position().bytes()..tokens[end].end_position().bytes()]
+        };
+
+        // Where the `=` of a keyed field is among the significant tokens
+        let equal = match field {
+            Field::ExpressionKey { .. } => {
+                let mut depth = 0;
+                significant
+                    .iter()
+                    .position(|&index| {
+                        match &*tokens[index].token_type() {
+                            TokenType::Symbol {
+                                symbol: Symbol::LeftBracket,
+                            } => depth += 1,
+                            TokenType::Symbol {
+                                symbol: Symbol::RightBracket,
+                            } => depth -= 1,
+                            _ => {}
+                        }
+
+                        depth == 0
+                    })
+                    .map(|right_bracket| right_bracket + 1)
+            }
+
+            Field::NameKey { .. } => Some(1),
+            Field::NoKey(_) => None,
+        };
+
+        let text = match equal {
+            // Comments next to the `=` have to stay where they are, so the spacing is only changed without them
+            Some(equal)
+                if !tokens[significant[equal - 1] + 1..significant[equal + 1]]
+                    .iter()
+                    .any(is_comment) =>
+            {
+                format!(
+                    "{} = {}",
+                    slice(first, significant[equal - 1]),
+                    slice(significant[equal + 1], last)
+                )
+            }
+
+            _ => slice(first, last).to_owned(),
+        };
+
+        let mut trailing_comments = comments(&tokens[last + 1..]);
+        if let Some(separator) = separator {
+            trailing_comments.extend(comments(
+                separator
+                    .leading_trivia()
+                    .chain(separator.trailing_trivia()),
+            ));
+        }
+
+        Self {
+            leading_comments: comments(&tokens[..first]),
+            text,
+            trailing_comments,
+        }
+    }
+}
+
+impl<'a> TableConstructor<'a> {
+    /// The table with one field on each line, each followed by a comma, such as:
+    ///
+    /// ```lua
+    /// {
+    ///     a = 1,
+    ///     b = 2,
+    /// }
+    /// ```
+    ///
+    /// `indentation` is the indentation of the line the table starts on, which the closing brace is put at,
+    /// and `indent` is added to it for the fields. The spacing around the `=` of keyed fields becomes
+    /// a single space, but the code of each field is otherwise kept as it is.
+    /// Comments are kept, those before a field are put on the lines before it and those after it at the end of its line.
+    ///
+    /// The returned table is parsed from new code, so it's owned and its positions don't point into the original code.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::{Expression, LastStmt, Value};
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("return {1,2;3}")?;
+    /// # let table = match ast.nodes().last_stmts() {
+    /// #     Some(LastStmt::Return(r#return)) => match r#return.returns().iter().next() {
+    /// #         Some(Expression::Value { value, .. }) => match &**value {
+    /// #             Value::TableConstructor(table) => table,
+    /// #             _ => unreachable!(),
+    /// #         },
+    /// #         _ => unreachable!(),
+    /// #     },
+    /// #     _ => unreachable!(),
+    /// # };
+    /// // `table` is the `{1,2;3}`
+    /// assert_eq!(table.to_multi_line("", "\t").to_string(), "{\n\t1,\n\t2,\n\t3,\n}");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_multi_line(&self, indentation: &str, indent: &str) -> TableConstructor<'static> {
+        let (start_brace, end_brace) = self.braces.tokens();
+        let field_indentation = format!("{}{}", indentation, indent);
+
+        // Comments after the `{` are put before the first field
+        let mut leading_comments = comments(start_brace.trailing_trivia());
+        let end_comments = comments(end_brace.leading_trivia());
+
+        if self.fields.is_empty() && leading_comments.is_empty() && end_comments.is_empty() {
+            return self.restyle("{}");
+        }
+
+        let mut code = String::from("{\n");
+        for (field, separator) in &self.fields {
+            let field = FieldText::new(field, separator.as_ref());
+            leading_comments.extend(field.leading_comments);
+
+            for comment in leading_comments.drain(..) {
+                code.push_str(&format!("{}{}\n", field_indentation, comment));
+            }
+
+            code.push_str(&format!("{}{},", field_indentation, field.text));
+            for comment in field.trailing_comments {
+                code.push(' ');
+                code.push_str(&comment);
+            }
+
+            code.push('\n');
+        }
+
+        for comment in leading_comments.into_iter().chain(end_comments) {
+            code.push_str(&format!("{}{}\n", field_indentation, comment));
+        }
+
+        code.push_str(indentation);
+        code.push('}');
+        self.restyle(&code)
+    }
+
+    /// The table on a single line, such as `{ a = 1, b = 2 }`, with no trailing separator.
+    /// The spacing around the `=` of keyed fields becomes a single space, as with
+    /// [`to_multi_line`](#method.to_multi_line).
+    /// Returns `None` if the table has comments between its fields, as they'd be lost, or if a field
+    /// is written over multiple lines.
+    pub fn to_single_line(&self) -> Option<TableConstructor<'static>> {
+        Some(self.restyle(&self.single_line_code()?))
+    }
+
+    /// The table on a single line if it fits in `max_width` characters, otherwise on multiple lines.
+    /// Refer to [`to_single_line`](#method.to_single_line) and [`to_multi_line`](#method.to_multi_line).
+    pub fn to_canonical(
+        &self,
+        indentation: &str,
+        indent: &str,
+        max_width: usize,
+    ) -> TableConstructor<'static> {
+        match self.single_line_code() {
+            Some(code) if code.chars().count() <= max_width => self.restyle(&code),
+            _ => self.to_multi_line(indentation, indent),
+        }
+    }
+
+    /// Rewrites the separator after every field that has one into the given one, keeping the trivia around it,
+    /// such as turning `{1; 2; 3}` into `{1, 2, 3}`. Fields without a separator are left without one.
+    ///
+    /// ```rust
+    /// # use full_moon::ast::FieldSeparator;
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("local t = {1, 2; 3;}")?;
+    /// let mut table = ast.table_constructors().remove(0);
+    /// table.normalize_field_separators(FieldSeparator::Semicolon);
+    /// assert_eq!(table.to_string(), "{1; 2; 3;}");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn normalize_field_separators(&mut self, separator: FieldSeparator) {
+        let symbol = match separator {
+            FieldSeparator::Comma => Symbol::Comma,
+            FieldSeparator::Semicolon => Symbol::Semicolon,
+        };
+
+        for (_, field_separator) in &mut self.fields {
+            if let Some(token) = field_separator {
+                *token = TokenReference::new(
+                    token.leading_trivia().cloned().collect(),
+                    Token::new(TokenType::Symbol { symbol }),
+                    token.trailing_trivia().cloned().collect(),
+                );
+            }
+        }
+    }
+
+    fn single_line_code(&self) -> Option<String> {
+        let (start_brace, end_brace) = self.braces.tokens();
+        if start_brace
+            .trailing_trivia()
+            .chain(end_brace.leading_trivia())
+            .any(is_comment)
+        {
+            return None;
+        }
+
+        let mut texts = Vec::new();
+        for (field, separator) in &self.fields {
+            let field = FieldText::new(field, separator.as_ref());
+            if !field.leading_comments.is_empty()
+                || !field.trailing_comments.is_empty()
+                || field.text.contains('\n')
+            {
+                return None;
+            }
+
+            texts.push(field.text);
+        }
+
+        if texts.is_empty() {
+            Some("{}".to_owned())
+        } else {
+            Some(format!("{{ {} }}", texts.join(", ")))
+        }
+    }
+
+    // Parses the new code of the table, keeping the trivia outside of its braces
+    fn restyle(&self, code: &str) -> TableConstructor<'static> {
+        let (start_brace, end_brace) = self.braces.tokens();
+        let mut full_code = String::from("return\n");
+        for trivia in start_brace.leading_trivia() {
+            full_code.push_str(&trivia.to_string());
+        }
+
+        full_code.push_str(code);
+        for trivia in end_brace.trailing_trivia() {
+            full_code.push_str(&trivia.to_string());
+        }
+
+        let ast = crate::parse(&full_code).expect("restyled table should be valid code");
+        if let Some(LastStmt::Return(r#return)) = ast.nodes().last_stmts() {
+            if let Some(Expression::Value { value, .. }) = r#return.returns().iter().next() {
+                if let Value::TableConstructor(table) = &**value {
+                    return table.owned();
+                }
+            }
+        }
+
+        unreachable!("restyled code should be a table")
+    }
+}
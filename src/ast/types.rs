@@ -1,23 +1,28 @@
 //! Contains the types necessary to parse [Roblox's typed Lua](https://devforum.roblox.com/t/luau-type-checking-beta/435382).
 //! Only usable when the "roblox" feature flag is enabled.
 use super::{punctuated::Punctuated, span::ContainedSpan, *};
+use crate::util::{display_option, display_tuple};
+use derive_more::Display;
 
 /// Any type, such as `string`, `boolean?`, `number | boolean`, etc.
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum TypeInfo<'a> {
 	/// A standalone type, such as `string` or `Foo`.
+	#[display(fmt = "{:#}", "_0")]
 	Basic(#[cfg_attr(feature = "serde", serde(borrow))] TokenReference<'a>),
 
-	/// A callback type, such as `(string, number) => boolean`.
+	/// A callback type, such as `(string, number) -> boolean` or `(string, number) => boolean`.
+	#[display(fmt = "{:#}{}{:#}{:#}{}", "parentheses.tokens().0", "arguments", "parentheses.tokens().1", "arrow", "return_type")]
 	Callback {
-		/// The argument types: `(string, number)`.
+		/// The argument types: `(string, number)`, which can be named, as in `(count: number)`.
 		#[cfg_attr(feature = "serde", serde(borrow))]
-		arguments: Punctuated<'a, TypeInfo<'a>>,
+		arguments: Punctuated<'a, TypeArgument<'a>>,
 		/// The parentheses for the arguments.
 		#[cfg_attr(feature = "serde", serde(borrow))]
+		#[visit(contains = "arguments")]
 		parentheses: ContainedSpan<'a>,
-		/// The "fat arrow" (`=>`) in between the arguments and the return type.
+		/// The arrow (`->` or `=>`) in between the arguments and the return type.
 		#[cfg_attr(feature = "serde", serde(borrow))]
 		arrow: TokenReference<'a>,
 		/// The return type: `boolean`.
@@ -26,12 +31,14 @@ pub enum TypeInfo<'a> {
 	},
 
 	/// A type using generics, such as `map<number, string>`.
+	#[display(fmt = "{:#}{:#}{}{:#}", "base", "arrows.tokens().0", "generics", "arrows.tokens().1")]
 	Generic {
 		/// The type that has generics: `map`.
 		#[cfg_attr(feature = "serde", serde(borrow))]
 		base: TokenReference<'a>,
 		/// The arrows (`<>`) containing the type parameters.
 		#[cfg_attr(feature = "serde", serde(borrow))]
+		#[visit(contains = "generics")]
 		arrows: ContainedSpan<'a>,
 		/// The type parameters: `number, string`.
 		#[cfg_attr(feature = "serde", serde(borrow))]
@@ -39,6 +46,7 @@ pub enum TypeInfo<'a> {
 	},
 
 	/// An optional type, such as `string?`.
+	#[display(fmt = "{}{:#}", "base", "question_mark")]
 	Optional {
 		/// The type that is optional: `string`.
 		#[cfg_attr(feature = "serde", serde(borrow))]
@@ -49,9 +57,11 @@ pub enum TypeInfo<'a> {
 	},
 
 	/// A type annotating the structure of a table: { foo: number, bar: string }
+	#[display(fmt = "{:#}{}{:#}", "braces.tokens().0", "fields", "braces.tokens().1")]
 	Table {
 		/// The braces (`{}`) containing the fields.
 		#[cfg_attr(feature = "serde", serde(borrow))]
+		#[visit(contains = "fields")]
 		braces: ContainedSpan<'a>,
 		/// The fields: `foo: number, bar: string`.
 		#[cfg_attr(feature = "serde", serde(borrow))]
@@ -59,12 +69,14 @@ pub enum TypeInfo<'a> {
 	},
 
 	/// A type in the form of `typeof(foo)`.
+	#[display(fmt = "{:#}{:#}{}{:#}", "typeof_token", "parentheses.tokens().0", "inner", "parentheses.tokens().1")]
 	Typeof {
 		/// The token `typeof`.
 		#[cfg_attr(feature = "serde", serde(borrow))]
 		typeof_token: TokenReference<'a>,
 		/// The parentheses used to contain the expression.
 		#[cfg_attr(feature = "serde", serde(borrow))]
+		#[visit(contains = "inner")]
 		parentheses: ContainedSpan<'a>,
 		/// The inner expression: `foo`.
 		#[cfg_attr(feature = "serde", serde(borrow))]
@@ -72,9 +84,11 @@ pub enum TypeInfo<'a> {
 	},
 
 	/// A tuple expression: `(string, number)`.
+	#[display(fmt = "{:#}{}{:#}", "parentheses.tokens().0", "types", "parentheses.tokens().1")]
 	Tuple {
 		/// The parentheses used to contain the types
 		#[cfg_attr(feature = "serde", serde(borrow))]
+		#[visit(contains = "types")]
 		parentheses: ContainedSpan<'a>,
 		/// The types: `(string, number)`.
 		#[cfg_attr(feature = "serde", serde(borrow))]
@@ -82,6 +96,7 @@ pub enum TypeInfo<'a> {
 	},
 
 	/// A union type: `string | number`, denoting one or the other.
+	#[display(fmt = "{}{:#}{}", "left", "pipe", "right")]
 	Union {
 		/// The left hand side: `string`.
 		#[cfg_attr(feature = "serde", serde(borrow))]
@@ -95,9 +110,55 @@ pub enum TypeInfo<'a> {
 	},
 }
 
+impl<'a> TypeInfo<'a> {
+	/// Parses a type on its own from tokens, such as `{ [string]: number }`.
+	/// You probably want [`parse_type`](../../fn.parse_type.html)
+	///
+	/// # Errors
+	/// If the tokens passed are not a single valid type, an [`AstError`](../enum.AstError.html) will be returned.
+	pub fn from_tokens(tokens: Vec<Token<'a>>) -> Result<TypeInfo<'a>, AstError<'a>> {
+		let (_, type_info) = parse_to_eof(parsers::ParseTypeInfo, tokens)?;
+		Ok(type_info)
+	}
+}
+
+/// An argument of a [callback type](enum.TypeInfo.html#variant.Callback), which can be given a name.
+/// The `count: number` in `(count: number) -> ()`.
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[display(
+	fmt = "{}{}",
+	"display_option(name.as_ref().map(display_tuple))",
+	"type_info"
+)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct TypeArgument<'a> {
+	#[cfg_attr(feature = "serde", serde(borrow))]
+	pub(crate) name: Option<(TokenReference<'a>, TokenReference<'a>)>,
+	#[cfg_attr(feature = "serde", serde(borrow))]
+	pub(crate) type_info: TypeInfo<'a>,
+}
+
+impl<'a> TypeArgument<'a> {
+	/// The name of the argument if it has one, `count` in `count: number`.
+	pub fn name(&self) -> Option<&TokenReference<'a>> {
+		Some(&self.name.as_ref()?.0)
+	}
+
+	/// The colon in between the name and the type if the argument has a name.
+	pub fn colon_token(&self) -> Option<&TokenReference<'a>> {
+		Some(&self.name.as_ref()?.1)
+	}
+
+	/// The type of the argument, `number` in `count: number`.
+	pub fn type_info(&self) -> &TypeInfo<'a> {
+		&self.type_info
+	}
+}
+
 /// A type field used within table types.
 /// The `foo: number` in `{ foo: number }`.
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[display(fmt = "{}{:#}{}", "key", "colon", "value")]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct TypeField<'a> {
 	#[cfg_attr(feature = "serde", serde(borrow))]
@@ -119,6 +180,11 @@ impl<'a> TypeField<'a> {
 		&self.colon
 	}
 
+	/// The colon in between the key name and the value type, but mutable
+	pub fn colon_token_mut(&mut self) -> &mut TokenReference<'a> {
+		&mut self.colon
+	}
+
 	/// The type for the field, `number` in `foo: number`.
 	pub fn value(&self) -> &TypeInfo<'a> {
 		&self.value
@@ -126,16 +192,19 @@ impl<'a> TypeField<'a> {
 }
 
 /// A key in a [`TypeField`](struct.TypeField.html). Can either be a name or an index signature.
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum TypeFieldKey<'a> {
 	/// A name, such as `foo`.
+	#[display(fmt = "{:#}", "_0")]
 	Name(TokenReference<'a>),
 
 	/// An index signature, such as `[number]`.
+	#[display(fmt = "{:#}{}{:#}", "brackets.tokens().0", "inner", "brackets.tokens().1")]
 	IndexSignature {
 		/// The brackets (`[]`) used to contain the type.
 		#[cfg_attr(feature = "serde", serde(borrow))]
+		#[visit(contains = "inner")]
 		brackets: ContainedSpan<'a>,
 
 		/// The type for the index signature, `number` in `[number]`.
@@ -145,7 +214,8 @@ pub enum TypeFieldKey<'a> {
 }
 
 /// A type assertion using `as`, such as `as number`.
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[display(fmt = "{:#}{}", "as_token", "cast_to")]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct AsAssertion<'a> {
 	#[cfg_attr(feature = "serde", serde(borrow))]
@@ -160,6 +230,11 @@ impl<'a> AsAssertion<'a> {
 		&self.as_token
 	}
 
+	/// The token `as`, but mutable
+	pub fn as_token_mut(&mut self) -> &mut TokenReference<'a> {
+		&mut self.as_token
+	}
+
 	/// The type to cast the expression into, `number` in `as number`.
 	pub fn cast_to(&self) -> &TypeInfo<'a> {
 		&self.cast_to
@@ -167,7 +242,8 @@ impl<'a> AsAssertion<'a> {
 }
 
 /// A type declaration, such as `type Meters = number`
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[display(fmt = "{:#}{:#}{}{:#}{}", "type_token", "base", "display_option(generics)", "equal_token", "declare_as")]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct TypeDeclaration<'a> {
 	#[cfg_attr(feature = "serde", serde(borrow))]
@@ -188,6 +264,11 @@ impl<'a> TypeDeclaration<'a> {
 		&self.type_token
 	}
 
+	/// The token `type`, but mutable
+	pub fn type_token_mut(&mut self) -> &mut TokenReference<'a> {
+		&mut self.type_token
+	}
+
 	/// The name of the type, `Meters` in `type Meters = number`.
 	pub fn type_name(&self) -> &TokenReference<'a> {
 		&self.base
@@ -203,17 +284,143 @@ impl<'a> TypeDeclaration<'a> {
 		&self.equal_token
 	}
 
+	/// The `=` token in between the type name and the definition, but mutable
+	pub fn equal_token_mut(&mut self) -> &mut TokenReference<'a> {
+		&mut self.equal_token
+	}
+
 	/// The definition of the type, `number` in `type Meters = number`.
 	pub fn type_definition(&self) -> &TypeInfo<'a> {
 		&self.declare_as
 	}
 }
 
+/// A compound assignment, such as `x += 1`
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[display(fmt = "{}{}{}", "lhs", "compound_operator", "rhs")]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct CompoundAssignment<'a> {
+	#[cfg_attr(feature = "serde", serde(borrow))]
+	pub(crate) lhs: Var<'a>,
+	#[cfg_attr(feature = "serde", serde(borrow))]
+	pub(crate) compound_operator: CompoundOp<'a>,
+	#[cfg_attr(feature = "serde", serde(borrow))]
+	pub(crate) rhs: Expression<'a>,
+}
+
+impl<'a> CompoundAssignment<'a> {
+	/// The variable being assigned to, the `x` part of `x += 1`
+	pub fn lhs(&self) -> &Var<'a> {
+		&self.lhs
+	}
+
+	/// The operator used, the `+=` part of `x += 1`
+	pub fn compound_operator(&self) -> &CompoundOp<'a> {
+		&self.compound_operator
+	}
+
+	/// The value being assigned, the `1` part of `x += 1`
+	pub fn rhs(&self) -> &Expression<'a> {
+		&self.rhs
+	}
+}
+
+impl<'a> Assignment<'a> {
+	/// Rewrites an assignment such as `x = x + 1` into a [compound assignment](struct.CompoundAssignment.html), `x += 1`.
+	/// Returns `None` if the assignment can't be rewritten without changing what it does, such as when
+	/// there's more than one variable, the variable isn't the first operand, the operator isn't the last one
+	/// to be done (`x = x * 2 + 1`), or getting the variable could have side effects (`t[f()] = t[f()] + 1`).
+	///
+	/// ```rust
+	/// # use full_moon::ast::Stmt;
+	/// # fn main() -> Result<(), Box<std::error::Error>> {
+	/// let ast = full_moon::parse("a = a + 1")?;
+	/// let assignment = match ast.nodes().iter_stmts().next() {
+	///     Some(Stmt::Assignment(assignment)) => assignment,
+	///     _ => unreachable!(),
+	/// };
+	///
+	/// assert_eq!(assignment.to_compound_assignment().unwrap().to_string(), "a += 1");
+	/// # Ok(())
+	/// # }
+	/// ```
+	pub fn to_compound_assignment(&self) -> Option<CompoundAssignment<'a>> {
+		use super::constant::{Operand, Operation};
+		use crate::node::Node;
+
+		if self.var_list().len() != 1 || self.expr_list().len() != 1 {
+			return None;
+		}
+
+		let lhs = self.var_list().iter().next()?;
+		if !is_side_effect_free(lhs) {
+			return None;
+		}
+
+		let expression = self.expr_list().iter().next()?;
+		let (operand, bin_op) = match Operation::new(expression) {
+			Operation::Binary(operand, bin_op, _) => (operand, bin_op),
+			_ => return None,
+		};
+
+		match *operand {
+			Operation::Operand(Operand::Value(Value::Var(var))) if var.similar(lhs) => {}
+			_ => return None,
+		}
+
+		let rhs = match expression {
+			Expression::Value { binop: Some(binop), .. } => binop.rhs(),
+			_ => unreachable!("binary operation isn't a value with a binop"),
+		};
+
+		let (token, symbol, operator): (_, _, fn(TokenReference<'a>) -> CompoundOp<'a>) = match bin_op {
+			BinOp::Plus(token) => (token, Symbol::PlusEqual, CompoundOp::PlusEqual),
+			BinOp::Minus(token) => (token, Symbol::MinusEqual, CompoundOp::MinusEqual),
+			BinOp::Star(token) => (token, Symbol::StarEqual, CompoundOp::StarEqual),
+			BinOp::Slash(token) => (token, Symbol::SlashEqual, CompoundOp::SlashEqual),
+			BinOp::Percent(token) => (token, Symbol::PercentEqual, CompoundOp::PercentEqual),
+			BinOp::Caret(token) => (token, Symbol::CaretEqual, CompoundOp::CaretEqual),
+			BinOp::TwoDots(token) => (token, Symbol::TwoDotsEqual, CompoundOp::TwoDotsEqual),
+			_ => return None,
+		};
+
+		Some(CompoundAssignment {
+			lhs: lhs.clone(),
+			compound_operator: operator(TokenReference::new(
+				self.equal_token().leading_trivia().cloned().collect(),
+				Token::new(TokenType::Symbol { symbol }),
+				token.trailing_trivia().cloned().collect(),
+			)),
+			rhs: rhs.clone(),
+		})
+	}
+}
+
+// Whether getting the variable can't call any functions, such as `x.y` or `x[1]`, but not `x[f()]`
+fn is_side_effect_free(var: &Var) -> bool {
+	let var_expression = match var {
+		Var::Name(_) => return true,
+		Var::Expression(var_expression) => var_expression,
+	};
+
+	if let Prefix::Expression(_) = var_expression.prefix() {
+		return false;
+	}
+
+	var_expression.iter_suffixes().all(|suffix| match suffix {
+		Suffix::Index(Index::Dot { .. }) => true,
+		Suffix::Index(Index::Brackets { expression, .. }) => expression.eval_constant().is_some(),
+		Suffix::Call(_) => false,
+	})
+}
+
 /// The generics used in a [type declaration](struct.TypeDeclaration.html).
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[display(fmt = "{:#}{}{:#}", "arrows.tokens().0", "generics", "arrows.tokens().1")]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct GenericDeclaration<'a> {
 	#[cfg_attr(feature = "serde", serde(borrow))]
+	#[visit(contains = "generics")]
 	pub(crate) arrows: ContainedSpan<'a>,
 	#[cfg_attr(feature = "serde", serde(borrow))]
 	pub(crate) generics: Punctuated<'a, TokenReference<'a>>,
@@ -232,7 +439,8 @@ impl<'a> GenericDeclaration<'a> {
 }
 
 /// A type specifier, the `: number` in `local foo: number`
-#[derive(Clone, Debug, PartialEq, Owned, Node, Visit)]
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[display(fmt = "{:#}{}", "punctuation", "type_info")]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct TypeSpecifier<'a> {
 	#[cfg_attr(feature = "serde", serde(borrow))]
@@ -253,3 +461,26 @@ impl<'a> TypeSpecifier<'a> {
 		&self.type_info
 	}
 }
+
+/// An attribute on a function, such as `@native` in `@native function foo() end`.
+#[derive(Clone, Debug, Display, PartialEq, Owned, Node, Visit)]
+#[display(fmt = "{:#}{:#}", "at_token", "name")]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct Attribute<'a> {
+	#[cfg_attr(feature = "serde", serde(borrow))]
+	pub(crate) at_token: TokenReference<'a>,
+	#[cfg_attr(feature = "serde", serde(borrow))]
+	pub(crate) name: TokenReference<'a>,
+}
+
+impl<'a> Attribute<'a> {
+	/// The token `@`.
+	pub fn at_token(&self) -> &TokenReference<'a> {
+		&self.at_token
+	}
+
+	/// The name of the attribute: `native` in `@native`.
+	pub fn name(&self) -> &TokenReference<'a> {
+		&self.name
+	}
+}
@@ -0,0 +1,249 @@
+// Creates unified diffs between two pieces of code, refer to `unified_diff`
+use std::cmp;
+
+// How many unchanged lines are shown around the changed ones
+const CONTEXT: usize = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Change {
+    Equal,
+    Delete,
+    Insert,
+}
+
+// The lines of the text, each with its new line if it has one
+fn lines(text: &str) -> Vec<&str> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+
+    for (index, _) in text.match_indices('\n') {
+        lines.push(&text[start..=index]);
+        start = index + 1;
+    }
+
+    if start < text.len() {
+        lines.push(&text[start..]);
+    }
+
+    lines
+}
+
+// The changes that turn the old lines into the new lines, as few as possible.
+// Myers' algorithm is used in its linear space form, so memory only grows with the number of lines rather than
+// with the product of the number of old and new lines, and the time taken grows with how many lines changed.
+fn changes(old: &[&str], new: &[&str]) -> Vec<Change> {
+    let max_d = max_d(old.len(), new.len());
+    let mut forward = vec![0; 2 * max_d + 2];
+    let mut backward = vec![0; 2 * max_d + 2];
+    let mut changes = Vec::with_capacity(cmp::max(old.len(), new.len()));
+
+    conquer(old, new, &mut forward, &mut backward, &mut changes);
+
+    // Within each run of changed lines, the removed lines are shown before the added ones
+    for run in changes.split_mut(|change| *change == Change::Equal) {
+        run.sort_unstable_by_key(|change| *change == Change::Insert);
+    }
+
+    changes
+}
+
+// More than the most edits the middle snake of `old` and `new` can be found after, going from both ends,
+// which is half of the edits it takes to remove every old line and add every new one
+fn max_d(old_len: usize, new_len: usize) -> usize {
+    (old_len + new_len) / 2 + 2
+}
+
+fn common_prefix(old: &[&str], new: &[&str]) -> usize {
+    old.iter()
+        .zip(new)
+        .take_while(|(old, new)| old == new)
+        .count()
+}
+
+fn common_suffix(old: &[&str], new: &[&str]) -> usize {
+    old.iter()
+        .rev()
+        .zip(new.iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count()
+}
+
+// Finds where the middle snake of the shortest edit script starts, searching from the start and the end at once.
+// Both sides have to have lines left, and differ in their first and last lines.
+fn middle_snake(
+    old: &[&str],
+    new: &[&str],
+    forward: &mut [usize],
+    backward: &mut [usize],
+) -> Option<(usize, usize)> {
+    let (n, m) = (old.len(), new.len());
+    let delta = n as isize - m as isize;
+    let odd = delta & 1 == 1;
+
+    // The furthest x reached on each diagonal k, stored around the middle so that k can be negative
+    let offset = forward.len() as isize / 2;
+    let at = |k: isize| (offset + k) as usize;
+    forward[at(1)] = 0;
+    backward[at(1)] = 0;
+
+    for d in 0..max_d(n, m) as isize {
+        for k in (-d..=d).rev().step_by(2) {
+            let mut x = if k == -d || (k != d && forward[at(k - 1)] < forward[at(k + 1)]) {
+                forward[at(k + 1)]
+            } else {
+                forward[at(k - 1)] + 1
+            };
+            let y = (x as isize - k) as usize;
+            let (start_x, start_y) = (x, y);
+
+            if x < n && y < m {
+                x += common_prefix(&old[x..], &new[y..]);
+            }
+
+            forward[at(k)] = x;
+
+            if odd && (k - delta).abs() < d && x + backward[at(-(k - delta))] >= n {
+                return Some((start_x, start_y));
+            }
+        }
+
+        for k in (-d..=d).rev().step_by(2) {
+            let mut x = if k == -d || (k != d && backward[at(k - 1)] < backward[at(k + 1)]) {
+                backward[at(k + 1)]
+            } else {
+                backward[at(k - 1)] + 1
+            };
+            let mut y = (x as isize - k) as usize;
+
+            if x < n && y < m {
+                let advance = common_suffix(&old[..n - x], &new[..m - y]);
+                x += advance;
+                y += advance;
+            }
+
+            backward[at(k)] = x;
+
+            if !odd && (k - delta).abs() <= d && x + forward[at(-(k - delta))] >= n {
+                return Some((n - x, m - y));
+            }
+        }
+    }
+
+    None
+}
+
+// Splits the lines where the middle snake starts, and finds the changes on either side of it
+fn conquer(
+    old: &[&str],
+    new: &[&str],
+    forward: &mut [usize],
+    backward: &mut [usize],
+    changes: &mut Vec<Change>,
+) {
+    // Most changes only touch a few lines, so the lines that are the same at the start and end are skipped first
+    let prefix = common_prefix(old, new);
+    let suffix = common_suffix(&old[prefix..], &new[prefix..]);
+    let old = &old[prefix..old.len() - suffix];
+    let new = &new[prefix..new.len() - suffix];
+
+    changes.resize(changes.len() + prefix, Change::Equal);
+
+    if old.is_empty() {
+        changes.resize(changes.len() + new.len(), Change::Insert);
+    } else if new.is_empty() {
+        changes.resize(changes.len() + old.len(), Change::Delete);
+    } else if let Some((x, y)) = middle_snake(old, new, forward, backward) {
+        conquer(&old[..x], &new[..y], forward, backward, changes);
+        conquer(&old[x..], &new[y..], forward, backward, changes);
+    } else {
+        changes.resize(changes.len() + old.len(), Change::Delete);
+        changes.resize(changes.len() + new.len(), Change::Insert);
+    }
+
+    changes.resize(changes.len() + suffix, Change::Equal);
+}
+
+// The range of a hunk in its header, such as `3,4` for the 4 lines starting at line 3
+fn hunk_range(start: usize, length: usize) -> String {
+    match length {
+        0 => format!("{},0", start),
+        1 => (start + 1).to_string(),
+        _ => format!("{},{}", start + 1, length),
+    }
+}
+
+pub(crate) fn unified_diff(old_text: &str, new_text: &str) -> String {
+    let old = lines(old_text);
+    let new = lines(new_text);
+    let changes = changes(&old, &new);
+
+    // The indexes of the old and new lines each change is at
+    let mut positions = Vec::with_capacity(changes.len() + 1);
+    let (mut old_index, mut new_index) = (0, 0);
+    for change in &changes {
+        positions.push((old_index, new_index));
+
+        match change {
+            Change::Equal => {
+                old_index += 1;
+                new_index += 1;
+            }
+
+            Change::Delete => old_index += 1,
+            Change::Insert => new_index += 1,
+        }
+    }
+
+    positions.push((old_index, new_index));
+
+    let changed: Vec<usize> = (0..changes.len())
+        .filter(|&index| changes[index] != Change::Equal)
+        .collect();
+
+    if changed.is_empty() {
+        return String::new();
+    }
+
+    let mut diff = String::from("--- original\n+++ transformed\n");
+    let mut index = 0;
+
+    while index < changed.len() {
+        let start = changed[index].saturating_sub(CONTEXT);
+        let mut end = changed[index] + 1;
+        index += 1;
+
+        // Changes close enough together for their context to touch are put in the same hunk
+        while index < changed.len() && changed[index] - end <= CONTEXT * 2 {
+            end = changed[index] + 1;
+            index += 1;
+        }
+
+        let end = cmp::min(end + CONTEXT, changes.len());
+        let (old_start, new_start) = positions[start];
+        let (old_end, new_end) = positions[end];
+
+        diff.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(old_start, old_end - old_start),
+            hunk_range(new_start, new_end - new_start)
+        ));
+
+        for position in start..end {
+            let (old_index, new_index) = positions[position];
+            let (marker, line) = match changes[position] {
+                Change::Equal => (' ', old[old_index]),
+                Change::Delete => ('-', old[old_index]),
+                Change::Insert => ('+', new[new_index]),
+            };
+
+            diff.push(marker);
+            diff.push_str(line);
+
+            if !line.ends_with('\n') {
+                diff.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+
+    diff
+}
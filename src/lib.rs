@@ -6,6 +6,8 @@
 //! Learn more by going to [the repository](https://github.com/Kampfkarren/full-moon)
 
 /// Utilities for ASTs (Abstract Syntax Trees). Contains all nodes used by Full Moon (such as blocks).
+/// Every accessor on a node borrows from the tree, so reading an [`Ast`](ast/struct.Ast.html)
+/// never clones it. Use [`Owned`](ast/owned/trait.Owned.html) only when a node needs to outlive the source code.
 pub mod ast;
 
 /// Contains the `Node` trait, implemented on all nodes
@@ -18,10 +20,15 @@ pub mod tokenizer;
 /// Used to create visitors that recurse through [`Ast`](ast/struct.Ast.html) nodes.
 pub mod visitors;
 
+mod diff;
 mod private;
+mod util;
 
 use full_moon_derive::Owned;
-use std::fmt;
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
 
 #[cfg(all(test, not(feature = "serde")))]
 compile_error!("Serde feature must be enabled for tests");
@@ -67,8 +74,221 @@ pub fn parse(code: &str) -> Result<ast::Ast, Error> {
     ast::Ast::from_tokens(tokens).map_err(Error::AstError)
 }
 
-/// Prints back Lua code from an [Ast](ast/struct.Ast.html)
+/// Creates an [`Ast`](ast/struct.Ast.html) from Lua code that starts at the given position in a larger document,
+/// such as Lua inside Markdown or a template. The positions of every token, and of any error,
+/// are where they are in the document. Refer to [`tokens_with_offset`](tokenizer/fn.tokens_with_offset.html) for more details.
+///
+/// # Errors
+/// If the code passed cannot be tokenized, a TokenizerError will be returned.
+/// If the code passed is not valid Lua 5.1 code, an AstError will be returned,
+/// specifically AstError::UnexpectedToken.
+///
+/// ```rust
+/// # use full_moon::{ast::Stmt, node::Node, tokenizer::Position};
+/// # fn main() -> Result<(), Box<std::error::Error>> {
+/// let ast = full_moon::parse_with_offset("call()", Position::new(120, 10, 4))?;
+/// let call = ast.nodes().iter_stmts().next().unwrap();
+/// assert_eq!(call.start_position(), Some(Position::new(120, 10, 4)));
+/// # Ok(())
+/// # }
+/// ```
+pub fn parse_with_offset(
+    code: &str,
+    offset: tokenizer::Position,
+) -> Result<ast::Ast<'_>, Error<'_>> {
+    let tokens = tokenizer::tokens_with_offset(code, offset).map_err(Error::TokenizerError)?;
+    ast::Ast::from_tokens(tokens).map_err(Error::AstError)
+}
+
+/// How much code was parsed by [`parse_with_stats`](fn.parse_with_stats.html), and how long each step took
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ParseStats {
+    token_count: usize,
+    node_count: usize,
+    tokenize_time: Duration,
+    parse_time: Duration,
+}
+
+impl ParseStats {
+    /// How many tokens the code was split into, including whitespace, comments and the end of file token.
+    /// This is 0 if the code couldn't be tokenized.
+    pub fn token_count(&self) -> usize {
+        self.token_count
+    }
+
+    /// How many nodes are in the [`Ast`](ast/struct.Ast.html), counting the block of code at the top,
+    /// every node inside of it as found by [`Node::children`](node/trait.Node.html#tymethod.children), and their tokens.
+    /// This is 0 if the code couldn't be parsed.
+    pub fn node_count(&self) -> usize {
+        self.node_count
+    }
+
+    /// How long it took to split the code into tokens
+    pub fn tokenize_time(&self) -> Duration {
+        self.tokenize_time
+    }
+
+    /// How long it took to create the [`Ast`](ast/struct.Ast.html) from the tokens
+    pub fn parse_time(&self) -> Duration {
+        self.parse_time
+    }
+}
+
+fn count_nodes(node: &dyn node::Node) -> usize {
+    1 + node.children().into_iter().map(count_nodes).sum::<usize>()
+}
+
+/// Creates an [`Ast`](ast/struct.Ast.html) from Lua code the same way as [`parse`](fn.parse.html),
+/// along with [stats](struct.ParseStats.html) about how much code there was and how long it took,
+/// such as to profile the parser over a large codebase.
+///
+/// ```rust
+/// let (ast, stats) = full_moon::parse_with_stats("local x = 1");
+/// assert!(ast.is_ok());
+/// // `local`, `x`, `=`, `1`, the whitespace between them, and the end of the file
+/// assert_eq!(stats.token_count(), 8);
+/// ```
+pub fn parse_with_stats(code: &str) -> (Result<ast::Ast<'_>, Error<'_>>, ParseStats) {
+    let mut stats = ParseStats::default();
+
+    let start = Instant::now();
+    let tokens = tokenizer::tokens(code);
+    stats.tokenize_time = start.elapsed();
+
+    let tokens = match tokens {
+        Ok(tokens) => tokens,
+        Err(error) => return (Err(Error::TokenizerError(error)), stats),
+    };
+    stats.token_count = tokens.len();
+
+    let start = Instant::now();
+    let ast = ast::Ast::from_tokens(tokens);
+    stats.parse_time = start.elapsed();
+
+    match ast {
+        Ok(ast) => {
+            stats.node_count = count_nodes(ast.nodes());
+            (Ok(ast), stats)
+        }
+
+        Err(error) => (Err(Error::AstError(error)), stats),
+    }
+}
+
+/// Creates a [`Value`](ast/enum.Value.html) from a Lua value on its own, such as a config file made of a single table.
+/// Only values are allowed, so `-1` and `1 + 2`, which are expressions, aren't, but `(1 + 2)` is.
+///
+/// # Errors
+/// If the code passed cannot be tokenized, a TokenizerError will be returned.
+/// If the code passed is not a single valid value, an AstError will be returned.
+///
+/// ```rust
+/// assert!(full_moon::parse_value("{ a = 1, b = { 2, 3 } }").is_ok());
+/// assert!(full_moon::parse_value("{ a = 1 } b = 2").is_err());
+/// ```
+pub fn parse_value(code: &str) -> Result<ast::Value<'_>, Error<'_>> {
+    let tokens = tokenizer::tokens(code).map_err(Error::TokenizerError)?;
+    ast::Value::from_tokens(tokens).map_err(Error::AstError)
+}
+
+/// Creates a [`TypeInfo`](ast/types/enum.TypeInfo.html) from a Luau type on its own, such as `{ [string]: number }`.
+/// Only available when the "roblox" feature flag is enabled.
+///
+/// # Errors
+/// If the code passed cannot be tokenized, a TokenizerError will be returned.
+/// If the code passed is not a single valid type, an AstError will be returned.
+///
+/// ```rust
+/// assert!(full_moon::parse_type("(number) -> string").is_ok());
+/// assert!(full_moon::parse_type("number string").is_err());
+/// ```
+#[cfg(feature = "roblox")]
+pub fn parse_type(code: &str) -> Result<ast::types::TypeInfo, Error> {
+    let tokens = tokenizer::tokens(code).map_err(Error::TokenizerError)?;
+    ast::types::TypeInfo::from_tokens(tokens).map_err(Error::AstError)
+}
+
+/// Prints back Lua code from an [Ast](ast/struct.Ast.html).
+/// Nodes are printed with the whitespace and comments around their tokens,
+/// so any changes made to the nodes will be reflected.
 pub fn print(ast: &ast::Ast) -> String {
-    ast.iter_tokens()
-        .fold(String::new(), |acc, token| acc + &token.to_string())
+    use std::fmt::Write;
+
+    let mut output = String::new();
+    write!(output, "{}{:#}", ast.nodes(), ast.eof()).expect("writing to a String can't fail");
+    output
+}
+
+/// A unified diff, in the format `git diff` uses, from the code an [Ast](ast/struct.Ast.html) was parsed from
+/// to the code it [prints](fn.print.html) now, such as after it's been changed.
+/// Changed lines are shown with up to 3 unchanged lines around them, and the diff is empty if nothing changed.
+///
+/// ```rust
+/// # fn main() -> Result<(), Box<std::error::Error>> {
+/// let code = "t.insert(t, 1)\nprint(t)\n";
+/// let mut ast = full_moon::parse(code)?;
+/// ast.use_method_calls();
+/// assert_eq!(
+///     full_moon::unified_diff(code, &ast),
+///     "--- original\n+++ transformed\n@@ -1,2 +1,2 @@\n-t.insert(t, 1)\n+t:insert(1)\n print(t)\n",
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub fn unified_diff(original_source: &str, ast: &ast::Ast) -> String {
+    diff::unified_diff(original_source, &print(ast))
+}
+
+/// Why code didn't [print](fn.print.html) back the same as it was parsed, returned by [`verify_round_trip`](fn.verify_round_trip.html).
+/// Only available when the "testing" feature flag is enabled.
+#[cfg(feature = "testing")]
+#[derive(Clone, Debug, PartialEq)]
+pub enum RoundTripError<'a> {
+    /// The code couldn't be parsed
+    Parse(Error<'a>),
+    /// The code printed back differently, with a [unified diff](fn.unified_diff.html) from the code to what was printed
+    Mismatch(String),
+}
+
+#[cfg(feature = "testing")]
+impl<'a> fmt::Display for RoundTripError<'a> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RoundTripError::Parse(error) => write!(formatter, "{}", error),
+            RoundTripError::Mismatch(diff) => {
+                write!(formatter, "code printed back differently:\n{}", diff)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "testing")]
+impl<'a> std::error::Error for RoundTripError<'a> {}
+
+/// Parses the code and checks that it [prints](fn.print.html) back byte for byte the same,
+/// as it always should since nothing is lost while parsing.
+/// Meant for testing the parser over a corpus of code, as a mismatch means something is printed wrong.
+/// Only available when the "testing" feature flag is enabled.
+///
+/// # Errors
+/// If the code can't be parsed, a [`RoundTripError::Parse`](enum.RoundTripError.html#variant.Parse) will be returned.
+/// If the code prints back differently, a [`RoundTripError::Mismatch`](enum.RoundTripError.html#variant.Mismatch)
+/// with a diff of what changed will be returned.
+///
+/// ```rust
+/// assert!(full_moon::verify_round_trip("local x = 1 -- comment\n").is_ok());
+/// assert!(full_moon::verify_round_trip("local x = ").is_err());
+/// ```
+#[cfg(feature = "testing")]
+pub fn verify_round_trip(source: &str) -> Result<(), RoundTripError<'_>> {
+    let ast = parse(source).map_err(RoundTripError::Parse)?;
+    let printed = print(&ast);
+
+    if printed == source {
+        Ok(())
+    } else {
+        Err(RoundTripError::Mismatch(diff::unified_diff(
+            source, &printed,
+        )))
+    }
 }
@@ -1,8 +1,10 @@
 use crate::{
-    ast::Ast,
+    ast::{span::ContainedSpan, Ast},
     private,
     tokenizer::{Position, Token, TokenReference},
+    visitors::{Visit, Visitor},
 };
+use std::hash::{Hash, Hasher};
 
 /// Used to represent nodes such as tokens or function definitions
 ///
@@ -15,13 +17,57 @@ pub trait Node: private::Sealed {
     fn end_position(&self) -> Option<Position>;
 
     /// Whether another node of the same type is the same as this one semantically, ignoring position
-    fn similar(&self, other: &Self) -> bool;
+    fn similar(&self, other: &Self) -> bool
+    where
+        Self: Sized;
+
+    /// The nodes directly inside of this one, in the order of the fields they're in, such as the `if` token,
+    /// condition, `then` token, block and so on of an [`If`](../ast/struct.If.html).
+    /// Tokens are nodes without any children, so walking down every node's children reaches every token.
+    /// Optional nodes that aren't there aren't included, and the nodes in a sequence,
+    /// such as a `Vec` or [`Punctuated`](../ast/punctuated/struct.Punctuated.html), are included on their own.
+    ///
+    /// ```rust
+    /// # use full_moon::node::Node;
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("local x, y = 1, 2")?;
+    /// let stmt = ast.nodes().iter_stmts().next().unwrap();
+    /// // The `local` statement, which has `local`, `x`, `,`, `y`, `=`, `1`, `,` and `2` in it
+    /// let local_assignment = &stmt.children()[0];
+    /// assert_eq!(local_assignment.children().len(), 8);
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn children(&self) -> Vec<&dyn Node>;
+
+    // Pushes the node onto the children of the node it's inside of, or the nodes inside of it if it's a sequence
+    #[doc(hidden)]
+    fn collect_as_children<'s>(&'s self, children: &mut Vec<&'s dyn Node>)
+    where
+        Self: Sized,
+    {
+        children.push(self);
+    }
 
     /// The full range of a node, if it has both start and end positions
     fn range(&self) -> Option<(Position, Position)> {
         Some((self.start_position()?, self.end_position()?))
     }
 
+    /// The full range of a node as a [`Span`](struct.Span.html), if it has both start and end positions
+    fn span(&self) -> Option<Span> {
+        let (start, end) = self.range()?;
+        Some(Span::new(start, end))
+    }
+
+    /// Whether any of the node's code is inside the span, such as a selection in an editor.
+    /// Always false if the node doesn't have both a start and end position.
+    fn intersects(&self, span: Span) -> bool {
+        self.span()
+            .map(|own_span| own_span.overlaps(span))
+            .unwrap_or(false)
+    }
+
     /// The tokens surrounding a node that are ignored and not accessible through the node's own accessors.
     /// Use this if you want to get surrounding comments or whitespace.
     /// Return value is None if a token doesn't have both a start and end position. Otherwise, it is a tuple
@@ -82,6 +128,14 @@ impl<T: Node> Node for &T {
     fn similar(&self, other: &Self) -> bool {
         (**self).similar(other)
     }
+
+    fn children(&self) -> Vec<&dyn Node> {
+        (**self).children()
+    }
+
+    fn collect_as_children<'s>(&'s self, children: &mut Vec<&'s dyn Node>) {
+        (**self).collect_as_children(children);
+    }
 }
 
 impl<T: Node> Node for &mut T {
@@ -96,6 +150,14 @@ impl<T: Node> Node for &mut T {
     fn similar(&self, other: &Self) -> bool {
         (**self).similar(other)
     }
+
+    fn children(&self) -> Vec<&dyn Node> {
+        (**self).children()
+    }
+
+    fn collect_as_children<'s>(&'s self, children: &mut Vec<&'s dyn Node>) {
+        (**self).collect_as_children(children);
+    }
 }
 
 impl<'a> Node for Token<'a> {
@@ -110,6 +172,10 @@ impl<'a> Node for Token<'a> {
     fn similar(&self, other: &Self) -> bool {
         *self.token_type() == *other.token_type()
     }
+
+    fn children(&self) -> Vec<&dyn Node> {
+        Vec::new()
+    }
 }
 
 impl<'a> Node for TokenReference<'a> {
@@ -124,6 +190,10 @@ impl<'a> Node for TokenReference<'a> {
     fn similar(&self, other: &Self) -> bool {
         (**self).similar(other)
     }
+
+    fn children(&self) -> Vec<&dyn Node> {
+        Vec::new()
+    }
 }
 
 impl<T: Node> Node for Option<T> {
@@ -142,6 +212,18 @@ impl<T: Node> Node for Option<T> {
             _ => false,
         }
     }
+
+    fn children(&self) -> Vec<&dyn Node> {
+        let mut children = Vec::new();
+        self.collect_as_children(&mut children);
+        children
+    }
+
+    fn collect_as_children<'s>(&'s self, children: &mut Vec<&'s dyn Node>) {
+        if let Some(node) = self {
+            node.collect_as_children(children);
+        }
+    }
 }
 
 impl<T: Node> Node for Vec<T> {
@@ -160,6 +242,18 @@ impl<T: Node> Node for Vec<T> {
             false
         }
     }
+
+    fn children(&self) -> Vec<&dyn Node> {
+        let mut children = Vec::new();
+        self.collect_as_children(&mut children);
+        children
+    }
+
+    fn collect_as_children<'s>(&'s self, children: &mut Vec<&'s dyn Node>) {
+        for node in self {
+            node.collect_as_children(children);
+        }
+    }
 }
 
 impl<A: Node, B: Node> Node for (A, B) {
@@ -184,4 +278,121 @@ impl<A: Node, B: Node> Node for (A, B) {
     fn similar(&self, other: &Self) -> bool {
         self.0.similar(&other.0) && self.1.similar(&other.1)
     }
+
+    fn children(&self) -> Vec<&dyn Node> {
+        let mut children = Vec::new();
+        self.collect_as_children(&mut children);
+        children
+    }
+
+    fn collect_as_children<'s>(&'s self, children: &mut Vec<&'s dyn Node>) {
+        self.0.collect_as_children(children);
+        self.1.collect_as_children(children);
+    }
+}
+
+/// A range of code between two positions, such as the code a node was parsed from.
+/// The start position is part of the span, while the end position is just after it.
+///
+/// ```rust
+/// # use full_moon::node::Node;
+/// # fn main() -> Result<(), Box<std::error::Error>> {
+/// let ast = full_moon::parse("local x = 1")?;
+/// let stmt = ast.nodes().iter_stmts().next().unwrap();
+/// let span = stmt.span().unwrap();
+/// assert_eq!(span.len_bytes(), 11);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Span {
+    start: Position,
+    end: Position,
+}
+
+impl Span {
+    /// Creates a span from its start and end positions
+    pub fn new(start: Position, end: Position) -> Self {
+        Self { start, end }
+    }
+
+    /// The position the span starts at
+    pub fn start(self) -> Position {
+        self.start
+    }
+
+    /// The position just after the end of the span
+    pub fn end(self) -> Position {
+        self.end
+    }
+
+    /// Whether the position is inside the span
+    pub fn contains(self, position: Position) -> bool {
+        self.start <= position && position < self.end
+    }
+
+    /// Whether any code is inside both spans
+    pub fn overlaps(self, other: Span) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+
+    /// Whether all of the other span is inside this one
+    pub fn encloses(self, other: Span) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    /// The number of bytes of code inside the span
+    pub fn len_bytes(self) -> usize {
+        self.end.bytes().saturating_sub(self.start.bytes())
+    }
+}
+
+/// Wraps a node so that it's compared and hashed semantically, ignoring whitespace, comments and positions,
+/// the same way as [`Node::similar`](trait.Node.html#tymethod.similar).
+/// Useful to put nodes in collections such as a `HashSet`.
+///
+/// ```rust
+/// # use full_moon::node::TriviaBlind;
+/// # fn main() -> Result<(), Box<std::error::Error>> {
+/// let ast = full_moon::parse("call(1) --[[ comment ]] call( 1 )")?;
+/// let mut stmts = ast.nodes().iter_stmts();
+/// assert_eq!(TriviaBlind(stmts.next().unwrap()), TriviaBlind(stmts.next().unwrap()));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct TriviaBlind<T>(pub T);
+
+impl<T: Node> PartialEq for TriviaBlind<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.similar(&other.0)
+    }
+}
+
+impl<T: Node> Eq for TriviaBlind<T> {}
+
+impl<'ast, T: Node + Visit<'ast>> Hash for TriviaBlind<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.visit(&mut TokenHasher { state });
+    }
+}
+
+// Hashes the tokens of a node, which are the same for similar nodes
+struct TokenHasher<'a, H> {
+    state: &'a mut H,
+}
+
+impl<'a, 'ast, H: Hasher> Visitor<'ast> for TokenHasher<'a, H> {
+    fn visit_token(&mut self, token: &TokenReference<'ast>) {
+        token.to_string().hash(self.state);
+    }
+
+    // The tokens of a contained span aren't visited
+    fn visit_contained_span(&mut self, span: &ContainedSpan<'ast>) {
+        span.tokens().0.to_string().hash(self.state);
+    }
+
+    fn visit_contained_span_end(&mut self, span: &ContainedSpan<'ast>) {
+        span.tokens().1.to_string().hash(self.state);
+    }
 }
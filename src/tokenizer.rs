@@ -5,10 +5,10 @@ use generational_arena::{Arena, Index};
 use nom::{
     branch::alt,
     bytes::complete::{tag, take_till, take_while, take_while1},
-    character::complete::{anychar, digit1, one_of},
-    combinator::{opt, recognize},
+    character::complete::{anychar, digit0, digit1, one_of},
+    combinator::{not, opt, recognize},
     multi::{many1, many_till},
-    sequence::{delimited, pair, preceded, tuple},
+    sequence::{delimited, pair, preceded, terminated, tuple},
     IResult,
 };
 #[cfg(feature = "serde")]
@@ -47,28 +47,50 @@ symbols!(
     Until => "until",
     While => "while",
 
+    // TODO: This only is valid in Lua 5.3
+    Ampersand => "&",
     // TODO: This only is valid in Roblox
     FatArrow => "=>",
+    // TODO: This only is valid in Roblox
+    At => "@",
+    // TODO: This only is valid in Roblox
+    CaretEqual => "^=",
     Caret => "^",
+    // TODO: This only is valid in Lua 5.2
+    TwoColons => "::",
     Colon => ":",
     Comma => ",",
     Ellipse => "...",
+    // TODO: This only is valid in Roblox
+    TwoDotsEqual => "..=",
     TwoDots => "..",
     Dot => ".",
     TwoEqual => "==",
     Equal => "=",
+    // TODO: This only is valid in Lua 5.3
+    TwoGreaterThan => ">>",
     GreaterThanEqual => ">=",
     GreaterThan => ">",
     Hash => "#",
     LeftBrace => "{",
     LeftBracket => "[",
     LeftParen => "(",
+    // TODO: This only is valid in Lua 5.3
+    TwoLessThan => "<<",
     LessThanEqual => "<=",
     LessThan => "<",
+    // TODO: This only is valid in Roblox
+    MinusEqual => "-=",
+    // TODO: This only is valid in Roblox
+    ThinArrow => "->",
     Minus => "-",
+    // TODO: This only is valid in Roblox
+    PercentEqual => "%=",
     Percent => "%",
     // TODO: This only is valid in Roblox
     Pipe => "|",
+    // TODO: This only is valid in Roblox
+    PlusEqual => "+=",
     Plus => "+",
     // TODO: This only is valid in Roblox
     QuestionMark => "?",
@@ -76,15 +98,25 @@ symbols!(
     RightBracket => "]",
     RightParen => ")",
     Semicolon => ";",
+    // TODO: This only is valid in Lua 5.3
+    DoubleSlash => "//",
+    // TODO: This only is valid in Roblox
+    SlashEqual => "/=",
     Slash => "/",
+    // TODO: This only is valid in Roblox
+    StarEqual => "*=",
     Star => "*",
     TildeEqual => "~=",
+    // TODO: This only is valid in Lua 5.3
+    Tilde => "~",
 );
 
 /// The possible errors that can happen while tokenizing.
 #[derive(Clone, Copy, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum TokenizerErrorType {
+    /// A number ran into a name without anything between them, such as `3x`, `3e` or `0xG`
+    MalformedNumber,
     /// An unclosed multi-line comment was found
     UnclosedComment,
     /// An unclosed string was found
@@ -198,6 +230,120 @@ impl<'a> TokenType<'a> {
             TokenType::Whitespace { .. } => TokenKind::Whitespace,
         }
     }
+
+    /// Creates a multi line string, such as `[[string]]`, with the fewest equals signs that
+    /// keep the string from being closed early by its own contents. Refer to
+    /// [`long_bracket_level`](fn.long_bracket_level.html) for more details.
+    /// As Lua skips a new line right after the opening brackets, contents starting with one get another put before them.
+    ///
+    /// ```rust
+    /// # use full_moon::tokenizer::{Token, TokenType};
+    /// let token = Token::new(TokenType::long_string("a]]b".into()));
+    /// assert_eq!(token.to_string(), "[=[a]]b]=]");
+    ///
+    /// let token = Token::new(TokenType::long_string("\nline".into()));
+    /// assert_eq!(token.to_string(), "[[\n\nline]]");
+    /// ```
+    pub fn long_string(contents: Cow<'a, str>) -> TokenType<'a> {
+        let literal = match contents.chars().next() {
+            Some(new_line @ '\n') | Some(new_line @ '\r') => {
+                Cow::Owned(format!("{}{}", new_line, contents))
+            }
+            _ => contents,
+        };
+
+        TokenType::StringLiteral {
+            multi_line: Some(long_bracket_level(&literal)),
+            literal,
+            quote_type: StringLiteralQuoteType::Brackets,
+        }
+    }
+
+    /// Creates a string with the contents given, in the preferred style of quotes, such as the style of a string being rewritten.
+    /// The style is only switched when the contents force it to be: a quoted string switches to the other quotes
+    /// if the contents have its quote in them but not the other one, and a multi line string becomes a double quoted one
+    /// if the contents have a carriage return, which multi line strings can't hold.
+    /// Anything else that a quoted string can't hold as it is, such as its own quote or a new line, is escaped.
+    ///
+    /// ```rust
+    /// # use full_moon::tokenizer::{StringLiteralQuoteType, Token, TokenType};
+    /// let string = |contents, quote_type| Token::new(TokenType::string(contents, quote_type)).to_string();
+    /// assert_eq!(string("a", StringLiteralQuoteType::Single), "'a'");
+    /// assert_eq!(string("it's", StringLiteralQuoteType::Single), "\"it's\"");
+    /// assert_eq!(string("'\"\n", StringLiteralQuoteType::Double), "\"'\\\"\\n\"");
+    /// assert_eq!(string("a]]b", StringLiteralQuoteType::Brackets), "[=[a]]b]=]");
+    /// ```
+    pub fn string(contents: &str, preferred: StringLiteralQuoteType) -> TokenType<'a> {
+        let quote_type = match preferred {
+            StringLiteralQuoteType::Brackets if contents.contains('\r') => {
+                StringLiteralQuoteType::Double
+            }
+            StringLiteralQuoteType::Brackets => {
+                return TokenType::long_string(Cow::Owned(contents.to_owned()))
+            }
+
+            StringLiteralQuoteType::Double
+                if contents.contains('"') && !contents.contains('\'') =>
+            {
+                StringLiteralQuoteType::Single
+            }
+            StringLiteralQuoteType::Single
+                if contents.contains('\'') && !contents.contains('"') =>
+            {
+                StringLiteralQuoteType::Double
+            }
+            quote_type => quote_type,
+        };
+
+        let quote = match quote_type {
+            StringLiteralQuoteType::Single => '\'',
+            _ => '"',
+        };
+
+        let mut literal = String::with_capacity(contents.len());
+        for character in contents.chars() {
+            match character {
+                '\\' => literal.push_str("\\\\"),
+                '\n' => literal.push_str("\\n"),
+                '\r' => literal.push_str("\\r"),
+                character if character == quote => {
+                    literal.push('\\');
+                    literal.push(character);
+                }
+                // Escapes always have three digits, so a digit after one isn't read as part of it
+                character if character.is_ascii_control() => {
+                    literal.push_str(&format!("\\{:03}", character as u8))
+                }
+                character => literal.push(character),
+            }
+        }
+
+        TokenType::StringLiteral {
+            literal: Cow::Owned(literal),
+            multi_line: None,
+            quote_type,
+        }
+    }
+}
+
+/// The fewest equals signs needed between the brackets of a multi line string or comment for it to hold the contents given.
+/// The contents can't have the closing brackets in them, such as `]]` for `[[string]]`, and also can't end
+/// with the start of them, such as `]`, as it would be joined with the closing brackets.
+///
+/// ```rust
+/// # use full_moon::tokenizer::long_bracket_level;
+/// assert_eq!(long_bracket_level("string"), 0);
+/// assert_eq!(long_bracket_level("a]]b"), 1);
+/// assert_eq!(long_bracket_level("a]]b]=]c"), 2);
+/// ```
+pub fn long_bracket_level(contents: &str) -> usize {
+    (0..)
+        .find(|&level| {
+            let equals = "=".repeat(level);
+            !contents.contains(&format!("]{}]", equals))
+                && !contents.ends_with(&format!("]{}", equals))
+        })
+        .expect("ran out of levels")
 }
 
 /// The kind of token. Contains no additional data.
@@ -233,6 +379,16 @@ pub struct Token<'a> {
 }
 
 impl<'a> Token<'a> {
+    /// Creates a token with the given type, for use in nodes that weren't parsed from code.
+    /// As the token isn't part of any code, both its start and end positions are the default position.
+    pub fn new(token_type: TokenType<'a>) -> Token<'a> {
+        Token {
+            start_position: Arc::new(AtomicPosition::new(Position::default())),
+            end_position: Arc::new(AtomicPosition::new(Position::default())),
+            token_type: Arc::new(AtomicRefCell::new(token_type)),
+        }
+    }
+
     /// The position a token begins at
     pub fn start_position(&self) -> Position {
         self.start_position.load()
@@ -254,35 +410,103 @@ impl<'a> Token<'a> {
     pub fn token_kind(&self) -> TokenKind {
         self.token_type().kind()
     }
+
+    /// The symbol of the token, such as `local` or `+`, if it's a [`TokenType::Symbol`](enum.TokenType.html#variant.Symbol)
+    pub fn as_symbol(&self) -> Option<Symbol> {
+        match &*self.token_type() {
+            TokenType::Symbol { symbol } => Some(*symbol),
+            _ => None,
+        }
+    }
+
+    /// The name of the token, such as `foo`, if it's a [`TokenType::Identifier`](enum.TokenType.html#variant.Identifier).
+    /// The name is still borrowed from the code if the token was parsed from it, so this is cheap to compare against other names.
+    ///
+    /// ```rust
+    /// # use std::borrow::Cow;
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let tokens = full_moon::tokenizer::tokens("foo")?;
+    /// assert_eq!(tokens[0].as_identifier(), Some(Cow::Borrowed("foo")));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn as_identifier(&self) -> Option<Cow<'a, str>> {
+        match &*self.token_type() {
+            TokenType::Identifier { identifier } => Some(identifier.clone()),
+            _ => None,
+        }
+    }
+
+    /// The text of the token, such as `0x10`, if it's a [`TokenType::Number`](enum.TokenType.html#variant.Number)
+    pub fn as_number(&self) -> Option<Cow<'a, str>> {
+        match &*self.token_type() {
+            TokenType::Number { text } => Some(text.clone()),
+            _ => None,
+        }
+    }
+
+    /// The contents of the token without its quotation marks or brackets, such as `hello` for `"hello"`,
+    /// if it's a [`TokenType::StringLiteral`](enum.TokenType.html#variant.StringLiteral).
+    /// Escape sequences, such as `\n`, are kept as they're written.
+    pub fn as_string(&self) -> Option<Cow<'a, str>> {
+        match &*self.token_type() {
+            TokenType::StringLiteral { literal, .. } => Some(literal.clone()),
+            _ => None,
+        }
+    }
+
+    /// The text of the token without the `--` or the brackets around it, such as ` hello` for `-- hello`,
+    /// if it's a comment. Use [`token_kind`](#method.token_kind) to tell whether it's a
+    /// [single line comment](enum.TokenKind.html#variant.SingleLineComment) or a
+    /// [multi line comment](enum.TokenKind.html#variant.MultiLineComment), such as `--[[ x ]]`.
+    ///
+    /// ```rust
+    /// # use full_moon::tokenizer::TokenKind;
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let tokens = full_moon::tokenizer::tokens("--[[ x ]]")?;
+    /// assert_eq!(tokens[0].comment_text().as_deref(), Some(" x "));
+    /// assert_eq!(tokens[0].token_kind(), TokenKind::MultiLineComment);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn comment_text(&self) -> Option<Cow<'a, str>> {
+        match &*self.token_type() {
+            TokenType::SingleLineComment { comment } => Some(comment.clone()),
+            TokenType::MultiLineComment { comment, .. } => Some(comment.clone()),
+            _ => None,
+        }
+    }
 }
 
 impl<'a> fmt::Display for Token<'a> {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         use self::TokenType::*;
 
+        // Written straight to the formatter, as this is called for every token when printing
         match &*self.token_type() {
-            Eof => "".to_string(),
-            Number { text } => text.to_string(),
-            Identifier { identifier } => identifier.to_string(),
+            Eof => Ok(()),
+            Number { text } => text.fmt(formatter),
+            Identifier { identifier } => identifier.fmt(formatter),
             MultiLineComment { blocks, comment } => {
-                format!("--[{0}[{1}]{0}]", "=".repeat(*blocks), comment)
+                let equals = "=".repeat(*blocks);
+                write!(formatter, "--[{0}[{1}]{0}]", equals, comment)
             }
-            SingleLineComment { comment } => format!("--{}", comment),
+            SingleLineComment { comment } => write!(formatter, "--{}", comment),
             StringLiteral {
                 literal,
                 multi_line,
                 quote_type,
             } => {
                 if let Some(blocks) = multi_line {
-                    format!("[{0}[{1}]{0}]", "=".repeat(*blocks), literal.to_string())
+                    let equals = "=".repeat(*blocks);
+                    write!(formatter, "[{0}[{1}]{0}]", equals, literal)
                 } else {
-                    format!("{0}{1}{0}", quote_type.to_string(), literal.to_string())
+                    write!(formatter, "{0}{1}{0}", quote_type, literal)
                 }
             }
-            Symbol { symbol } => symbol.to_string(),
-            Whitespace { characters } => characters.to_string(),
+            Symbol { symbol } => symbol.fmt(formatter),
+            Whitespace { characters } => characters.fmt(formatter),
         }
-        .fmt(formatter)
     }
 }
 
@@ -321,15 +545,209 @@ pub enum TokenReference<'a> {
 
     /// Token reference was manually created, likely through deserialization
     #[doc(hidden)]
-    Owned(Token<'a>),
+    Owned {
+        leading_trivia: Vec<Token<'a>>,
+        token: Token<'a>,
+        trailing_trivia: Vec<Token<'a>>,
+    },
 }
 
 impl<'a> TokenReference<'a> {
+    /// Creates a reference to a new token, with the trivia (whitespace and comments) to put around it.
+    /// Useful when creating nodes that weren't parsed from code.
+    pub fn new(
+        leading_trivia: Vec<Token<'a>>,
+        token: Token<'a>,
+        trailing_trivia: Vec<Token<'a>>,
+    ) -> TokenReference<'a> {
+        TokenReference::Owned {
+            leading_trivia,
+            token,
+            trailing_trivia,
+        }
+    }
+
+    /// Creates a reference to a new [symbol](enum.Symbol.html) token, such as `{` or `end`, with no trivia.
+    /// Useful when creating nodes that weren't parsed from code.
+    ///
+    /// ```rust
+    /// # use full_moon::tokenizer::{Symbol, TokenReference};
+    /// assert_eq!(TokenReference::symbol(Symbol::End).to_string(), "end");
+    /// ```
+    pub fn symbol(symbol: Symbol) -> TokenReference<'a> {
+        TokenReference::new(
+            Vec::new(),
+            Token::new(TokenType::Symbol { symbol }),
+            Vec::new(),
+        )
+    }
+
+    /// The trivia (whitespace and comments) before the token that belongs to it.
+    /// This is everything since the previous token's [trailing trivia](#method.trailing_trivia).
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("local x = 1 -- one\n\n-- two\nlocal y = 2")?;
+    /// let second = ast.nodes().iter_stmts().nth(1).unwrap();
+    /// # let second = match second {
+    /// #     full_moon::ast::Stmt::LocalAssignment(local_assignment) => local_assignment,
+    /// #     _ => unreachable!(),
+    /// # };
+    /// let trivia: Vec<_> = second.local_token().leading_trivia().map(ToString::to_string).collect();
+    /// assert_eq!(trivia, vec!["\n", "-- two", "\n"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn leading_trivia(&self) -> impl Iterator<Item = &Token<'a>> {
+        let (borrowed, owned) = match self {
+            TokenReference::Borrowed { arena, index } => {
+                let (index, _) = index.into_raw_parts();
+                let trivia_start = trivia_before(arena, index);
+
+                let start = if trivia_start == 0 {
+                    0
+                } else {
+                    trailing_trivia_end(arena, trivia_start - 1)
+                };
+
+                (Some(trivia_range(arena, start, index)), None)
+            }
+
+            TokenReference::Owned { leading_trivia, .. } => (None, Some(leading_trivia.iter())),
+        };
+
+        borrowed
+            .into_iter()
+            .flatten()
+            .chain(owned.into_iter().flatten())
+    }
+
+    /// The trivia (whitespace and comments) after the token that belongs to it.
+    /// This is everything until the end of the line the token is on, including the new line.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<std::error::Error>> {
+    /// let ast = full_moon::parse("local x = 1 -- one\n\n-- two\nlocal y = 2")?;
+    /// # let first = match ast.nodes().iter_stmts().next().unwrap() {
+    /// #     full_moon::ast::Stmt::LocalAssignment(local_assignment) => local_assignment,
+    /// #     _ => unreachable!(),
+    /// # };
+    /// // The `1` in `local x = 1`
+    /// let one = first.expr_list().iter().next().unwrap();
+    /// # let one = match one {
+    /// #     full_moon::ast::Expression::Value { value, .. } => match &**value {
+    /// #         full_moon::ast::Value::Number(number) => number,
+    /// #         _ => unreachable!(),
+    /// #     },
+    /// #     _ => unreachable!(),
+    /// # };
+    /// let trivia: Vec<_> = one.trailing_trivia().map(ToString::to_string).collect();
+    /// assert_eq!(trivia, vec![" ", "-- one", "\n"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn trailing_trivia(&self) -> impl Iterator<Item = &Token<'a>> {
+        let (borrowed, owned) = match self {
+            TokenReference::Borrowed { arena, index } => {
+                let (index, _) = index.into_raw_parts();
+                let end = trailing_trivia_end(arena, index);
+                (Some(trivia_range(arena, index + 1, end)), None)
+            }
+
+            TokenReference::Owned {
+                trailing_trivia, ..
+            } => (None, Some(trailing_trivia.iter())),
+        };
+
+        borrowed
+            .into_iter()
+            .flatten()
+            .chain(owned.into_iter().flatten())
+    }
+
     /// Sets the type of token. Note that positions will not update after using this function.
     /// If you need them to, call [`Ast::update_positions`](../ast/struct.Ast.html#method.update_positions)
     pub fn set_token_type(&mut self, new_token_type: TokenType<'a>) {
         *self.token_type.borrow_mut() = new_token_type;
     }
+
+    // Splits a symbol made of two one character symbols, such as `>>`, into a token for each of them.
+    // The first keeps the leading trivia and the second the trailing trivia.
+    #[cfg(all(feature = "roblox", feature = "lua53"))]
+    pub(crate) fn split_symbol(
+        &self,
+        first: Symbol,
+        second: Symbol,
+    ) -> (TokenReference<'a>, TokenReference<'a>) {
+        let start = self.start_position();
+        let middle = Position {
+            bytes: start.bytes + 1,
+            character: start.character + 1,
+            line: start.line,
+        };
+
+        let token = |symbol, start, end| Token {
+            start_position: Arc::new(AtomicPosition::new(start)),
+            end_position: Arc::new(AtomicPosition::new(end)),
+            token_type: Arc::new(AtomicRefCell::new(TokenType::Symbol { symbol })),
+        };
+
+        (
+            TokenReference::new(
+                self.leading_trivia().cloned().collect(),
+                token(first, start, middle),
+                Vec::new(),
+            ),
+            TokenReference::new(
+                Vec::new(),
+                token(second, middle, self.end_position()),
+                self.trailing_trivia().cloned().collect(),
+            ),
+        )
+    }
+}
+
+// Tokens are put into an arena in the order they appear in, so the raw index is their position in the code
+fn trivia_range<'a, 'b>(
+    arena: &'b Arena<Token<'a>>,
+    start: usize,
+    end: usize,
+) -> impl Iterator<Item = &'b Token<'a>> {
+    (start..end).filter_map(move |index| Some(arena.get_unknown_gen(index)?.0))
+}
+
+fn is_trivia(arena: &Arena<Token>, index: usize) -> bool {
+    match arena.get_unknown_gen(index) {
+        Some((token, _)) => token.token_type().ignore(),
+        None => false,
+    }
+}
+
+// The index of the first trivia token in the trivia right before the token at `index`
+fn trivia_before(arena: &Arena<Token>, index: usize) -> usize {
+    let mut start = index;
+    while start > 0 && is_trivia(arena, start - 1) {
+        start -= 1;
+    }
+
+    start
+}
+
+// The index right after the last trivia token trailing the token at `index`
+fn trailing_trivia_end(arena: &Arena<Token>, index: usize) -> usize {
+    let mut end = index + 1;
+    while is_trivia(arena, end) {
+        let token = arena.get_unknown_gen(end).unwrap().0;
+        end += 1;
+
+        if let TokenType::Whitespace { characters } = &*token.token_type() {
+            if characters.contains('\n') {
+                break;
+            }
+        }
+    }
+
+    end
 }
 
 impl<'a> std::borrow::Borrow<Token<'a>> for &TokenReference<'a> {
@@ -347,7 +765,7 @@ impl<'a> std::ops::Deref for TokenReference<'a> {
                 arena.get(*index).expect("arena doesn't have index?")
             }
 
-            TokenReference::Owned(token) => &token,
+            TokenReference::Owned { token, .. } => &token,
         }
     }
 }
@@ -358,9 +776,24 @@ impl<'a> fmt::Debug for TokenReference<'a> {
     }
 }
 
+/// Displays the token. With the alternate flag (`{:#}`), its leading and trailing trivia are displayed too.
 impl<'a> fmt::Display for TokenReference<'a> {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        (**self).fmt(formatter)
+        if formatter.alternate() {
+            for trivia in self.leading_trivia() {
+                trivia.fmt(formatter)?;
+            }
+
+            (**self).fmt(formatter)?;
+
+            for trivia in self.trailing_trivia() {
+                trivia.fmt(formatter)?;
+            }
+
+            Ok(())
+        } else {
+            (**self).fmt(formatter)
+        }
     }
 }
 
@@ -394,7 +827,11 @@ impl<'a> Serialize for TokenReference<'a> {
 #[cfg(feature = "serde")]
 impl<'de: 'a, 'a> Deserialize<'de> for TokenReference<'a> {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-        Ok(TokenReference::Owned(Token::deserialize(deserializer)?))
+        Ok(TokenReference::new(
+            Vec::new(),
+            Token::deserialize(deserializer)?,
+            Vec::new(),
+        ))
     }
 }
 
@@ -442,6 +879,17 @@ pub struct Position {
 }
 
 impl Position {
+    /// Creates a position from how many bytes into the code it is, and the line and character it's at,
+    /// such as to compare against the positions of nodes.
+    /// Positions are ordered by their bytes alone, so the line and character aren't checked against them.
+    pub fn new(bytes: usize, line: usize, character: usize) -> Self {
+        Self {
+            bytes,
+            character,
+            line,
+        }
+    }
+
     /// How many bytes, ignoring lines, it would take to find this position
     pub fn bytes(self) -> usize {
         self.bytes
@@ -602,17 +1050,60 @@ fn parse_hex_number(code: &str) -> IResult<&str, &str> {
     recognize(pair(tag("0x"), take_while1(|c: char| c.is_digit(16))))(code)
 }
 
+#[cfg(not(any(feature = "lua52", feature = "lua53")))]
+fn parse_hex_float(_: &str) -> IResult<&str, &str> {
+    Err(nom::Err::Error((
+        "lua52 feature not enabled",
+        nom::error::ErrorKind::Alt,
+    )))
+}
+
+#[cfg(any(feature = "lua52", feature = "lua53"))]
+fn hex_digits(code: &str) -> IResult<&str, &str> {
+    take_while1(|c: char| c.is_ascii_hexdigit())(code)
+}
+
+#[cfg(any(feature = "lua52", feature = "lua53"))]
+fn hex_expt(code: &str) -> IResult<&str, &str> {
+    recognize(tuple((one_of("pP"), opt(one_of("+-")), digit1)))(code)
+}
+
+// Lua 5.2 added hexadecimal floats, which can have a fraction and a binary exponent, such as `0x1.8p1`.
+// Like with decimal numbers, a second dot means the first is part of a concatenation, such as in `0x1..2`.
+#[cfg(any(feature = "lua52", feature = "lua53"))]
+fn parse_hex_float(code: &str) -> IResult<&str, &str> {
+    recognize(pair(
+        tag("0x"),
+        alt((
+            recognize(tuple((
+                hex_digits,
+                terminated(tag("."), not(tag("."))),
+                take_while(|c: char| c.is_ascii_hexdigit()),
+                opt(hex_expt),
+            ))),
+            recognize(tuple((tag("."), hex_digits, opt(hex_expt)))),
+            recognize(pair(hex_digits, hex_expt)),
+        )),
+    ))(code)
+}
+
 fn num_frac(code: &str) -> IResult<&str, &str> {
     recognize(pair(tag("."), digit1))(code)
 }
 
+// The fraction after the integer part of a number, which can be empty, such as in `5.`.
+// A second dot means the first is part of a concatenation, such as in `1..2`.
+fn num_trailing_frac(code: &str) -> IResult<&str, &str> {
+    recognize(pair(terminated(tag("."), not(tag("."))), digit0))(code)
+}
+
 fn num_expt(code: &str) -> IResult<&str, &str> {
-    recognize(pair(tag("e"), digit1))(code)
+    recognize(tuple((one_of("eE"), opt(one_of("+-")), digit1)))(code)
 }
 
 fn parse_basic_number(code: &str) -> IResult<&str, &str> {
     recognize(pair(
-        alt((num_frac, recognize(pair(digit1, opt(num_frac))))),
+        alt((num_frac, recognize(pair(digit1, opt(num_trailing_frac))))),
         opt(num_expt),
     ))(code)
 }
@@ -631,11 +1122,28 @@ fn parse_roblox_number(code: &str) -> IResult<&str, &str> {
 }
 
 fn parse_number(code: &str) -> IResult<&str, &str> {
-    alt((parse_roblox_number, parse_hex_number, parse_basic_number))(code)
+    alt((
+        parse_roblox_number,
+        parse_hex_float,
+        parse_hex_number,
+        parse_basic_number,
+    ))(code)
 }
 
 fn advance_number(code: &str) -> Advancement {
     match parse_number(code) {
+        // Lua reads every letter and digit right after a number as part of it,
+        // so `3x`, `3e` and `0xG` are malformed numbers rather than a number and a name
+        Ok((rest, _))
+            if rest
+                .chars()
+                .next()
+                .map(|character| character.is_ascii_alphanumeric() || character == '_')
+                .unwrap_or(false) =>
+        {
+            Err(TokenizerErrorType::MalformedNumber)
+        }
+
         Ok((_, number)) => Ok(Some(TokenAdvancement {
             advance: number.chars().count(),
             token_type: TokenType::Number {
@@ -752,12 +1260,25 @@ fn advance_quote(code: &str) -> Advancement {
 
 fn advance_symbol(code: &str) -> Advancement {
     match parse_symbol(code) {
-        Ok((_, string)) => Ok(Some(TokenAdvancement {
-            advance: string.chars().count(),
-            token_type: TokenType::Symbol {
-                symbol: Symbol::from_str(string).unwrap(),
-            },
-        })),
+        Ok((_, string)) => {
+            let symbol = Symbol::from_str(string).unwrap();
+
+            // Without Lua 5.3, its operators are tokenized as the symbols they start with,
+            // so that `>>` still closes two generics in `Array<Array<number>>`
+            #[cfg(not(feature = "lua53"))]
+            let (string, symbol) = match symbol {
+                Symbol::Ampersand | Symbol::Tilde => return Ok(None),
+                Symbol::TwoLessThan | Symbol::TwoGreaterThan | Symbol::DoubleSlash => {
+                    (&string[..1], Symbol::from_str(&string[..1]).unwrap())
+                }
+                _ => (string, symbol),
+            };
+
+            Ok(Some(TokenAdvancement {
+                advance: string.chars().count(),
+                token_type: TokenType::Symbol { symbol },
+            }))
+        }
 
         Err(_) => Ok(None),
     }
@@ -766,8 +1287,9 @@ fn advance_symbol(code: &str) -> Advancement {
 #[inline]
 fn parse_whitespace(code: &str) -> IResult<&str, &str> {
     // From regex "^[^\S\n]+\n?|\n"
+    // Lua's whitespace includes form feeds (\x0c) and vertical tabs (\x0b)
     alt((
-        recognize(pair(many1(one_of(" \t\r")), opt(tag("\n")))),
+        recognize(pair(many1(one_of(" \t\r\x0c\x0b")), opt(tag("\n")))),
         tag("\n"),
     ))(code)
 }
@@ -795,12 +1317,25 @@ pub struct TokenizerError {
     position: Position,
 }
 
+impl TokenizerError {
+    /// The type of error that occurred
+    pub fn error(&self) -> TokenizerErrorType {
+        self.error
+    }
+
+    /// The position the tokenizer was at when the error occurred
+    pub fn position(&self) -> Position {
+        self.position
+    }
+}
+
 impl fmt::Display for TokenizerError {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         write!(
             formatter,
             "{} at line {}, column {}",
             match self.error {
+                TokenizerErrorType::MalformedNumber => "malformed number".to_string(),
                 TokenizerErrorType::UnclosedComment => "unclosed comment".to_string(),
                 TokenizerErrorType::UnclosedString => "unclosed string".to_string(),
                 TokenizerErrorType::UnexpectedToken(character) => {
@@ -830,22 +1365,82 @@ impl std::error::Error for TokenizerError {}
 /// assert!(tokens("--[[ Unclosed comment!").is_err());
 /// ```
 pub fn tokens<'a>(code: &'a str) -> Result<Vec<Token<'a>>, TokenizerError> {
+    match partial_tokens(code) {
+        (tokens, None) => Ok(tokens),
+        (_, Some(error)) => Err(error),
+    }
+}
+
+/// Tokenizes as much of the code as possible, returning every [`Token`](struct.Token.html)
+/// produced before the first error alongside the [`TokenizerError`](struct.TokenizerError.html) itself, if any.
+/// Useful for tools such as editors that still want to highlight code that is partially malformed.
+/// If an error occurs, the tokens returned will not end with an end of file token.
+///
+/// ```rust
+/// # use full_moon::tokenizer::partial_tokens;
+/// let (tokens, error) = partial_tokens("local x = 'unclosed");
+/// assert_eq!(tokens.len(), 6); // `local`, ` `, `x`, ` `, `=`, ` `
+/// assert_eq!(error.unwrap().position().character(), 11);
+/// ```
+pub fn partial_tokens<'a>(code: &'a str) -> (Vec<Token<'a>>, Option<TokenizerError>) {
+    partial_tokens_from(
+        code,
+        Position {
+            bytes: 0,
+            character: 1,
+            line: 1,
+        },
+    )
+}
+
+/// Returns a list of [`Token`](struct.Token.html) structs, with positions starting from the one given instead of
+/// the start of the code. Useful for code that's part of a larger document, such as Lua inside Markdown,
+/// so that the positions of the tokens and errors are where they are in the document.
+/// The position is where the first character of the code is, so only the first line starts at its character.
+/// You probably want [`parse_with_offset`](../fn.parse_with_offset.html) instead.
+///
+/// # Errors
+///
+/// If the code passed is malformed from normal Lua expectations,
+/// a [`TokenizerError`](struct.TokenizerError.html) will be returned.
+///
+/// ```rust
+/// # use full_moon::tokenizer::{tokens_with_offset, Position};
+/// # fn main() -> Result<(), Box<std::error::Error>> {
+/// let tokens = tokens_with_offset("local x", Position::new(100, 10, 5))?;
+/// assert_eq!(tokens[2].start_position(), Position::new(106, 10, 11));
+/// # Ok(())
+/// # }
+/// ```
+pub fn tokens_with_offset<'a>(
+    code: &'a str,
+    offset: Position,
+) -> Result<Vec<Token<'a>>, TokenizerError> {
+    match partial_tokens_from(code, offset) {
+        (tokens, None) => Ok(tokens),
+        (_, Some(error)) => Err(error),
+    }
+}
+
+fn partial_tokens_from<'a>(
+    code: &'a str,
+    start: Position,
+) -> (Vec<Token<'a>>, Option<TokenizerError>) {
     let mut tokens = Vec::new();
-    let mut position = Position {
-        bytes: 0,
-        character: 1,
-        line: 1,
-    };
+    let mut position = start;
 
     let mut next_is_new_line = false;
 
     macro_rules! advance {
         ($function:ident) => {
-            match $function(&code[position.bytes..]) {
+            match $function(&code[position.bytes - start.bytes..]) {
                 Ok(Some(advancement)) => {
                     let start_position = position;
 
-                    for character in code[position.bytes..].chars().take(advancement.advance) {
+                    for character in code[position.bytes - start.bytes..]
+                        .chars()
+                        .take(advancement.advance)
+                    {
                         if next_is_new_line {
                             next_is_new_line = false;
                             position.line += 1;
@@ -873,13 +1468,13 @@ pub fn tokens<'a>(code: &'a str) -> Result<Vec<Token<'a>>, TokenizerError> {
                 Ok(None) => {}
 
                 Err(error) => {
-                    return Err(TokenizerError { error, position });
+                    return (tokens, Some(TokenizerError { error, position }));
                 }
             };
         };
     }
 
-    while code.bytes().count() > position.bytes {
+    while code.len() > position.bytes - start.bytes {
         advance!(advance_whitespace);
         advance!(advance_comment);
         advance!(advance_number);
@@ -887,14 +1482,17 @@ pub fn tokens<'a>(code: &'a str) -> Result<Vec<Token<'a>>, TokenizerError> {
         advance!(advance_symbol);
         advance!(advance_identifier);
 
-        return Err(TokenizerError {
+        let error = TokenizerError {
             error: TokenizerErrorType::UnexpectedToken(
-                code.chars()
-                    .nth(position.character - 1)
+                code[position.bytes - start.bytes..]
+                    .chars()
+                    .next()
                     .expect("text overflow while giving unexpected token error"),
             ),
             position,
-        });
+        };
+
+        return (tokens, Some(error));
     }
 
     tokens.push(Token {
@@ -903,7 +1501,7 @@ pub fn tokens<'a>(code: &'a str) -> Result<Vec<Token<'a>>, TokenizerError> {
         token_type: Arc::new(AtomicRefCell::new(TokenType::Eof)),
     });
 
-    Ok(tokens)
+    (tokens, None)
 }
 
 #[cfg(feature = "serde")]
@@ -924,6 +1522,48 @@ mod serde_arc_atomic_refcell {
     }
 }
 
+/// Returns every comment in the code along with the position it starts at, in the order they appear.
+/// Both single line comments, such as `-- comment`, and multi line comments, such as `--[[ comment ]]`,
+/// are included, written the same way as in the code. This only tokenizes the code, so is faster than
+/// parsing it when nothing else is needed.
+///
+/// ```rust
+/// # use full_moon::tokenizer::comments;
+/// # fn main() -> Result<(), Box<std::error::Error>> {
+/// let comments = comments("local x = 1 -- one\n--[[ two ]] call()")?;
+/// assert_eq!(comments[0].1, "-- one");
+/// assert_eq!(comments[1].0.line(), 2);
+/// assert_eq!(comments[1].1, "--[[ two ]]");
+/// # Ok(())
+/// # }
+/// ```
+pub fn comments(code: &str) -> Result<Vec<(Position, String)>, TokenizerError> {
+    let tokens = tokens(code)?;
+    let mut comments = Vec::new();
+
+    for (index, token) in tokens.iter().enumerate() {
+        match &*token.token_type() {
+            TokenType::SingleLineComment { .. } | TokenType::MultiLineComment { .. } => {}
+            _ => continue,
+        }
+
+        let mut position = token.start_position();
+
+        // A token right after a new line is given the position of the new line, not the start of its own line
+        if index > 0 && tokens[index - 1].to_string().ends_with('\n') {
+            position = Position {
+                bytes: position.bytes,
+                character: 1,
+                line: position.line + 1,
+            };
+        }
+
+        comments.push((position, token.to_string()));
+    }
+
+    Ok(comments)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::tokenizer::*;
@@ -1018,6 +1658,61 @@ mod tests {
                 },
             }))
         );
+
+        test_advancer!(
+            advance_number("5."),
+            Ok(Some(TokenAdvancement {
+                advance: 2,
+                token_type: TokenType::Number {
+                    text: Cow::from("5."),
+                },
+            }))
+        );
+
+        test_advancer!(
+            advance_number("5.e2"),
+            Ok(Some(TokenAdvancement {
+                advance: 4,
+                token_type: TokenType::Number {
+                    text: Cow::from("5.e2"),
+                },
+            }))
+        );
+
+        test_advancer!(
+            advance_number("1.5E-3"),
+            Ok(Some(TokenAdvancement {
+                advance: 6,
+                token_type: TokenType::Number {
+                    text: Cow::from("1.5E-3"),
+                },
+            }))
+        );
+    }
+
+    #[test]
+    fn test_concatenated_numbers() {
+        let tokens = tokens("1..2").unwrap();
+        let token_types: Vec<_> = tokens
+            .iter()
+            .map(|token| token.token_type().clone())
+            .collect();
+
+        assert_eq!(
+            token_types,
+            vec![
+                TokenType::Number {
+                    text: Cow::from("1"),
+                },
+                TokenType::Symbol {
+                    symbol: Symbol::TwoDots,
+                },
+                TokenType::Number {
+                    text: Cow::from("2"),
+                },
+                TokenType::Eof,
+            ]
+        );
     }
 
     #[test]
@@ -1113,6 +1808,39 @@ mod tests {
                 },
             }))
         );
+
+        test_advancer!(
+            advance_whitespace("\x0c\x0b\r\nhello"),
+            Ok(Some(TokenAdvancement {
+                advance: 4,
+                token_type: TokenType::Whitespace {
+                    characters: Cow::from("\x0c\x0b\r\n"),
+                },
+            }))
+        );
+    }
+
+    #[test]
+    fn test_form_feed_and_vertical_tab() {
+        let tokens = tokens("local\x0cx\x0b=\x0c\x0b1").expect("couldn't tokenize");
+        let kinds = tokens
+            .iter()
+            .map(|token| token.token_kind())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Symbol,
+                TokenKind::Whitespace,
+                TokenKind::Identifier,
+                TokenKind::Whitespace,
+                TokenKind::Symbol,
+                TokenKind::Whitespace,
+                TokenKind::Number,
+                TokenKind::Eof,
+            ]
+        );
     }
 
     #[test]
@@ -1176,10 +1904,194 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_keywords() {
+        let keywords = [
+            ("and", Symbol::And),
+            ("break", Symbol::Break),
+            ("do", Symbol::Do),
+            ("else", Symbol::Else),
+            ("elseif", Symbol::ElseIf),
+            ("end", Symbol::End),
+            ("false", Symbol::False),
+            ("for", Symbol::For),
+            ("function", Symbol::Function),
+            ("if", Symbol::If),
+            ("in", Symbol::In),
+            ("local", Symbol::Local),
+            ("nil", Symbol::Nil),
+            ("not", Symbol::Not),
+            ("or", Symbol::Or),
+            ("repeat", Symbol::Repeat),
+            ("return", Symbol::Return),
+            ("then", Symbol::Then),
+            ("true", Symbol::True),
+            ("until", Symbol::Until),
+            ("while", Symbol::While),
+        ];
+
+        for (keyword, symbol) in keywords.iter() {
+            assert_eq!(Symbol::from_keyword(keyword), Some(*symbol));
+            assert_eq!(symbol.to_string(), *keyword);
+
+            let keyword_tokens = tokens(keyword).expect("couldn't tokenize");
+            assert_eq!(
+                *keyword_tokens[0].token_type(),
+                TokenType::Symbol { symbol: *symbol }
+            );
+
+            // Identifiers that only start with a keyword aren't keywords
+            let identifier = format!("{}_", keyword);
+            let identifier_tokens = tokens(&identifier).expect("couldn't tokenize");
+            assert_eq!(identifier_tokens[0].token_kind(), TokenKind::Identifier);
+        }
+
+        for not_keyword in &["", "Local", "ends", "+", "..", "=>", "self"] {
+            assert_eq!(Symbol::from_keyword(not_keyword), None);
+        }
+    }
+
+    #[test]
+    fn test_malformed_numbers() {
+        for (code, character) in &[("3x", 11), ("3e", 11), ("0xG", 11), ("0b12", 11)] {
+            let error =
+                tokens(&format!("local x = {}", code)).expect_err("malformed number tokenized");
+            assert_eq!(error.error(), TokenizerErrorType::MalformedNumber);
+            assert_eq!(error.position().character(), *character);
+        }
+
+        for code in &["3", "3e5", "0x1F", "1..2", "3.5 x"] {
+            assert!(tokens(code).is_ok(), "couldn't tokenize {}", code);
+        }
+    }
+
+    #[test]
+    #[cfg(any(feature = "lua52", feature = "lua53"))]
+    fn test_hex_floats() {
+        for code in &["0x1.8p1", "0xA.", "0x.8", "0x1p-2", "0xF.Fp+4"] {
+            let tokens = tokens(code).expect("couldn't tokenize hex float");
+            assert_eq!(tokens[0].as_number().as_deref(), Some(*code));
+        }
+
+        let tokens = tokens("0x1..2").expect("couldn't tokenize concatenation");
+        assert_eq!(tokens[0].as_number().as_deref(), Some("0x1"));
+    }
+
+    #[test]
+    fn test_partial_tokens() {
+        let (tokens, error) = partial_tokens("local x = 1\nlocal y = \"oops\nlocal z = 3");
+        let error = error.expect("tokenizing should have failed");
+
+        assert_eq!(error.error(), TokenizerErrorType::UnclosedString);
+        assert_eq!(error.position().line(), 2);
+        assert_eq!(error.position().character(), 11);
+
+        assert_eq!(
+            tokens
+                .iter()
+                .map(|token| token.to_string())
+                .collect::<Vec<_>>(),
+            vec!["local", " ", "x", " ", "=", " ", "1", "\n", "local", " ", "y", " ", "=", " "],
+        );
+        assert!(tokens
+            .iter()
+            .all(|token| token.token_kind() != TokenKind::Eof));
+
+        let (tokens, error) = partial_tokens("local x = 1");
+        assert!(error.is_none());
+        assert_eq!(tokens.last().unwrap().token_kind(), TokenKind::Eof);
+    }
+
     #[test]
     fn test_fuzzer() {
         let _ = tokens("*ա");
         let _ = tokens("̹(");
         let _ = tokens("¹;");
     }
+
+    #[test]
+    fn test_comments() {
+        let comments =
+            comments("-- first\nlocal x = 1 --[==[ second\nline ]==]\n\t--third").unwrap();
+
+        let found: Vec<_> = comments
+            .iter()
+            .map(|(position, comment)| (position.line(), position.character(), comment.as_str()))
+            .collect();
+
+        assert_eq!(
+            found,
+            vec![
+                (1, 1, "-- first"),
+                (2, 13, "--[==[ second\nline ]==]"),
+                (4, 2, "--third"),
+            ]
+        );
+
+        assert!(super::comments("--[[ unclosed").is_err());
+    }
+
+    #[test]
+    fn test_classify_tokens() {
+        let tokens = tokens("local x = 0x10 .. [[long]]").unwrap();
+        let tokens: Vec<_> = tokens
+            .iter()
+            .filter(|token| !token.token_type().ignore())
+            .collect();
+
+        assert_eq!(tokens[0].as_symbol(), Some(Symbol::Local));
+        assert_eq!(tokens[0].as_identifier(), None);
+        assert_eq!(tokens[1].as_identifier().as_deref(), Some("x"));
+        assert_eq!(tokens[2].as_symbol(), Some(Symbol::Equal));
+        assert_eq!(tokens[3].as_number().as_deref(), Some("0x10"));
+        assert_eq!(tokens[3].as_string(), None);
+        assert_eq!(tokens[4].as_symbol(), Some(Symbol::TwoDots));
+        assert_eq!(tokens[5].as_string().as_deref(), Some("long"));
+        assert_eq!(tokens[5].as_number(), None);
+        assert_eq!(tokens[6].as_symbol(), None);
+    }
+
+    #[test]
+    fn test_comment_text() {
+        let tokens = tokens("-- hello\n--[[ x ]] --[==[y]]==]--").unwrap();
+        let texts: Vec<_> = tokens
+            .iter()
+            .filter_map(|token| Some((token.comment_text()?, token.token_kind())))
+            .collect();
+
+        assert_eq!(
+            texts,
+            vec![
+                (Cow::from(" hello"), TokenKind::SingleLineComment),
+                (Cow::from(" x "), TokenKind::MultiLineComment),
+                (Cow::from("y]"), TokenKind::MultiLineComment),
+                (Cow::from(""), TokenKind::SingleLineComment),
+            ]
+        );
+        assert_eq!(tokens[1].comment_text(), None);
+    }
+
+    #[test]
+    fn test_long_bracket_level() {
+        assert_eq!(long_bracket_level(""), 0);
+        assert_eq!(long_bracket_level("a ] b ]=] c"), 0);
+        assert_eq!(long_bracket_level("a]]b"), 1);
+        assert_eq!(long_bracket_level("a]"), 1);
+        assert_eq!(long_bracket_level("a]]b]=]c"), 2);
+        assert_eq!(long_bracket_level("a]]b]="), 2);
+
+        for contents in &[
+            "a]]b",
+            "a]]b]=]c",
+            "ends with ]",
+            "]==]",
+            "\nline",
+            "\r\nline",
+        ] {
+            let token = Token::new(TokenType::long_string(Cow::from(*contents)));
+            let code = token.to_string();
+            let tokens = tokens(&code).unwrap();
+            assert_eq!(*tokens[0].token_type(), *token.token_type());
+        }
+    }
 }
@@ -0,0 +1,204 @@
+// Helpers used when displaying nodes, and by the transforms in `ast`.
+// Tokens are displayed with `{:#}` so that their trivia is kept.
+// Each display helper gives back something that writes straight to the formatter, so printing an Ast
+// doesn't build a new string for every node in it.
+#[cfg(feature = "roblox")]
+use crate::ast::{punctuated::Punctuated, types::TypeSpecifier};
+use crate::{
+    ast::{span::ContainedSpan, Expression, Value, Var},
+    tokenizer::{Token, TokenKind, TokenReference, TokenType},
+    visitors::{Visit, Visitor},
+};
+use atomic_refcell::AtomicRef;
+use std::{
+    borrow::Borrow,
+    fmt::{self, Display},
+    marker::PhantomData,
+};
+
+pub struct DisplayOption<T, O>(O, PhantomData<T>);
+
+impl<T: Display, O: Borrow<Option<T>>> Display for DisplayOption<T, O> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self.0.borrow() {
+            Some(x) => write!(formatter, "{:#}", x),
+            None => Ok(()),
+        }
+    }
+}
+
+pub fn display_option<T: Display, O: Borrow<Option<T>>>(option: O) -> DisplayOption<T, O> {
+    DisplayOption(option, PhantomData)
+}
+
+pub struct DisplayTuple<'b, A, B>(&'b (A, B));
+
+impl<A: Display, B: Display> Display for DisplayTuple<'_, A, B> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{:#}{:#}", (self.0).0, (self.0).1)
+    }
+}
+
+pub fn display_tuple<A: Display, B: Display>(tuple: &(A, B)) -> DisplayTuple<'_, A, B> {
+    DisplayTuple(tuple)
+}
+
+pub struct DisplayOptionalPunctuated<'a, 'b, T>(&'b (T, Option<TokenReference<'a>>));
+
+impl<T: Display> Display for DisplayOptionalPunctuated<'_, '_, T> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{:#}{}", (self.0).0, display_option(&(self.0).1))
+    }
+}
+
+pub fn display_optional_punctuated<'a, 'b, T: Display>(
+    pair: &'b (T, Option<TokenReference<'a>>),
+) -> DisplayOptionalPunctuated<'a, 'b, T> {
+    DisplayOptionalPunctuated(pair)
+}
+
+pub struct DisplayOptionalPunctuatedVec<'a, 'b, T>(&'b [(T, Option<TokenReference<'a>>)]);
+
+impl<T: Display> Display for DisplayOptionalPunctuatedVec<'_, '_, T> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for pair in self.0 {
+            display_optional_punctuated(pair).fmt(formatter)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub fn display_optional_punctuated_vec<'a, 'b, T: Display>(
+    vec: &'b [(T, Option<TokenReference<'a>>)],
+) -> DisplayOptionalPunctuatedVec<'a, 'b, T> {
+    DisplayOptionalPunctuatedVec(vec)
+}
+
+pub struct JoinVec<T, V>(V, PhantomData<T>);
+
+impl<T: Display, V: AsRef<[T]>> Display for JoinVec<T, V> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for item in self.0.as_ref() {
+            write!(formatter, "{:#}", item)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub fn join_vec<T: Display, V: AsRef<[T]>>(vec: V) -> JoinVec<T, V> {
+    JoinVec(vec, PhantomData)
+}
+
+#[cfg(feature = "roblox")]
+pub struct JoinTypeSpecifiers<'a, 'b, T> {
+    names: &'b Punctuated<'a, T>,
+    type_specifiers: &'b [Option<TypeSpecifier<'a>>],
+}
+
+#[cfg(feature = "roblox")]
+impl<T: Display> Display for JoinTypeSpecifiers<'_, '_, T> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for (index, pair) in self.names.pairs().enumerate() {
+            let type_specifier = self.type_specifiers.get(index).and_then(Option::as_ref);
+
+            write!(
+                formatter,
+                "{:#}{}{}",
+                pair.value(),
+                display_option(type_specifier),
+                display_option(pair.punctuation())
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+// Displays names (or parameters) with the type specifiers that go after each one, such as `a: number, b`
+#[cfg(feature = "roblox")]
+pub fn join_type_specifiers<'a, 'b, T: Display>(
+    names: &'b Punctuated<'a, T>,
+    type_specifiers: &'b [Option<TypeSpecifier<'a>>],
+) -> JoinTypeSpecifiers<'a, 'b, T> {
+    JoinTypeSpecifiers {
+        names,
+        type_specifiers,
+    }
+}
+
+pub fn whitespace<'a>(characters: &'static str) -> Token<'a> {
+    Token::new(TokenType::Whitespace {
+        characters: characters.into(),
+    })
+}
+
+// The characters of a whitespace token
+pub fn whitespace_characters<'a, 'b>(token: &'b Token<'a>) -> Option<AtomicRef<'b, str>> {
+    AtomicRef::filter_map(token.token_type(), |token_type| match token_type {
+        TokenType::Whitespace { characters } => Some(&**characters),
+        _ => None,
+    })
+}
+
+// The whitespace at the start of the line the token is on, if it's the first thing on the line
+pub fn indentation<'a>(token: &TokenReference<'a>) -> Option<Token<'a>> {
+    token
+        .leading_trivia()
+        .last()
+        .filter(|trivia| {
+            trivia.token_kind() == TokenKind::Whitespace && !trivia.to_string().contains('\n')
+        })
+        .cloned()
+}
+
+// Whether a token is a comment rather than code or whitespace
+pub fn is_comment(token: &Token) -> bool {
+    token.token_type().ignore() && token.token_kind() != TokenKind::Whitespace
+}
+
+#[derive(Default)]
+struct CommentFinder {
+    found: bool,
+}
+
+impl<'a> Visitor<'a> for CommentFinder {
+    fn visit_token(&mut self, token: &TokenReference<'a>) {
+        self.found |= token
+            .leading_trivia()
+            .chain(token.trailing_trivia())
+            .any(is_comment);
+    }
+
+    // The tokens of a contained span aren't visited, so they're checked here instead
+    fn visit_contained_span(&mut self, span: &ContainedSpan<'a>) {
+        let (start, end) = span.tokens();
+        self.visit_token(start);
+        self.visit_token(end);
+    }
+}
+
+// Whether there are any comments around the tokens of a node, or around a single token
+pub fn has_comments<'a>(node: &impl Visit<'a>) -> bool {
+    let mut finder = CommentFinder::default();
+    node.visit(&mut finder);
+    finder.found
+}
+
+// The name an expression is made of, the `x` in `x`
+pub fn as_name<'a, 'b>(expression: &'b Expression<'a>) -> Option<&'b TokenReference<'a>> {
+    match expression {
+        Expression::Value {
+            value,
+            binop: None,
+            #[cfg(feature = "roblox")]
+                as_assertion: None,
+        } => match &**value {
+            Value::Var(Var::Name(name)) => Some(name),
+            _ => None,
+        },
+
+        _ => None,
+    }
+}
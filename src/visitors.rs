@@ -136,6 +136,12 @@ pub trait VisitMut<'ast>: Sealed {
     fn visit_mut<V: VisitorMut<'ast>>(&mut self, visitor: &mut V);
 }
 
+impl<'ast, T: Visit<'ast>> Visit<'ast> for &T {
+    fn visit<V: Visitor<'ast>>(&self, visitor: &mut V) {
+        (**self).visit(visitor);
+    }
+}
+
 impl<'ast, T: Visit<'ast>> Visit<'ast> for Vec<T> {
     fn visit<V: Visitor<'ast>>(&self, visitor: &mut V) {
         for item in self {
@@ -206,53 +212,78 @@ impl<'ast, T: VisitMut<'ast>> VisitMut<'ast> for Box<T> {
     }
 }
 
-create_visitor!(ast: {
+// Every node with its own visit method, passed to the given macro after any other nodes given to it.
+// The visitors, folds and s-expressions are all made from this list, so none of them can miss a node.
+macro_rules! visited_nodes {
+    ($macro:ident! { $($extra:tt)* } $($rest:tt)*) => {
+        $macro! {
+            ast: {
+                $($extra)*
+                visit_assignment => Assignment,
+                visit_bin_op => BinOpRhs,
+                visit_block => Block,
+                visit_call => Call,
+                visit_do => Do,
+                visit_else_if => ElseIf,
+                visit_expression => Expression,
+                visit_field => Field,
+                visit_function_args => FunctionArgs,
+                visit_function_body => FunctionBody,
+                visit_function_call => FunctionCall,
+                visit_function_declaration => FunctionDeclaration,
+                visit_function_name => FunctionName,
+                visit_generic_for => GenericFor,
+                visit_if => If,
+                visit_index => Index,
+                visit_local_assignment => LocalAssignment,
+                visit_local_function => LocalFunction,
+                visit_last_stmt => LastStmt,
+                visit_method_call => MethodCall,
+                visit_numeric_for => NumericFor,
+                visit_parameter => Parameter,
+                visit_prefix => Prefix,
+                visit_return => Return,
+                visit_repeat => Repeat,
+                visit_stmt => Stmt,
+                visit_suffix => Suffix,
+                visit_table_constructor => TableConstructor,
+                visit_un_op => UnOp,
+                visit_value => Value,
+                visit_var => Var,
+                visit_var_expression => VarExpression,
+                visit_while => While,
+
+                // Types
+                #[cfg(feature = "roblox")] {
+                    visit_as_assertion => AsAssertion,
+                    visit_attribute => Attribute,
+                    visit_compound_assignment => CompoundAssignment,
+                    visit_compound_op => CompoundOp,
+                    visit_generic_declaration => GenericDeclaration,
+                    visit_type_argument => TypeArgument,
+                    visit_type_declaration => TypeDeclaration,
+                    visit_type_field => TypeField,
+                    visit_type_field_key => TypeFieldKey,
+                    visit_type_info => TypeInfo,
+                    visit_type_specifier => TypeSpecifier,
+                }
+
+                // Lua 5.2
+                #[cfg(feature = "lua52")] {
+                    visit_goto => Goto,
+                    visit_label => Label,
+                }
+            }
+            $($rest)*
+        }
+    };
+}
+
+pub(crate) use visited_nodes;
+
+visited_nodes!(create_visitor! {
     visit_anonymous_call => FunctionArgs,
-    visit_assignment => Assignment,
-    visit_bin_op => BinOpRhs,
-    visit_block => Block,
-    visit_call => Call,
     visit_contained_span => ContainedSpan,
-    visit_do => Do,
-    visit_else_if => ElseIf,
-    visit_expression => Expression,
-    visit_field => Field,
-    visit_function_args => FunctionArgs,
-    visit_function_body => FunctionBody,
-    visit_function_call => FunctionCall,
-    visit_function_declaration => FunctionDeclaration,
-    visit_function_name => FunctionName,
-    visit_generic_for => GenericFor,
-    visit_if => If,
-    visit_index => Index,
-    visit_local_assignment => LocalAssignment,
-    visit_local_function => LocalFunction,
-    visit_last_stmt => LastStmt,
-    visit_method_call => MethodCall,
-    visit_numeric_for => NumericFor,
-    visit_parameter => Parameter,
-    visit_prefix => Prefix,
-    visit_return => Return,
-    visit_repeat => Repeat,
-    visit_stmt => Stmt,
-    visit_suffix => Suffix,
-    visit_table_constructor => TableConstructor,
-    visit_un_op => UnOp,
-    visit_value => Value,
-    visit_var => Var,
-    visit_var_expression => VarExpression,
-    visit_while => While,
-
-    // Types
-    #[cfg(feature = "roblox")] {
-        visit_as_assertion => AsAssertion,
-        visit_generic_declaration => GenericDeclaration,
-        visit_type_declaration => TypeDeclaration,
-        visit_type_field => TypeField,
-        visit_type_field_key => TypeFieldKey,
-        visit_type_info => TypeInfo,
-        visit_type_specifier => TypeSpecifier,
-    }
 }, token: {
     visit_eof,
     visit_identifier,
@@ -0,0 +1,108 @@
+// Read-only analysis of a parsed tree should borrow everything it looks at
+use full_moon::{ast, node::Node, parse, tokenizer::TokenReference, visitors::Visitor};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+struct CountingAllocator;
+
+thread_local! {
+    // Counted for each thread, so that the tests running alongside each other don't count each other's allocations
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // Allocations made while the thread is being torn down aren't counted
+        let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[derive(Default)]
+struct Analysis {
+    identifiers: usize,
+    local_names: usize,
+    binops: usize,
+}
+
+impl<'ast> Visitor<'ast> for Analysis {
+    fn visit_identifier(&mut self, _token: &TokenReference<'ast>) {
+        self.identifiers += 1;
+    }
+
+    fn visit_local_assignment(&mut self, local_assignment: &ast::LocalAssignment<'ast>) {
+        self.local_names += local_assignment.name_list().len();
+    }
+
+    fn visit_expression(&mut self, expression: &ast::Expression<'ast>) {
+        if let ast::Expression::Value {
+            binop: Some(binop), ..
+        } = expression
+        {
+            if let ast::BinOp::Plus(_) = binop.bin_op() {
+                self.binops += 1;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_analysis_does_not_allocate() {
+    let ast = parse(
+        "local x, y = 1, 2
+        local function add(a, b)
+            return a + b + x
+        end
+        print(add(y, { 1, 2, x = 3 }))",
+    )
+    .unwrap();
+
+    let before = allocations();
+    let mut analysis = Analysis::default();
+    analysis.visit_ast(&ast);
+    let range = ast.nodes().range();
+    let after = allocations();
+
+    assert_eq!(after - before, 0);
+    assert!(range.is_some());
+    assert!(analysis.identifiers > 0);
+    assert!(analysis.local_names > 0);
+    assert!(analysis.binops > 0);
+}
+
+#[test]
+fn test_print_does_not_allocate_per_node() {
+    let code = "local x, y = 1, 2
+        local function add(a, b)
+            return a + b + x -- add them
+        end
+        print(add(y, { 1, 2, x = 3 }), (\"hello\"):upper(), not x)
+        ";
+    let ast = parse(code).unwrap();
+    let tokens = ast.iter_tokens().count();
+
+    let before = allocations();
+    let printed = full_moon::print(&ast);
+    let after = allocations();
+
+    assert_eq!(printed, code);
+    // Only growing the output string should allocate, which happens far less than once per token
+    assert!(
+        after - before < tokens / 4,
+        "{} allocations",
+        after - before
+    );
+}
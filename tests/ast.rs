@@ -0,0 +1,2736 @@
+use full_moon::{
+    ast::{
+        self,
+        branches::BranchKind,
+        builders::IfBuilder,
+        code_loading::{Loader, Severity},
+        comment_style::CommentStyle,
+        constant::Constant,
+        enclosing::Depth,
+        field_separator_kind,
+        fold::{NodeRef, VisitEvent},
+        folding::FoldingKind,
+        Arity, BinOp, Expression, Field, FieldSeparator, LastStmt, Value, ValueCount,
+    },
+    parse, parse_value,
+    tokenizer::{self, Position, Token, TokenReference, TokenType},
+    visitors::VisitorMut,
+};
+
+fn return_expression<'a>(ast: &'a full_moon::ast::Ast<'a>) -> &'a Expression<'a> {
+    match ast.nodes().last_stmts() {
+        Some(LastStmt::Return(r#return)) => r#return.returns().iter().next().unwrap(),
+        _ => panic!("expected a return statement"),
+    }
+}
+
+#[test]
+fn test_expression_binops() {
+    let ast = parse("return a + b * c - d").unwrap();
+    let binops = return_expression(&ast)
+        .binops()
+        .map(|binop| match binop {
+            BinOp::Plus(token) | BinOp::Star(token) | BinOp::Minus(token) => token.to_string(),
+            _ => unreachable!(),
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(binops, vec!["+", "*", "-"]);
+}
+
+#[test]
+fn test_expression_binops_nested() {
+    let ast = parse("return -(a + (b .. c)) == d").unwrap();
+    assert_eq!(return_expression(&ast).binops().count(), 3);
+
+    let ast = parse("return a").unwrap();
+    assert_eq!(return_expression(&ast).binops().count(), 0);
+}
+
+#[test]
+fn test_function_name_method() {
+    let ast = parse("function a.b:c() end function a.b.c() end").unwrap();
+    let names = ast
+        .nodes()
+        .iter_stmts()
+        .map(|stmt| match stmt {
+            ast::Stmt::FunctionDeclaration(declaration) => declaration.name(),
+            _ => unreachable!(),
+        })
+        .collect::<Vec<_>>();
+
+    let method = names[0];
+    assert_eq!(
+        method
+            .names()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        vec!["a", "b"]
+    );
+    assert_eq!(method.method_colon().unwrap().to_string(), ":");
+    assert_eq!(method.method_name().unwrap().to_string(), "c");
+
+    let field = names[1];
+    assert_eq!(field.names().len(), 3);
+    assert!(field.method_colon().is_none());
+    assert!(field.method_name().is_none());
+}
+
+#[test]
+fn test_return_is_tail_call() {
+    fn is_tail_call(code: &str) -> bool {
+        let ast = parse(code).unwrap();
+        match ast.nodes().last_stmts() {
+            Some(LastStmt::Return(r#return)) => r#return.is_tail_call(),
+            _ => panic!("expected a return statement"),
+        }
+    }
+
+    assert!(is_tail_call("return f()"));
+    assert!(is_tail_call("return f(x)"));
+    assert!(is_tail_call("return a.b:c(x)"));
+    assert!(!is_tail_call("return f(), 1"));
+    assert!(!is_tail_call("return f(x) + 1"));
+    assert!(!is_tail_call("return (f(x))"));
+    assert!(!is_tail_call("return x"));
+    assert!(!is_tail_call("return"));
+}
+
+#[test]
+fn test_defaults() {
+    use full_moon::{
+        ast::punctuated::{Pair, Punctuated},
+        node::Node,
+        tokenizer::{Symbol, TokenReference},
+    };
+
+    let mut punctuated: Punctuated<Expression> = Punctuated::default();
+    assert_eq!(punctuated.len(), 0);
+
+    let ast = parse("return 1, 2").unwrap();
+    let returns = match ast.nodes().last_stmts() {
+        Some(LastStmt::Return(r#return)) => r#return.returns().clone(),
+        _ => unreachable!(),
+    };
+
+    punctuated.extend(returns.into_pairs());
+    let last = punctuated.pop().unwrap().into_value();
+    punctuated.push(Pair::new(last, Some(TokenReference::symbol(Symbol::Comma))));
+    punctuated.push(Pair::new(return_expression(&ast).clone(), None));
+    assert_eq!(punctuated.len(), 3);
+    assert!(punctuated.pairs().last().unwrap().punctuation().is_none());
+
+    let block = ast::Block::default();
+    assert_eq!(block.iter_stmts().count(), 0);
+    assert!(block.last_stmts().is_none());
+
+    let table = ast::TableConstructor::default();
+    let (start, end) = table.braces().tokens();
+    assert_eq!(start.to_string(), "{");
+    assert_eq!(end.to_string(), "}");
+    assert_eq!(table.iter_fields().count(), 0);
+    assert!(table.similar(&ast::TableConstructor::default()));
+}
+
+#[test]
+fn test_to_sexpr() {
+    let ast = parse("if a then b() end").unwrap();
+    assert_eq!(
+        ast.to_sexpr(),
+        r#"(Block
+  (Stmt
+    (If "if"
+      (Expression
+        (Value
+          (Var "a")))
+      "then"
+      (Block
+        (Stmt
+          (FunctionCall
+            (Prefix "b")
+            (Suffix
+              (Call
+                (FunctionArgs
+                  (ContainedSpan "(" ")")))))))
+      "end")))"#
+    );
+}
+
+#[test]
+fn test_requires() {
+    let ast = parse(
+        r#"local a = require("a")
+local b = require "foo.b"
+local c = require(script.Parent.c)
+local d = require("d").value
+local e = import("e")
+local f = something.require("f")
+"#,
+    )
+    .unwrap();
+
+    let requires = ast.requires();
+    assert_eq!(requires.len(), 4);
+
+    assert_eq!(requires[0].module_name().unwrap(), "a");
+    assert_eq!(requires[0].loader().to_string(), "require");
+    assert_eq!(requires[0].loader().start_position().line(), 1);
+    assert_eq!(
+        requires[0].module().unwrap().start_position().character(),
+        19
+    );
+
+    assert_eq!(requires[1].module_name().unwrap(), "foo.b");
+    assert_eq!(requires[1].module().unwrap().start_position().line(), 2);
+
+    assert!(requires[2].is_dynamic());
+    assert!(requires[2].module().is_none());
+    assert_eq!(requires[2].loader().start_position().line(), 3);
+
+    assert_eq!(requires[3].module_name().unwrap(), "d");
+
+    let requires = ast.requires_with(&["require", "import"]);
+    assert_eq!(
+        requires
+            .iter()
+            .filter_map(|require| require.module_name())
+            .collect::<Vec<_>>(),
+        vec!["a", "foo.b", "d", "e"]
+    );
+}
+
+#[test]
+fn test_wrap_in_do() {
+    let ast = parse("local a = 1 -- one\nlocal b = 2").unwrap();
+    let block = ast.nodes().clone();
+    let do_block = block.clone().wrap_in_do();
+
+    assert_eq!(
+        do_block.to_string(),
+        "do\nlocal a = 1 -- one\nlocal b = 2\nend"
+    );
+    assert_eq!(do_block.block().iter_stmts().count(), 2);
+    assert_eq!(do_block.unwrap(), block);
+}
+
+#[test]
+fn test_print_nodes() {
+    let source = "-- comment\nlocal a = { 1, 2 }\n\nreturn a -- trailing\n";
+    let ast = parse(source).unwrap();
+    assert_eq!(full_moon::print(&ast), source);
+    assert_eq!(format!("{}{:#}", ast.nodes(), ast.eof()), source);
+}
+
+#[test]
+fn test_mixed_indentation() {
+    let ast = parse("local function f()\n\t  call()\n\tcall()\n    call()\n \t\nend").unwrap();
+    let lines = ast
+        .mixed_indentation()
+        .iter()
+        .map(|position| (position.line(), position.character()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(lines, vec![(2, 1)]);
+}
+
+#[test]
+fn test_references_repeat_until() {
+    let ast = parse("repeat local x = f() until x").unwrap();
+    let references = ast.references();
+    let x = references.last().unwrap();
+
+    assert_eq!(x.name().to_string(), "x");
+    let local = x
+        .local()
+        .expect("x in the condition should be the body's local");
+    assert_eq!(local.start_position().bytes(), 13);
+}
+
+#[test]
+fn test_references_scopes() {
+    let ast = parse(
+        "local x = x
+        for i = i, 10 do print(i) end
+        do local y end
+        print(y)
+        function t:m(a) return self, a end",
+    )
+    .unwrap();
+
+    let resolved = ast
+        .references()
+        .iter()
+        .map(|reference| (reference.name().to_string(), !reference.is_global()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        resolved,
+        vec![
+            ("x".to_owned(), false),
+            ("i".to_owned(), false),
+            ("print".to_owned(), false),
+            ("i".to_owned(), true),
+            ("print".to_owned(), false),
+            ("y".to_owned(), false),
+            ("t".to_owned(), false),
+            ("self".to_owned(), true),
+            ("a".to_owned(), true),
+        ]
+    );
+}
+
+#[test]
+fn test_assignments_to() {
+    use ast::assignments::WriteKind;
+
+    let ast = parse(
+        "x = 1\nlocal y, x = 2, 3\nt.x = 4\nx2 = 5\nfor x in pairs(t) do end\n\
+         function x() end\nfunction t.x() end\nlocal function x() end",
+    )
+    .unwrap();
+    let writes = ast
+        .assignments_to("x")
+        .iter()
+        .map(|write| (write.kind(), write.name().start_position().bytes()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        writes,
+        vec![
+            (WriteKind::Assignment, 0),
+            (WriteKind::LocalAssignment, 15),
+            (WriteKind::GenericFor, 43),
+            (WriteKind::FunctionDeclaration, 73),
+            (WriteKind::LocalFunction, 115),
+        ]
+    );
+}
+
+fn assert_eval(code: &str, expected: Option<Constant>) {
+    let code = format!("return {}", code);
+    let ast = parse(&code).unwrap();
+    assert_eq!(
+        return_expression(&ast).eval_constant(),
+        expected,
+        "{}",
+        code
+    );
+}
+
+#[test]
+fn test_eval_constant_and_or() {
+    assert_eval("nil and 1", Some(Constant::Nil));
+    assert_eval("1 and 2", Some(Constant::Number(2.0)));
+    assert_eval("nil or 3", Some(Constant::Number(3.0)));
+    assert_eval("false or nil", Some(Constant::Nil));
+    assert_eval("1 or call()", Some(Constant::Number(1.0)));
+    assert_eval("true and call()", None);
+}
+
+#[test]
+fn test_eval_constant() {
+    assert_eval("1 - 2 - 3", Some(Constant::Number(-4.0)));
+    assert_eval("-2 ^ 2", Some(Constant::Number(-4.0)));
+    assert_eval("2 ^ 3 ^ 2", Some(Constant::Number(512.0)));
+    assert_eval("not nil == true", Some(Constant::Boolean(true)));
+    assert_eval("0x10 % 3", Some(Constant::Number(1.0)));
+    assert_eval("#'abc' + '1'", Some(Constant::Number(4.0)));
+    assert_eval(
+        "'a\\tb' .. 1 .. 0.5",
+        Some(Constant::String("a\tb10.5".into())),
+    );
+    assert_eval("0.1 + 0.2 .. ''", Some(Constant::String("0.3".into())));
+    assert_eval(
+        "2 ^ 100 .. ''",
+        Some(Constant::String("1.2676506002282e+30".into())),
+    );
+    assert_eval("1 < 2 and 'yes'", Some(Constant::String("yes".into())));
+    assert_eval("1 < 'x'", None);
+    assert_eval("x + 1", None);
+}
+
+fn value_count(code: &str) -> ValueCount {
+    let code = format!("return {}", code);
+    let ast = parse(&code).unwrap();
+    match ast.nodes().last_stmts() {
+        Some(LastStmt::Return(r#return)) => r#return.returns().value_count(),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn test_value_count() {
+    assert_eq!(value_count("1, 2"), ValueCount::Exact(2));
+    assert_eq!(value_count("1, f()"), ValueCount::AtLeast(1));
+    assert_eq!(value_count("..."), ValueCount::AtLeast(0));
+    assert_eq!(value_count("f(), 1"), ValueCount::Exact(2));
+    assert_eq!(value_count("1, (f())"), ValueCount::Exact(2));
+    assert_eq!(value_count("1, f() + 1"), ValueCount::Exact(2));
+    assert_eq!(value_count(""), ValueCount::Exact(0));
+}
+
+#[test]
+fn test_truncates_multi_return() {
+    let ast = parse("return (f()), (1), f(), ((...)), (f()) + 1, (a.b:c())").unwrap();
+    let truncates = match ast.nodes().last_stmts() {
+        Some(LastStmt::Return(r#return)) => r#return
+            .returns()
+            .iter()
+            .map(Expression::truncates_multi_return)
+            .collect::<Vec<_>>(),
+        _ => unreachable!(),
+    };
+
+    assert_eq!(truncates, vec![true, false, false, true, false, true]);
+}
+
+#[test]
+fn test_count_mismatches() {
+    let ast = parse("a, b = 1\na, b = f()\nlocal c = 1, 2\nlocal d, e\nx = 1, 2, f()\ny, z = ...")
+        .unwrap();
+    let mismatches: Vec<_> = ast
+        .count_mismatches()
+        .iter()
+        .map(|mismatch| {
+            (
+                mismatch.stmt().to_string().trim().to_owned(),
+                mismatch.name_count(),
+                mismatch.value_count(),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        mismatches,
+        vec![
+            ("a, b = 1".to_owned(), 2, ValueCount::Exact(1)),
+            ("local c = 1, 2".to_owned(), 1, ValueCount::Exact(2)),
+            ("x = 1, 2, f()".to_owned(), 1, ValueCount::AtLeast(2)),
+        ]
+    );
+}
+
+#[test]
+fn test_loops() {
+    use full_moon::ast::loops::Loop;
+
+    let ast = parse(
+        "for i = 1, 10 do\n\twhile x do end\nend\nfor k, v in pairs(t) do end\nrepeat\n\tbreak\nuntil done",
+    )
+    .unwrap();
+
+    let loops: Vec<_> = ast
+        .loops()
+        .iter()
+        .map(|r#loop| {
+            let kind = match r#loop {
+                Loop::NumericFor(_) => "numeric for",
+                Loop::GenericFor(_) => "generic for",
+                Loop::While(_) => "while",
+                Loop::Repeat(_) => "repeat",
+            };
+
+            (
+                kind,
+                r#loop.body().iter_stmts().count() + r#loop.body().last_stmts().iter().count(),
+                r#loop
+                    .condition()
+                    .map(|condition| condition.to_string().trim().to_owned()),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        loops,
+        vec![
+            ("numeric for", 1, None),
+            ("while", 0, Some("x".to_owned())),
+            ("generic for", 0, None),
+            ("repeat", 1, Some("done".to_owned())),
+        ]
+    );
+}
+
+#[test]
+fn test_infinite_loops() {
+    let infinite = |code| {
+        parse(code)
+            .unwrap()
+            .infinite_loops()
+            .iter()
+            .map(|r#loop| r#loop.condition().unwrap().to_string().trim().to_owned())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(infinite("while true do end"), vec!["true"]);
+    assert_eq!(infinite("repeat until false"), vec!["false"]);
+    assert_eq!(infinite("while 1 do\n\tcall()\nend"), vec!["1"]);
+    assert!(infinite("while true do break end").is_empty());
+    assert!(infinite("repeat return until false").is_empty());
+    assert!(infinite("while true do\n\tif x then break end\nend").is_empty());
+    assert!(infinite("while true do\n\tfor i = 1, 10 do return end\nend").is_empty());
+    assert!(infinite("while x do end\nrepeat until true\nfor i = 1, 10 do end").is_empty());
+
+    // The break only leaves the inner loop, and the return only leaves the function
+    assert_eq!(
+        infinite("while true do\n\twhile x do break end\nend"),
+        vec!["true"]
+    );
+    assert_eq!(
+        infinite("while true do\n\tlocal function f() return end\nend"),
+        vec!["true"]
+    );
+}
+
+#[test]
+fn test_return_mut() {
+    struct AppendValue<'a>(Expression<'a>);
+
+    impl<'ast> VisitorMut<'ast> for AppendValue<'ast> {
+        fn visit_return(&mut self, r#return: &mut ast::Return<'ast>) {
+            let mut returns = r#return.returns().clone();
+            returns.extend(vec![self.0.clone()]);
+            r#return.set_returns(returns);
+        }
+    }
+
+    let nil = parse("return nil").unwrap();
+    let nil = return_expression(&nil);
+
+    let mut ast = parse("return x").unwrap();
+    AppendValue(nil.clone()).visit_ast(&mut ast);
+    assert_eq!(full_moon::print(&ast), "return x, nil");
+
+    // There's nothing after the `return` to separate it from the new value
+    let mut ast = parse("return").unwrap();
+    AppendValue(nil.clone()).visit_ast(&mut ast);
+    assert_eq!(full_moon::print(&ast), "return nil");
+
+    let mut ast = parse("return -- nothing").unwrap();
+    AppendValue(nil.clone()).visit_ast(&mut ast);
+    assert_eq!(full_moon::print(&ast), "return -- nothing\nnil");
+}
+
+#[test]
+fn test_use_method_calls() {
+    let rewrite = |code| {
+        let mut ast = parse(code).unwrap();
+        ast.use_method_calls();
+        full_moon::print(&ast)
+    };
+
+    assert_eq!(rewrite("t.insert(t, 1)"), "t:insert(1)");
+    assert_eq!(rewrite("local x = obj.get(obj)"), "local x = obj:get()");
+    assert_eq!(rewrite("obj.a(obj, obj.b(obj))"), "obj:a(obj:b())");
+    assert_eq!(rewrite("obj.f(obj)(1)"), "obj:f()(1)");
+
+    // The first argument isn't the receiver, or the receiver isn't a plain name
+    assert_eq!(rewrite("t.insert(u, 1)"), "t.insert(u, 1)");
+    assert_eq!(rewrite("t.insert(t.x, 1)"), "t.insert(t.x, 1)");
+    assert_eq!(rewrite("a.b.c(a.b)"), "a.b.c(a.b)");
+    assert_eq!(rewrite("f().g(f())"), "f().g(f())");
+    assert_eq!(rewrite("t.insert{t}"), "t.insert{t}");
+    assert_eq!(rewrite("t:insert(t)"), "t:insert(t)");
+
+    // Comments around the receiver would be lost
+    assert_eq!(
+        rewrite("t.insert(t --[[list]], 1)"),
+        "t.insert(t --[[list]], 1)"
+    );
+}
+
+#[test]
+fn test_disabled_ranges() {
+    let disabled = |code: &'static str, marker| {
+        parse(code)
+            .unwrap()
+            .disabled_ranges(marker)
+            .iter()
+            .map(|range| &code[range.start().bytes()..range.end().bytes()])
+            .collect::<Vec<_>>()
+    };
+
+    let code = "local a = 1\n-- fmt: off\nlocal b   =   {\n\t1,2,\n}\n-- fmt: on\nlocal c = 3";
+    assert_eq!(
+        disabled(code, "fmt"),
+        vec!["-- fmt: off\nlocal b   =   {\n\t1,2,\n}\n-- fmt: on"]
+    );
+    assert!(disabled(code, "stylua").is_empty());
+
+    // Turning it off twice does nothing, and it's off until the end of the file without a comment turning it on
+    assert_eq!(
+        disabled(
+            "a()\n--[[stylua:off]] b() -- stylua: off\nc() --stylua: on\nd()\n-- stylua: off\ne()",
+            "stylua"
+        ),
+        vec![
+            "--[[stylua:off]] b() -- stylua: off\nc() --stylua: on",
+            "-- stylua: off\ne()",
+        ]
+    );
+
+    // Only the whole marker counts
+    assert!(disabled("-- not stylua: off\na()\n-- stylua: offline", "stylua").is_empty());
+}
+
+#[test]
+fn test_hoist_locals() {
+    let hoist = |code| {
+        let mut ast = parse(code).unwrap();
+        ast.hoist_locals();
+        let code = full_moon::print(&ast);
+        assert!(parse(&code).is_ok(), "hoisted code doesn't parse: {}", code);
+        code
+    };
+
+    assert_eq!(
+        hoist("local a = 1\ncall(a)\nlocal x\nx = 2\n"),
+        "local a = 1\nlocal x\ncall(a)\nx = 2\n"
+    );
+
+    assert_eq!(
+        hoist("function f()\n\tcall()\n\t-- the values\n\tlocal x, y = 1, 2 -- two\n\treturn x + y\nend"),
+        "function f()\n\tlocal x, y\n\tcall()\n\t-- the values\n\tx, y = 1, 2 -- two\n\treturn x + y\nend"
+    );
+
+    // A declaration at the end of a line is still put on its own line
+    assert_eq!(hoist("call() local x"), "local x\ncall() ");
+    assert_eq!(
+        hoist("do call() local x = 1; end"),
+        "do local x\ncall() x = 1; end"
+    );
+
+    // Moving these would change what the names refer to
+    for code in &[
+        "call(x)\nlocal x = 1",
+        "call()\nlocal x = x",
+        "local x = 1\ncall()\nlocal x = 2",
+        "local f = function() return x end\ncall()\nlocal x = 1",
+        "call()\nlocal x, x = 1, 2",
+    ] {
+        assert_eq!(&hoist(code), code);
+    }
+
+    #[cfg(feature = "roblox")]
+    assert_eq!(
+        hoist("call()\nlocal x: number = 1\nlocal y = 2"),
+        "local y\ncall()\nlocal x: number = 1\ny = 2"
+    );
+}
+
+#[test]
+fn test_identifier() {
+    let ast = parse("local foo = bar").unwrap();
+    let local_assignment = match ast.nodes().iter_stmts().next() {
+        Some(ast::Stmt::LocalAssignment(local_assignment)) => local_assignment,
+        _ => unreachable!(),
+    };
+
+    let name = local_assignment.name_list().iter().next().unwrap();
+    assert!(matches!(
+        name.as_identifier(),
+        Some(std::borrow::Cow::Borrowed("foo"))
+    ));
+    assert_eq!(local_assignment.local_token().as_identifier(), None);
+    assert_eq!(
+        local_assignment.equal_token().unwrap().as_identifier(),
+        None
+    );
+
+    // The trivia around the name isn't included
+    assert_eq!(name.to_string(), "foo");
+    assert!(name.trailing_trivia().next().is_some());
+}
+
+#[test]
+fn test_unified_diff() {
+    struct Rename;
+
+    impl<'ast> VisitorMut<'ast> for Rename {
+        fn visit_identifier(&mut self, token: &mut TokenReference<'ast>) {
+            if token.as_identifier().as_deref() == Some("count") {
+                *token = TokenReference::new(
+                    token.leading_trivia().cloned().collect(),
+                    Token::new(TokenType::Identifier {
+                        identifier: "total".into(),
+                    }),
+                    token.trailing_trivia().cloned().collect(),
+                );
+            }
+        }
+    }
+
+    let code = "local a = 1\nlocal b = 2\nlocal c = 3\nlocal d = 4\nlocal count = 5\nlocal e = 6\nlocal f = 7\nlocal g = 8\nlocal h = 9\n";
+    let mut ast = parse(code).unwrap();
+    assert_eq!(full_moon::unified_diff(code, &ast), "");
+
+    Rename.visit_ast(&mut ast);
+    assert_eq!(
+        full_moon::unified_diff(code, &ast),
+        "--- original\n+++ transformed\n@@ -2,7 +2,7 @@\n local b = 2\n local c = 3\n local d = 4\n-local count = 5\n+local total = 5\n local e = 6\n local f = 7\n local g = 8\n"
+    );
+
+    // Changes near each other are put in the same hunk, while ones further apart get their own
+    let code = "count()\n\n\n\n\n\ncount()\n\n\n\n\n\n\n\ncount()";
+    let mut ast = parse(code).unwrap();
+    Rename.visit_ast(&mut ast);
+    assert_eq!(
+        full_moon::unified_diff(code, &ast),
+        "--- original\n+++ transformed\n@@ -1,10 +1,10 @@\n-count()\n+total()\n \n \n \n \n \n-count()\n+total()\n \n \n \n@@ -12,4 +12,4 @@\n \n \n \n-count()\n\\ No newline at end of file\n+total()\n\\ No newline at end of file\n"
+    );
+}
+
+#[test]
+fn test_unified_diff_large() {
+    // Diffing takes memory for every line rather than for every pair of old and new lines,
+    // which would be hundreds of millions here as the changes at both ends leave nothing to skip
+    let mut lines: Vec<_> = (0..20_000)
+        .map(|index| format!("x = {}\n", index))
+        .collect();
+    let original = lines.concat();
+    lines[0] = "x = -1\n".to_owned();
+    lines[19_999] = "x = -2\n".to_owned();
+
+    let code = lines.concat();
+    let ast = parse(&code).unwrap();
+    let diff = full_moon::unified_diff(&original, &ast);
+    let hunks: Vec<_> = diff.lines().filter(|line| line.starts_with("@@")).collect();
+    assert_eq!(hunks, vec!["@@ -1,4 +1,4 @@", "@@ -19997,4 +19997,4 @@"]);
+    assert!(diff.contains("-x = 0\n+x = -1\n"));
+    assert!(diff.contains("-x = 19999\n+x = -2\n"));
+}
+
+#[test]
+fn test_duplicate_parameters() {
+    let ast = parse(
+        "call(function(a, a) end)\nfunction t:f(self, b, c, b, b) end\nlocal function g(a, b, ...)\n\treturn function(b, a) end\nend",
+    )
+    .unwrap();
+
+    let duplicates: Vec<_> = ast
+        .duplicate_parameters()
+        .iter()
+        .map(|name| (name.to_string(), name.start_position().bytes()))
+        .collect();
+
+    assert_eq!(
+        duplicates,
+        vec![
+            ("a".to_owned(), 17),
+            ("b".to_owned(), 50),
+            ("b".to_owned(), 53),
+        ]
+    );
+}
+
+#[test]
+fn test_number_spelling() {
+    let ast = parse("return 0xFF, 1e3, 3.50, 'x', -1").unwrap();
+    let values: Vec<_> = match ast.nodes().last_stmts() {
+        Some(LastStmt::Return(r#return)) => r#return
+            .returns()
+            .iter()
+            .map(|expression| match expression {
+                Expression::Value { value, .. } => (
+                    value.spelling().map(|spelling| spelling.into_owned()),
+                    value.parse_number(),
+                ),
+                Expression::UnaryOperator { .. } => (None, None),
+                _ => unreachable!(),
+            })
+            .collect(),
+        _ => unreachable!(),
+    };
+
+    assert_eq!(
+        values,
+        vec![
+            (Some("0xFF".to_owned()), Some(255.0)),
+            (Some("1e3".to_owned()), Some(1000.0)),
+            (Some("3.50".to_owned()), Some(3.5)),
+            (None, None),
+            (None, None),
+        ]
+    );
+
+    // The spelling is kept when printing
+    assert_eq!(full_moon::print(&ast), "return 0xFF, 1e3, 3.50, 'x', -1");
+}
+
+#[test]
+fn test_split_assignments() {
+    let split = |code| {
+        let mut ast = parse(code).unwrap();
+        ast.split_assignments();
+        full_moon::print(&ast)
+    };
+
+    assert_eq!(split("a, b = 1, 2"), "a = 1\nb = 2");
+    assert_eq!(
+        split("a, b, c = nil, 'x', -1 -- values\n"),
+        "a = nil\nb = 'x'\nc = -1 -- values\n"
+    );
+    assert_eq!(
+        split("do\n\tx, y = y0, 2;\nend"),
+        "do\n\tx = y0\n\ty = 2;\nend"
+    );
+
+    // Splitting would change the order things are evaluated in, or what's assigned
+    assert_eq!(split("a, b = f()"), "a, b = f()");
+    assert_eq!(split("a, b = f(), 2"), "a, b = f(), 2");
+    assert_eq!(split("a, b = ..."), "a, b = ...");
+    assert_eq!(split("a, b = 1"), "a, b = 1");
+    assert_eq!(split("a, b = b, a"), "a, b = b, a");
+    assert_eq!(split("a, a = 1, 2"), "a, a = 1, 2");
+    assert_eq!(split("t.x, y = 1, 2"), "t.x, y = 1, 2");
+    assert_eq!(split("a, b = {}, 2"), "a, b = {}, 2");
+
+    // Comments in between the variables or values would be lost
+    assert_eq!(split("a, b = 1, -- one\n2"), "a, b = 1, -- one\n2");
+    assert_eq!(split("a --[[x]], b = 1, 2"), "a --[[x]], b = 1, 2");
+    assert_eq!(split("-- a and b\na, b = 1, 2"), "-- a and b\na = 1\nb = 2");
+}
+
+#[test]
+fn test_identifiers() {
+    let ast = parse("local x = y.z").unwrap();
+    let identifiers = ast.identifiers();
+    assert_eq!(identifiers.len(), 3);
+
+    let names: Vec<_> = identifiers
+        .iter()
+        .map(|identifier| (identifier.to_string(), identifier.start_position().bytes()))
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            ("x".to_owned(), 6),
+            ("y".to_owned(), 10),
+            ("z".to_owned(), 12),
+        ]
+    );
+
+    let ast = parse("t = {a = 1, [b] = c}\nt:m(...)\nfor i, v in pairs(t) do end").unwrap();
+    let names: Vec<_> = ast
+        .identifiers()
+        .iter()
+        .map(|identifier| identifier.to_string())
+        .collect();
+    assert_eq!(
+        names,
+        vec!["t", "a", "b", "c", "t", "m", "i", "v", "pairs", "t"]
+    );
+}
+
+#[test]
+fn test_convert_comments() {
+    let convert = |code, style| {
+        let mut ast = parse(code).unwrap();
+        ast.convert_comments(style);
+        let printed = full_moon::print(&ast);
+        assert!(parse(&printed).is_ok(), "couldn't parse {:?}", printed);
+        printed
+    };
+
+    assert_eq!(
+        convert("-- a\n-- b\nlocal x = 1", CommentStyle::Block),
+        "--[[ a\n b]]\nlocal x = 1"
+    );
+    assert_eq!(
+        convert("do\n\t-- a\n\t-- b\n\n\t-- c\nend", CommentStyle::Block),
+        "do\n\t--[[ a\n\t b]]\n\n\t--[[ c]]\nend"
+    );
+    assert_eq!(
+        convert("x = 1 -- a\n-- b\ny = 2", CommentStyle::Block),
+        "x = 1 --[[ a]]\n--[[ b]]\ny = 2"
+    );
+
+    // The closing brackets are picked so that the comments can't close the block early
+    assert_eq!(
+        convert("-- a]]\n-- b]", CommentStyle::Block),
+        "--[=[ a]]\n b]]=]"
+    );
+
+    assert_eq!(
+        convert("--[[ a ]]\nx = 1 --[=[ b]] ]=]", CommentStyle::Line),
+        "-- a \nx = 1 -- b]] "
+    );
+
+    // Converting these would comment out code or start a block comment
+    assert_eq!(
+        convert("x = --[[ a ]] 1\n--[[ b\n c]]", CommentStyle::Line),
+        "x = --[[ a ]] 1\n--[[ b\n c]]"
+    );
+    assert_eq!(
+        convert("--[=[[[x]=]\n", CommentStyle::Line),
+        "--[=[[[x]=]\n"
+    );
+
+    // Positions are updated after the comments change
+    let mut ast = parse("-- a\n-- b\nlocal x = 1").unwrap();
+    ast.convert_comments(CommentStyle::Block);
+    assert_eq!(ast.eof().start_position().bytes(), 23);
+}
+
+#[test]
+fn test_shadowed_locals() {
+    let shadows = |code| {
+        parse(code)
+            .unwrap()
+            .shadowed_locals()
+            .iter()
+            .map(|shadow| {
+                (
+                    shadow.local().to_string(),
+                    shadow.local().start_position().bytes(),
+                    shadow.shadowed().start_position().bytes(),
+                    shadow.is_intentional(),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        shadows("local x = 1\ndo\n\tlocal x = 2\nend"),
+        vec![("x".to_owned(), 22, 6, false)]
+    );
+    assert_eq!(
+        shadows("local a, _ = 1\nlocal function f(a, b) for _, b in pairs(a) do end end"),
+        vec![
+            ("a".to_owned(), 32, 6, false),
+            ("_".to_owned(), 42, 9, true),
+            ("b".to_owned(), 45, 35, false),
+        ]
+    );
+    assert_eq!(
+        shadows("local f\nlocal x\nfunction g() local function f() end end"),
+        vec![("f".to_owned(), 44, 6, false)]
+    );
+
+    // Redeclaring a local in the same block, or declaring one after its scope ended, isn't shadowing
+    assert_eq!(shadows("local x = 1 local x = x"), vec![]);
+    assert_eq!(shadows("do local x end local x"), vec![]);
+    assert_eq!(shadows("for i = 1, 2 do local i = i end"), vec![]);
+}
+
+#[test]
+fn test_branch_conditions() {
+    let conditions = |code| {
+        parse(code)
+            .unwrap()
+            .branch_conditions()
+            .iter()
+            .map(|condition| {
+                (
+                    condition.kind(),
+                    condition.condition().to_string().trim().to_owned(),
+                    condition.start_position().unwrap().bytes(),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        conditions("if a then\nelseif b == 1 then\nend\nwhile not c do end"),
+        vec![
+            (BranchKind::If, "a".to_owned(), 3),
+            (BranchKind::ElseIf, "b == 1".to_owned(), 17),
+            (BranchKind::While, "not c".to_owned(), 39),
+        ]
+    );
+
+    // Conditions are in the order they appear, so a repeat's comes after its body's
+    assert_eq!(
+        conditions("repeat if a then end until b"),
+        vec![
+            (BranchKind::If, "a".to_owned(), 10),
+            (BranchKind::Repeat, "b".to_owned(), 27),
+        ]
+    );
+}
+
+#[test]
+fn test_empty_block_trivia() {
+    let ast = parse("do   end").unwrap();
+    assert_eq!(full_moon::print(&ast), "do   end");
+
+    let r#do = match ast.nodes().iter_stmts().next() {
+        Some(ast::Stmt::Do(r#do)) => r#do,
+        _ => unreachable!(),
+    };
+
+    // The whitespace in between stays with `do`, as it's on the same line
+    assert!(r#do.block().iter_stmts().next().is_none());
+    assert_eq!(
+        r#do.do_token()
+            .trailing_trivia()
+            .map(|trivia| trivia.to_string())
+            .collect::<String>(),
+        "   "
+    );
+    assert_eq!(r#do.end_token().leading_trivia().count(), 0);
+}
+
+#[test]
+fn test_arity() {
+    let arity = |code| match parse(code).unwrap().nodes().iter_stmts().next() {
+        Some(ast::Stmt::LocalFunction(local_function)) => local_function.func_body().arity(),
+        _ => unreachable!(),
+    };
+
+    assert_eq!(arity("local function f() end"), Arity::Fixed(0));
+    assert_eq!(arity("local function f(a, b) end"), Arity::Fixed(2));
+    assert_eq!(arity("local function f(...) end"), Arity::Variadic(0));
+    assert_eq!(arity("local function f(a, ...) end"), Arity::Variadic(1));
+}
+
+#[test]
+fn test_parse_with_offset() {
+    use full_moon::tokenizer::{Position, TokenizerErrorType};
+
+    let code = "local x = 1\nprint(x)";
+    let ast = full_moon::parse_with_offset(code, Position::new(200, 10, 5)).unwrap();
+    assert_eq!(full_moon::print(&ast), code);
+
+    let tokens: Vec<_> = ast
+        .iter_tokens()
+        .filter(|token| token.to_string() == "x")
+        .map(|token| token.start_position())
+        .collect();
+    assert_eq!(
+        tokens,
+        vec![Position::new(206, 10, 11), Position::new(218, 11, 7)]
+    );
+
+    // Errors are in the document's positions too
+    match full_moon::parse_with_offset("x = 1\ny = $", Position::new(50, 3, 1)) {
+        Err(full_moon::Error::TokenizerError(error)) => {
+            assert_eq!(error.error(), TokenizerErrorType::UnexpectedToken('$'));
+            assert_eq!(error.position(), Position::new(60, 4, 5));
+        }
+
+        other => panic!("expected a tokenizer error, got {:?}", other),
+    }
+
+    match full_moon::parse_with_offset("local = 1", Position::new(50, 3, 1)) {
+        Err(full_moon::Error::AstError(full_moon::ast::AstError::UnexpectedToken {
+            token,
+            ..
+        })) => assert_eq!(token.start_position(), Position::new(56, 3, 7)),
+        other => panic!("expected an ast error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_use_dot_indexing() {
+    let rewrite = |code| {
+        let mut ast = parse(code).unwrap();
+        ast.use_dot_indexing();
+        full_moon::print(&ast)
+    };
+
+    assert_eq!(rewrite("x = t[\"foo\"]"), "x = t.foo");
+    assert_eq!(rewrite("t['a_1'][\"_b\"] = 1"), "t.a_1._b = 1");
+    assert_eq!(rewrite("t[ [[foo]] ]:m()"), "t.foo:m()");
+    assert_eq!(rewrite("t [\"foo\"] ()"), "t .foo ()");
+
+    // Keys that aren't valid names, or aren't plain strings
+    assert_eq!(rewrite("x = t[\"end\"]"), "x = t[\"end\"]");
+    assert_eq!(rewrite("x = t[\"2x\"]"), "x = t[\"2x\"]");
+    assert_eq!(rewrite("x = t[\"a b\"]"), "x = t[\"a b\"]");
+    assert_eq!(rewrite("x = t[\"\"]"), "x = t[\"\"]");
+    assert_eq!(rewrite("x = t[\"f\\111o\"]"), "x = t[\"f\\111o\"]");
+    assert_eq!(rewrite("x = t[\"a\" .. \"b\"]"), "x = t[\"a\" .. \"b\"]");
+    assert_eq!(rewrite("x = t[foo]"), "x = t[foo]");
+
+    // `goto` is only a name before Lua 5.2
+    let goto = rewrite("x = t[\"goto\"]");
+    if cfg!(feature = "lua52") {
+        assert_eq!(goto, "x = t[\"goto\"]");
+    } else {
+        assert_eq!(goto, "x = t.goto");
+    }
+
+    // Comments inside the brackets would be lost
+    assert_eq!(
+        rewrite("x = t[\"foo\" --[[key]]]"),
+        "x = t[\"foo\" --[[key]]]"
+    );
+}
+
+#[test]
+fn test_table_constructors() {
+    use full_moon::node::Node;
+
+    let ast = parse("local a = {1}; local b = {x=2}").unwrap();
+    let tables: Vec<_> = ast
+        .table_constructors()
+        .iter()
+        .map(|table| {
+            (
+                table.to_string(),
+                table.start_position().unwrap().bytes(),
+                table.end_position().unwrap().bytes(),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        tables,
+        vec![("{1}".to_owned(), 10, 13), ("{x=2}".to_owned(), 25, 30)]
+    );
+}
+
+#[test]
+fn test_redundant_returns() {
+    let redundant = |code| {
+        parse(code)
+            .unwrap()
+            .redundant_returns()
+            .iter()
+            .map(|r#return| r#return.token().start_position().bytes())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(redundant("function f() print(1) return end"), vec![22]);
+    assert_eq!(
+        redundant("local f = function() return; end\ncall(function() return end)"),
+        vec![21, 49]
+    );
+
+    // Returning values, returning early, and returning from the top of the file aren't redundant
+    assert!(redundant("function f() return 1 end").is_empty());
+    assert!(redundant("function f() if x then return end print(1) end").is_empty());
+    assert!(redundant("print(1) return").is_empty());
+}
+
+#[test]
+fn test_function_call_calls() {
+    let ast = parse("builder:a(1):b(2)\nf.g(x)[1](y, z):h 'w'").unwrap();
+    let calls: Vec<Vec<_>> = ast
+        .nodes()
+        .iter_stmts()
+        .map(|stmt| match stmt {
+            ast::Stmt::FunctionCall(call) => call
+                .calls()
+                .map(|args| args.to_string().trim().to_owned())
+                .collect(),
+            _ => unreachable!(),
+        })
+        .collect();
+
+    assert_eq!(
+        calls,
+        vec![vec!["(1)", "(2)"], vec!["(x)", "(y, z)", "'w'"]]
+    );
+}
+
+#[test]
+fn test_owned_ast() {
+    use full_moon::ast::owned::OwnedAst;
+
+    struct Script {
+        ast: OwnedAst,
+    }
+
+    let mut scripts = Vec::new();
+    for code in &["local x = 1", "print(x)"] {
+        let code = code.to_string();
+
+        // The code is moved into the OwnedAst, so nothing else keeps it alive
+        scripts.push(Script {
+            ast: OwnedAst::parse(code).unwrap(),
+        });
+    }
+
+    assert_eq!(scripts[0].ast.source(), "local x = 1");
+    assert_eq!(full_moon::print(scripts[1].ast.ast()), "print(x)");
+    assert_eq!(scripts[1].ast.ast().identifiers().len(), 2);
+
+    let mut script = scripts.remove(0);
+    script.ast.ast_mut().hoist_locals();
+    assert_eq!(full_moon::print(&script.ast.into_ast()), "local x = 1");
+
+    match OwnedAst::parse(String::from("local = 1")) {
+        Err(full_moon::Error::AstError(ast::AstError::UnexpectedToken { token, .. })) => {
+            assert_eq!(token.to_string(), "=")
+        }
+        other => panic!("expected an ast error, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn test_normalized_module_name() {
+    let ast = parse(
+        r#"local a = require("a/b/c")
+local b = require("a.b.c")
+local c = require "a\\b\\c"
+local d = require(path)
+local e = require("../a")
+local f = require("a")
+local g = require("..\\a")
+"#,
+    )
+    .unwrap();
+
+    let requires = ast.requires();
+    assert_eq!(requires[0].normalized_module_name().unwrap(), "a.b.c");
+    assert_eq!(
+        requires[0].normalized_module_name(),
+        requires[1].normalized_module_name()
+    );
+    assert_eq!(requires[2].normalized_module_name().unwrap(), "a.b.c");
+    assert!(requires[3].normalized_module_name().is_none());
+
+    // Going up a directory isn't the same module as one with the same name in this one
+    assert_eq!(requires[4].normalized_module_name().unwrap(), "../a");
+    assert_ne!(
+        requires[4].normalized_module_name(),
+        requires[5].normalized_module_name()
+    );
+    assert_eq!(requires[6].normalized_module_name().unwrap(), "../a");
+}
+
+#[test]
+fn test_fold() {
+    let ast = parse(
+        r#"if a then
+    if b then end
+elseif c then
+end
+while d do
+    if e then end
+end
+"#,
+    )
+    .unwrap();
+
+    let ifs = ast.fold(0, |count, event| match event {
+        VisitEvent::Enter(NodeRef::If(_)) => count + 1,
+        _ => count,
+    });
+    assert_eq!(ifs, 3);
+
+    // Every node entered is exited, and every token is seen once
+    let (depth, tokens) = ast.fold((0, Vec::new()), |(depth, mut tokens), event| match event {
+        VisitEvent::Enter(_) => (depth + 1, tokens),
+        VisitEvent::Exit(_) => (depth - 1, tokens),
+        VisitEvent::Token(token) => {
+            tokens.push(token.to_string());
+            (depth, tokens)
+        }
+    });
+    assert_eq!(depth, 0);
+    assert_eq!(tokens.len(), 20);
+    assert_eq!(
+        tokens.concat(),
+        "ifathenifbthenendelseifcthenendwhileddoifethenendend"
+    );
+
+    // The tokens of brackets are on either side of what's inside of them
+    let ast = parse("f(x, {1, t[2]})").unwrap();
+    let tokens = ast.fold(Vec::new(), |mut tokens, event| {
+        if let VisitEvent::Token(token) = event {
+            tokens.push(token.to_string());
+        }
+
+        tokens
+    });
+    assert_eq!(
+        tokens,
+        vec!["f", "(", "x", ",", "{", "1", ",", "t", "[", "2", "]", "}", ")", ""]
+    );
+}
+
+#[cfg(feature = "roblox")]
+#[test]
+fn test_function_attributes() {
+    let code =
+        "@native local function f() end\n@native @checked function g() end\nfunction h() end";
+    let ast = parse(code).unwrap();
+    assert_eq!(full_moon::print(&ast), code);
+
+    let attributes: Vec<Vec<String>> = ast
+        .nodes()
+        .iter_stmts()
+        .map(|stmt| {
+            let attributes = match stmt {
+                ast::Stmt::LocalFunction(function) => function.attributes(),
+                ast::Stmt::FunctionDeclaration(function) => function.attributes(),
+                _ => unreachable!(),
+            };
+
+            attributes
+                .iter()
+                .map(|attribute| attribute.name().to_string())
+                .collect()
+        })
+        .collect();
+
+    assert_eq!(
+        attributes,
+        [
+            vec!["native".to_owned()],
+            vec!["native".to_owned(), "checked".to_owned()],
+            Vec::new(),
+        ]
+    );
+
+    assert!(parse("@native local x = 1").is_err());
+    assert!(parse("@ function f() end").is_err());
+}
+
+#[test]
+fn test_block_is_empty() {
+    fn do_block_is_empty(code: &str) -> bool {
+        match parse(code).unwrap().nodes().iter_stmts().next() {
+            Some(ast::Stmt::Do(r#do)) => r#do.block().is_empty(),
+            _ => unreachable!(),
+        }
+    }
+
+    assert!(do_block_is_empty("do end"));
+    assert!(do_block_is_empty("do\n    -- comment\nend"));
+    assert!(!do_block_is_empty("do local x = 1 end"));
+    assert!(!do_block_is_empty("do return end"));
+
+    assert!(parse("").unwrap().nodes().is_empty());
+    assert!(!parse("do end").unwrap().nodes().is_empty());
+}
+
+#[test]
+fn test_remove_empty_blocks() {
+    fn remove(code: &str) -> String {
+        let mut ast = parse(code).unwrap();
+        ast.remove_empty_blocks();
+        full_moon::print(&ast)
+    }
+
+    assert_eq!(
+        remove("local x = 1\ndo end\nprint(x)\n"),
+        "local x = 1\nprint(x)\n"
+    );
+    assert_eq!(remove("do\n    do end\nend\n"), "");
+    assert_eq!(remove("do local x = 1 end"), "do local x = 1 end");
+    assert_eq!(remove("do -- keep me\nend"), "do -- keep me\nend");
+
+    // The call in the condition still has to happen
+    assert_eq!(remove("if f() then end\n"), "f()\n");
+    assert_eq!(
+        remove("do\n    if t:update(1) then\n    end\nend\n"),
+        "do\n    t:update(1)\nend\n"
+    );
+    assert_eq!(remove("if f\"x\" then end;"), "f\"x\";");
+    assert_eq!(remove("if x == f() then end"), "if x == f() then end");
+    assert_eq!(remove("if x then end\nif true then end\n"), "");
+
+    assert_eq!(
+        remove("if x then\n    print(x)\nelseif y then\nelse\nend\n"),
+        "if x then\n    print(x)\nend\n"
+    );
+    assert_eq!(
+        remove("if x then\n    print(x)\nelseif f() then\nend\n"),
+        "if x then\n    print(x)\nelseif f() then\nend\n"
+    );
+    assert_eq!(
+        remove("if x then\nelseif y then\n    print(y)\nend\n"),
+        "if x then\nelseif y then\n    print(y)\nend\n"
+    );
+}
+
+#[test]
+fn test_foldable_regions() {
+    let ast = parse(
+        r#"local function f(x)
+    for i = 1, 10 do
+        if x then
+            print(i)
+        elseif y then print(y)
+        else
+            local t = {
+                1,
+                2,
+            }
+        end
+    end
+
+    return { 1, 2 }
+end
+
+while x do print(x) end
+"#,
+    )
+    .unwrap();
+
+    let regions = ast.foldable_regions();
+    let kinds: Vec<_> = regions.iter().map(|region| region.kind()).collect();
+    assert_eq!(
+        kinds,
+        [
+            FoldingKind::Function,
+            FoldingKind::Loop,
+            FoldingKind::Branch,
+            FoldingKind::Branch,
+            FoldingKind::Branch,
+            FoldingKind::Table,
+        ]
+    );
+
+    let function = regions[0].span();
+    assert_eq!(function.start().line(), 1);
+    assert_eq!(function.start().character(), 17);
+    assert_eq!(function.end().line(), 15);
+    assert_eq!(regions[1].span().start().line(), 2);
+
+    let table = regions[5].span();
+    assert_eq!(table.start().line(), 7);
+    assert_eq!(table.start().character(), 23);
+    assert_eq!(table.end().line(), 10);
+    assert_eq!(table.end().character(), 14);
+
+    // The elseif branch runs up to the `else`
+    let else_if = regions[3].span();
+    assert_eq!(else_if.start().line(), 5);
+    assert_eq!(else_if.end().line(), 6);
+    assert_eq!(else_if.end().character(), 9);
+}
+
+#[test]
+fn test_implicit_globals() {
+    let ast = parse(
+        r#"foo = 1
+print(1)
+local x
+x = 2
+function bar() end
+function bar.baz() end
+local function qux()
+    local y
+    y, z = 3, 4
+    t.field = 5
+end
+for i = 1, 10 do
+    i = i + 1
+end
+"#,
+    )
+    .unwrap();
+
+    let globals: Vec<_> = ast
+        .implicit_globals()
+        .iter()
+        .map(|name| (name.to_string(), name.start_position().bytes()))
+        .collect();
+    assert_eq!(
+        globals,
+        [
+            ("foo".to_owned(), 0),
+            ("bar".to_owned(), 40),
+            ("z".to_owned(), 113),
+        ]
+    );
+
+    let globals: Vec<_> = ast
+        .implicit_globals_with(&["foo", "z"])
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(globals, ["bar"]);
+
+    assert!(parse("print = nil table = {}")
+        .unwrap()
+        .implicit_globals()
+        .is_empty());
+}
+
+#[test]
+fn test_if_builder() {
+    let a = parse("return a").unwrap();
+    let b = parse("return b == 1").unwrap();
+    let first = parse("print(1)\n").unwrap();
+    // Blocks that don't end with a new line still have their keywords put on the next line
+    let second = parse("print(2)").unwrap();
+    let third = parse("local x = 3\nprint(x)\n").unwrap();
+
+    let r#if = IfBuilder::new(return_expression(&a).clone(), first.nodes().clone())
+        .else_if(return_expression(&b).clone(), second.nodes().clone())
+        .else_block(third.nodes().clone())
+        .build();
+
+    let code = r#if.to_string();
+    assert_eq!(
+        code,
+        "if a then\nprint(1)\nelseif b == 1 then\nprint(2)\nelse\nlocal x = 3\nprint(x)\nend"
+    );
+    assert!(parse(&code).is_ok());
+
+    let empty = IfBuilder::new(return_expression(&a).clone(), ast::Block::default()).build();
+    assert_eq!(empty.to_string(), "if a then\nend");
+    assert!(parse(&empty.to_string()).is_ok());
+}
+
+#[test]
+fn test_code_loads() {
+    let ast = parse(
+        r#"local f = load(userInput)
+local a = require("a")
+local b = require(var)
+dofile("init.lua")
+"#,
+    )
+    .unwrap();
+
+    let code_loads = ast.code_loads();
+    assert_eq!(
+        code_loads
+            .iter()
+            .map(|code_load| (
+                code_load.loader().to_string(),
+                code_load.severity(),
+                code_load.is_dynamic(),
+                code_load.loader().start_position().bytes(),
+            ))
+            .collect::<Vec<_>>(),
+        vec![
+            ("load".to_string(), Severity::High, true, 10),
+            ("require".to_string(), Severity::Medium, true, 59),
+            ("dofile".to_string(), Severity::Medium, false, 72),
+        ],
+    );
+
+    let code_loads = ast.code_loads_with(&[Loader {
+        name: "require",
+        severity: Severity::Low,
+        dynamic_only: false,
+    }]);
+    assert_eq!(code_loads.len(), 2);
+    assert!(code_loads
+        .iter()
+        .all(|code_load| code_load.severity() == Severity::Low));
+}
+
+#[test]
+fn test_enclosing_statement() {
+    let code = r#"local x = 1
+for i = 1, 10 do
+    if i > x then
+        print(i)
+    end
+end
+"#;
+    let ast = parse(code).unwrap();
+    let position = |text: &str| {
+        let bytes = code.find(text).unwrap();
+        let line = code[..bytes].matches('\n').count() + 1;
+        let character = bytes
+            - code[..bytes]
+                .rfind('\n')
+                .map(|index| index + 1)
+                .unwrap_or(0)
+            + 1;
+        Position::new(bytes, line, character)
+    };
+
+    let print = ast.enclosing_statement(position("(i)")).unwrap();
+    assert_eq!(print.to_string().trim(), "print(i)");
+
+    let r#if = ast.enclosing_statement(position("i > x")).unwrap();
+    assert!(r#if.to_string().trim_start().starts_with("if i > x then"));
+
+    let r#for = ast
+        .enclosing_statement_with(position("(i)"), Depth::Outermost)
+        .unwrap();
+    assert!(r#for.to_string().starts_with("for i = 1, 10 do"));
+
+    let local = ast.enclosing_statement(position("1\n")).unwrap();
+    assert_eq!(local.to_string(), "local x = 1\n");
+
+    // The indentation before a statement isn't part of any of them but the ones around it
+    let for_body = ast.enclosing_statement(position("    if")).unwrap();
+    assert!(for_body.to_string().starts_with("for"));
+    assert!(ast
+        .enclosing_statement(Position::new(code.len(), 7, 1))
+        .is_none());
+}
+
+#[test]
+fn test_align_assignments() {
+    let mut ast = parse(
+        r#"local a = 1
+local bb = 2
+ccc.d = 3 -- comment
+call()
+x = 1
+yy = 2
+-- comment
+zzz = 3
+local function f()
+    local a = 1
+    local bbb = 2
+end
+"#,
+    )
+    .unwrap();
+
+    ast.align_assignments();
+    assert_eq!(
+        full_moon::print(&ast),
+        r#"local a  = 1
+local bb = 2
+ccc.d    = 3 -- comment
+call()
+x  = 1
+yy = 2
+-- comment
+zzz = 3
+local function f()
+    local a   = 1
+    local bbb = 2
+end
+"#,
+    );
+
+    // Aligning again doesn't change anything
+    let aligned = full_moon::print(&ast);
+    ast.align_assignments();
+    assert_eq!(full_moon::print(&ast), aligned);
+}
+
+#[test]
+fn test_field_separator_kind() {
+    let ast = parse("local t = {1; 2, 3}").unwrap();
+    let table = &ast.table_constructors()[0];
+
+    assert_eq!(
+        table
+            .iter_fields()
+            .map(field_separator_kind)
+            .collect::<Vec<_>>(),
+        vec![
+            Some(FieldSeparator::Semicolon),
+            Some(FieldSeparator::Comma),
+            None
+        ],
+    );
+
+    let ast = parse("local t = {a = 1, [2] = 3;}").unwrap();
+    let table = &ast.table_constructors()[0];
+    assert_eq!(
+        table
+            .iter_fields()
+            .map(field_separator_kind)
+            .collect::<Vec<_>>(),
+        vec![Some(FieldSeparator::Comma), Some(FieldSeparator::Semicolon)],
+    );
+
+    let mut field = table.iter_fields().next().unwrap().clone();
+    field.1 = Some(TokenReference::symbol(tokenizer::Symbol::Dot));
+    assert_eq!(field_separator_kind(&field), None);
+}
+
+#[test]
+fn test_normalize_field_separators() {
+    let ast = parse("local t = {1; 2; 3}").unwrap();
+    let mut table = ast.table_constructors().remove(0);
+    table.normalize_field_separators(FieldSeparator::Comma);
+    assert_eq!(table.to_string(), "{1, 2, 3}");
+    assert!(table
+        .iter_fields()
+        .take(2)
+        .all(|field| field_separator_kind(field) == Some(FieldSeparator::Comma)));
+
+    // The trivia around each separator is kept
+    let ast = parse("local t = {\n\ta = 1 ; -- a\n\tb = 2,\n}").unwrap();
+    let mut table = ast.table_constructors().remove(0);
+    table.normalize_field_separators(FieldSeparator::Semicolon);
+    assert_eq!(table.to_string(), "{\n\ta = 1 ; -- a\n\tb = 2;\n}");
+}
+
+#[cfg(feature = "roblox")]
+#[test]
+fn test_local_assignment_types_display() {
+    let code = "local foo: number, bar, baz: boolean = 1, 2, true";
+    let ast = parse(code).unwrap();
+    let stmt = ast.nodes().iter_stmts().next().unwrap();
+    assert_eq!(stmt.to_string(), code);
+    assert_eq!(full_moon::print(&ast), code);
+
+    let code = "local x: string\n";
+    let ast = parse(code).unwrap();
+    assert_eq!(full_moon::print(&ast), code);
+}
+
+#[test]
+fn test_statement_count() {
+    let code = r#"
+local function outer(list)
+    for _, item in ipairs(list) do
+        if item then
+            print(item)
+        elseif item == false then
+            break
+        else
+            repeat
+                item = next(item)
+            until not item
+        end
+    end
+
+    return function() return list end
+end
+
+-- not a statement
+do end
+"#;
+
+    let ast = parse(code).unwrap();
+    assert_eq!(ast.statement_count(), 10);
+    assert_eq!(ast.logical_lines(), 18);
+
+    let compact = parse(
+        "local function outer(list) for _, item in ipairs(list) do if item then print(item) \
+         elseif item == false then break else repeat item = next(item) until not item end end \
+         return function() return list end end do end",
+    )
+    .unwrap();
+    assert_eq!(compact.statement_count(), 10);
+    assert_eq!(compact.logical_lines(), 18);
+
+    assert_eq!(parse("").unwrap().statement_count(), 0);
+    assert_eq!(parse("-- comment\n").unwrap().logical_lines(), 0);
+}
+
+#[test]
+fn test_goto_as_name() {
+    // `goto` is only a keyword from Lua 5.2, before that it can be used as a name
+    let code = "local goto = 1\ngoto = goto + 1";
+
+    if cfg!(feature = "lua52") {
+        assert!(parse(code).is_err());
+        assert!(parse("local t = { goto = 1 }").is_err());
+        assert!(parse("print(t.goto)").is_err());
+
+        let ast = parse("goto continue\n::continue::").unwrap();
+        assert_eq!(ast.nodes().iter_stmts().count(), 2);
+        assert_eq!(full_moon::print(&ast), "goto continue\n::continue::");
+        assert!(parse("goto goto").is_err());
+    } else {
+        assert_eq!(parse(code).unwrap().statement_count(), 2);
+        assert!(parse("goto continue").is_err());
+        assert!(parse("::continue::").is_err());
+    }
+}
+
+#[cfg(feature = "lua52")]
+#[test]
+fn test_goto_fold_and_sexpr() {
+    let ast = parse("goto continue\n::continue::").unwrap();
+    let labels = ast.fold(Vec::new(), |mut labels, event| {
+        match event {
+            VisitEvent::Enter(NodeRef::Goto(goto)) => labels.push(goto.label_name().to_string()),
+            VisitEvent::Enter(NodeRef::Label(label)) => labels.push(label.name().to_string()),
+            _ => {}
+        }
+
+        labels
+    });
+    assert_eq!(labels, vec!["continue", "continue"]);
+
+    let sexpr = ast.to_sexpr();
+    assert!(sexpr.contains("(Goto \"goto\" \"continue\")"));
+    assert!(sexpr.contains("(Label \"::\" \"continue\" \"::\")"));
+}
+
+#[test]
+fn test_parse_value() {
+    let code = "-- config\n{\n\tname = \"full-moon\",\n\tsizes = { 2, 3 },\n}\n";
+    let value = parse_value(code).unwrap();
+    assert_eq!(value.to_string(), code);
+
+    let table = match value {
+        Value::TableConstructor(table) => table,
+        _ => panic!("expected a table"),
+    };
+
+    let fields: Vec<_> = table.iter_fields().collect();
+    assert_eq!(fields.len(), 2);
+
+    match &fields[1].0 {
+        Field::NameKey { key, value, .. } => {
+            assert_eq!(key.to_string(), "sizes");
+            match value {
+                Expression::Value { value, .. } => match &**value {
+                    Value::TableConstructor(sizes) => assert_eq!(sizes.iter_fields().count(), 2),
+                    _ => panic!("expected a nested table"),
+                },
+                _ => panic!("expected a value"),
+            }
+        }
+        _ => panic!("expected a named field"),
+    }
+
+    assert!(parse_value("{} {}").is_err());
+    assert!(parse_value("{ a = 1 } b = 2").is_err());
+    assert!(parse_value("").is_err());
+}
+
+#[cfg(feature = "lua53")]
+#[test]
+fn test_lua53_operators() {
+    let ast = parse("return a & b | c ~ d << 2 >> 1 // 3").unwrap();
+    let binops = return_expression(&ast)
+        .binops()
+        .map(|binop| match binop {
+            BinOp::Ampersand(token)
+            | BinOp::Pipe(token)
+            | BinOp::Tilde(token)
+            | BinOp::TwoLessThan(token)
+            | BinOp::TwoGreaterThan(token)
+            | BinOp::DoubleSlash(token) => token.to_string(),
+            _ => unreachable!(),
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(binops, vec!["&", "|", "~", "<<", ">>", "//"]);
+    assert_eq!(
+        full_moon::print(&ast),
+        "return a & b | c ~ d << 2 >> 1 // 3"
+    );
+
+    // `(6 & 3) | (5 ~ ((1 << 2) >> (1 // 3)))`, any other nesting gives a different result
+    let ast = parse("return 6 & 3 | 5 ~ 1 << 2 >> 1 // 3").unwrap();
+    assert_eq!(
+        return_expression(&ast).eval_constant(),
+        Some(Constant::Number(3.0))
+    );
+
+    let ast = parse("return 1 << 2 >> 1 .. ''").unwrap();
+    assert_eq!(
+        return_expression(&ast).eval_constant(),
+        Some(Constant::Number(2.0))
+    );
+
+    let ast = parse("return ~0 ~ -7 // 2").unwrap();
+    assert_eq!(
+        return_expression(&ast).eval_constant(),
+        Some(Constant::Number(3.0))
+    );
+
+    assert!(parse("return a ~= b").is_ok());
+}
+
+#[cfg(all(feature = "roblox", feature = "lua53"))]
+#[test]
+fn test_lua53_nested_generics() {
+    use full_moon::ast::types::TypeInfo;
+
+    // `>>` is a single token with Lua 5.3, but still closes both generics
+    let code =
+        "local x: Array<Array<number>> = 1 >> 2\nlocal y: Map<string, Array<Array<number>>>\n";
+    let ast = parse(code).unwrap();
+    assert_eq!(full_moon::print(&ast), code);
+
+    let type_info = full_moon::parse_type("Array<Array<number>> ").unwrap();
+    let (outer_end, inner_end) = match &type_info {
+        TypeInfo::Generic {
+            arrows, generics, ..
+        } => match generics.iter().next() {
+            Some(TypeInfo::Generic {
+                arrows: inner_arrows,
+                ..
+            }) => (arrows.tokens().1, inner_arrows.tokens().1),
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    };
+
+    assert_eq!(inner_end.to_string(), ">");
+    assert_eq!(inner_end.start_position().bytes(), 18);
+    assert_eq!(outer_end.to_string(), ">");
+    assert_eq!(outer_end.start_position().bytes(), 19);
+    assert_eq!(type_info.to_string(), "Array<Array<number>> ");
+
+    // Only one generic is closed, so there's a `>` left over
+    assert!(parse("local x: Array<number>> = 1").is_err());
+    assert!(parse("local x = y :: Array<number>> 1").is_err());
+}
+
+#[cfg(not(feature = "lua53"))]
+#[test]
+fn test_lua53_operators_disabled() {
+    assert!(parse("return a & b").is_err());
+    assert!(parse("return a // b").is_err());
+    assert!(parse("return ~a").is_err());
+    assert!(parse("return a >> b").is_err());
+}
+
+#[test]
+fn test_parse_with_stats() {
+    let code = "local x = 1 -- one\nprint(x)\n";
+    let (ast, stats) = full_moon::parse_with_stats(code);
+    assert!(ast.is_ok());
+    assert_eq!(stats.token_count(), tokenizer::tokens(code).unwrap().len());
+    // `local`, `x`, `=`, `1`, `print`, `(`, `x`, `)`, 6 whitespace tokens, the comment, and the end of the file
+    assert_eq!(stats.token_count(), 16);
+    assert_eq!(stats.node_count(), 23);
+
+    let (ast, stats) = full_moon::parse_with_stats("local x = ");
+    assert!(ast.is_err());
+    assert_eq!(stats.token_count(), 7);
+    assert_eq!(stats.node_count(), 0);
+
+    let (ast, stats) = full_moon::parse_with_stats("local x = \"");
+    assert!(ast.is_err());
+    assert_eq!(stats.token_count(), 0);
+}
+
+#[test]
+fn test_float_comparisons() {
+    let ast = parse("if x == 0.1 then end\nif x == 1 then end").unwrap();
+    let comparisons = ast.float_comparisons();
+    assert_eq!(comparisons.len(), 1);
+    assert_eq!(comparisons[0].float().to_string(), "0.1 ");
+    assert_eq!(comparisons[0].other().unwrap().to_string(), "x ");
+    if let BinOp::TildeEqual(_) = comparisons[0].bin_op() {
+        panic!("expected ==");
+    }
+    assert_eq!(comparisons[0].start_position().unwrap().bytes(), 3);
+    assert_eq!(comparisons[0].end_position().unwrap().bytes(), 11);
+
+    let ast = parse(
+        "local a = 1e3 ~= y\nlocal b = f(x + 1 == -0.5)\nlocal c = x == 0xE or x == 10 or t.x == 2.",
+    )
+    .unwrap();
+    let comparisons = ast.float_comparisons();
+    let floats: Vec<_> = comparisons
+        .iter()
+        .map(|comparison| comparison.float().to_string().trim().to_owned())
+        .collect();
+    assert_eq!(floats, vec!["1e3", "0.5", "2."]);
+
+    assert_eq!(comparisons[0].other().unwrap().to_string(), "y\n");
+    assert!(comparisons[1].other().is_none());
+    assert_eq!(comparisons[2].other().unwrap().to_string(), "t.x ");
+
+    assert!(parse("return x == 1, 1 < 0.5")
+        .unwrap()
+        .float_comparisons()
+        .is_empty());
+
+    if cfg!(any(feature = "lua52", feature = "lua53")) {
+        let ast = parse("local a = x == 0x1.8p1 or x == 0x1p4 or x == 0xE1").unwrap();
+        let comparisons = ast.float_comparisons();
+        let floats: Vec<_> = comparisons
+            .iter()
+            .map(|comparison| comparison.float().to_string().trim().to_owned())
+            .collect();
+        assert_eq!(floats, vec!["0x1.8p1", "0x1p4"]);
+        assert_eq!(comparisons[0].float().parse_number(), Some(3.0));
+        assert_eq!(comparisons[1].float().parse_number(), Some(16.0));
+    }
+}
+
+#[test]
+fn test_doc_comment() {
+    let code = r#"local x = 1 -- not documentation
+-- Creates a new instance.
+-- @param name string
+function Module.new(name)
+end
+
+do
+    --- Checks the name.
+    --    Indented
+    local function check(name) end
+
+    -- Too far away
+
+    local function undocumented() end
+end
+"#;
+
+    let ast = parse(code).unwrap();
+    let mut stmts = ast.nodes().iter_stmts().skip(1);
+
+    let new = match stmts.next() {
+        Some(ast::Stmt::FunctionDeclaration(declaration)) => declaration,
+        _ => unreachable!(),
+    };
+    assert_eq!(
+        new.doc_comment().as_deref(),
+        Some("Creates a new instance.\n@param name string")
+    );
+
+    let block = match stmts.next() {
+        Some(ast::Stmt::Do(r#do)) => r#do.block(),
+        _ => unreachable!(),
+    };
+    let local_functions: Vec<_> = block
+        .iter_stmts()
+        .map(|stmt| match stmt {
+            ast::Stmt::LocalFunction(local_function) => local_function,
+            _ => unreachable!(),
+        })
+        .collect();
+
+    assert_eq!(
+        local_functions[0].doc_comment().as_deref(),
+        Some("Checks the name.\n   Indented")
+    );
+    assert_eq!(local_functions[1].doc_comment(), None);
+
+    let ast = parse("--[[ not a line comment ]]\nlocal function f() end").unwrap();
+    match ast.nodes().iter_stmts().next() {
+        Some(ast::Stmt::LocalFunction(local_function)) => {
+            assert_eq!(local_function.doc_comment(), None)
+        }
+        _ => unreachable!(),
+    };
+}
+
+#[test]
+fn test_punctuated_extend() {
+    use full_moon::ast::punctuated::{Pair, Punctuated};
+
+    let first = parse("return a").unwrap();
+    let rest = parse("return b, c").unwrap();
+    let rest = match rest.nodes().last_stmts() {
+        Some(LastStmt::Return(r#return)) => r#return.returns(),
+        _ => unreachable!(),
+    };
+
+    let mut punctuated = Punctuated::new();
+    punctuated.push(Pair::new(return_expression(&first).clone(), None));
+    punctuated.extend(rest.iter().cloned());
+    assert_eq!(punctuated.to_string(), "a, b, c");
+    assert!(punctuated.pairs().last().unwrap().punctuation().is_none());
+
+    let mut punctuated = Punctuated::new();
+    punctuated.push(Pair::new(return_expression(&first).clone(), None));
+    punctuated.extend(rest.pairs().cloned());
+    assert_eq!(punctuated.to_string(), "a, b, c");
+}
+
+#[test]
+fn test_punctuated_push_pop() {
+    use full_moon::ast::punctuated::{Pair, Punctuated};
+
+    let numbers = parse("return 1, 2, 3").unwrap();
+    let numbers: Vec<_> = match numbers.nodes().last_stmts() {
+        Some(LastStmt::Return(r#return)) => r#return.returns().iter().cloned().collect(),
+        _ => unreachable!(),
+    };
+
+    let mut punctuated = Punctuated::new();
+    for number in &numbers {
+        punctuated.push_value(number.clone());
+    }
+
+    assert_eq!(punctuated.to_string(), "1, 2, 3");
+    assert_eq!(punctuated.len(), 3);
+
+    let pairs: Vec<_> = punctuated.pairs().collect();
+    assert!(pairs[0].punctuation().is_some());
+    assert!(pairs[1].punctuation().is_some());
+    assert!(pairs[2].punctuation().is_none());
+
+    let last = punctuated.pop().unwrap();
+    assert_eq!(last.value().to_string(), "3");
+    assert!(last.punctuation().is_none());
+    assert_eq!(punctuated.to_string(), "1, 2, ");
+
+    punctuated.push(last);
+    assert_eq!(punctuated.to_string(), "1, 2, 3");
+
+    while punctuated.pop().is_some() {}
+    assert!(punctuated.is_empty());
+    assert_eq!(punctuated.pop(), None);
+
+    // Only the last pair can be without punctuation
+    punctuated.push(Pair::new(numbers[0].clone(), None));
+    punctuated.push(Pair::new(numbers[1].clone(), None));
+    assert_eq!(punctuated.to_string(), "1, 2");
+    assert!(punctuated.pairs().next().unwrap().punctuation().is_some());
+
+    // Pairs are separated like the rest of the sequence
+    let ast = parse("function a.b() end").unwrap();
+    let mut names = match ast.nodes().iter_stmts().next() {
+        Some(ast::Stmt::FunctionDeclaration(declaration)) => declaration.name().names().clone(),
+        _ => unreachable!(),
+    };
+
+    let c = names.iter().next().unwrap().clone();
+    names.push(Pair::new(c.clone(), None));
+    assert_eq!(names.to_string(), "a.b.a");
+    names.extend(vec![c.clone()]);
+    assert_eq!(names.to_string(), "a.b.a.a");
+    names.extend(vec![Pair::new(c, None)]);
+    assert_eq!(names.to_string(), "a.b.a.a.a");
+}
+
+#[test]
+fn test_punctuated_insert_remove() {
+    use full_moon::{
+        ast::punctuated::{Pair, Punctuated},
+        tokenizer::Symbol,
+    };
+
+    let ast = parse("return 1, 2, 3").unwrap();
+    let numbers = match ast.nodes().last_stmts() {
+        Some(LastStmt::Return(r#return)) => r#return.returns(),
+        _ => unreachable!(),
+    };
+
+    let x = parse("return x").unwrap();
+    let x = return_expression(&x);
+
+    let inserted = |index| {
+        let mut punctuated = numbers.clone();
+        punctuated.insert(index, x.clone());
+        assert_eq!(punctuated.len(), 4);
+        assert!(punctuated.pairs().last().unwrap().punctuation().is_none());
+        punctuated.to_string()
+    };
+
+    assert_eq!(inserted(0), "x, 1, 2, 3");
+    assert_eq!(inserted(1), "1, x, 2, 3");
+    assert_eq!(inserted(2), "1, 2, x, 3");
+    assert_eq!(inserted(3), "1, 2, 3, x");
+
+    let removed = |index| {
+        let mut punctuated = numbers.clone();
+        let value = punctuated.remove(index).map(|value| value.to_string());
+        (value, punctuated.to_string())
+    };
+
+    assert_eq!(removed(0), (Some("1".to_owned()), "2, 3".to_owned()));
+    assert_eq!(removed(1), (Some("2".to_owned()), "1, 3".to_owned()));
+    assert_eq!(removed(2), (Some("3".to_owned()), "1, 2".to_owned()));
+    assert_eq!(removed(3), (None, "1, 2, 3".to_owned()));
+
+    let mut punctuated = numbers.clone();
+    while punctuated.remove(0).is_some() {}
+    assert!(punctuated.is_empty());
+    punctuated.insert(0, x.clone());
+    assert_eq!(punctuated.to_string(), "x");
+
+    // A trailing separator stays trailing
+    let mut fields = Punctuated::new();
+    fields.push(Pair::new(
+        1,
+        Some(TokenReference::symbol(Symbol::Semicolon)),
+    ));
+    fields.push(Pair::new(
+        2,
+        Some(TokenReference::symbol(Symbol::Semicolon)),
+    ));
+    fields.insert(2, 3);
+    assert_eq!(fields.to_string(), "1;2;3;");
+    fields.insert(0, 0);
+    assert_eq!(fields.to_string(), "0;1;2;3;");
+    assert_eq!(fields.remove(3), Some(3));
+    assert_eq!(fields.to_string(), "0;1;2;");
+
+    // The separators of the sequence are used wherever the value goes
+    let ast = parse("function a.b() end\nfunction c() end").unwrap();
+    let mut names = ast.nodes().iter_stmts().map(|stmt| match stmt {
+        ast::Stmt::FunctionDeclaration(declaration) => declaration.name().names().clone(),
+        _ => unreachable!(),
+    });
+
+    let (a_b, c) = (names.next().unwrap(), names.next().unwrap());
+    let c = c.iter().next().unwrap();
+    for (index, expected) in vec!["c.a.b", "a.c.b", "a.b.c"].into_iter().enumerate() {
+        let mut names = a_b.clone();
+        names.insert(index, c.clone());
+        assert_eq!(names.to_string(), expected);
+    }
+}
+
+#[test]
+#[should_panic(expected = "insertion index (is 4) should be <= len (is 3)")]
+fn test_punctuated_insert_out_of_bounds() {
+    let ast = parse("return 1, 2, 3").unwrap();
+    let mut numbers = match ast.nodes().last_stmts() {
+        Some(LastStmt::Return(r#return)) => r#return.returns().clone(),
+        _ => unreachable!(),
+    };
+
+    numbers.insert(4, return_expression(&ast).clone());
+}
+
+#[test]
+fn test_unwrap_parens() {
+    let ast = parse("return (((x)))").unwrap();
+    let expression = return_expression(&ast);
+    assert_eq!(expression.unwrap_parens().to_string(), "x");
+    assert_eq!(expression.paren_depth(), 3);
+
+    let ast = parse("return (x) + 1").unwrap();
+    let expression = return_expression(&ast);
+    assert_eq!(expression.unwrap_parens().to_string(), "(x) + 1");
+    assert_eq!(expression.paren_depth(), 0);
+}
+
+#[test]
+fn test_function_call_simple_name() {
+    let ast = parse("table.insert(t, 1); a:b(); (x)(); t[1](); f()(); a.b.c:d(1)").unwrap();
+    let names = ast
+        .nodes()
+        .iter_stmts()
+        .map(|stmt| match stmt {
+            ast::Stmt::FunctionCall(call) => call.simple_name(),
+            _ => unreachable!(),
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        names,
+        vec![
+            Some("table.insert".to_owned()),
+            Some("a:b".to_owned()),
+            None,
+            None,
+            None,
+            Some("a.b.c:d".to_owned()),
+        ]
+    );
+
+    // A call put together by hand can end in an index, such as one deserialized from `t.f`
+    let ast = parse("t.f = 1").unwrap();
+    let var_expression = match ast.nodes().iter_stmts().next() {
+        Some(ast::Stmt::Assignment(assignment)) => match assignment.var_list().iter().next() {
+            Some(ast::Var::Expression(var_expression)) => var_expression,
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    };
+
+    let serialized = serde_json::to_string(var_expression).unwrap();
+    let call: ast::FunctionCall = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(call.simple_name(), None);
+}
+
+#[test]
+fn test_token_mut() {
+    struct DoSpacing;
+
+    impl<'ast> VisitorMut<'ast> for DoSpacing {
+        fn visit_do(&mut self, do_block: &mut ast::Do<'ast>) {
+            let token = (**do_block.do_token()).clone();
+            let new_line = Token::new(TokenType::Whitespace {
+                characters: "\n".into(),
+            });
+
+            *do_block.do_token_mut() = TokenReference::new(Vec::new(), token, vec![new_line]);
+        }
+    }
+
+    let mut ast = parse("do call() end").unwrap();
+    DoSpacing.visit_ast(&mut ast);
+    assert_eq!(full_moon::print(&ast), "do\ncall() end");
+}
+
+// Neither Lua nor Luau allow a comma after the last argument of a call
+#[test]
+fn test_call_trailing_comma() {
+    assert!(parse("f(1, 2)").is_ok());
+    assert!(parse("f(1, 2,)").is_err());
+    assert!(parse("f(,)").is_err());
+}
+
+#[cfg(feature = "roblox")]
+#[test]
+fn test_to_compound_assignment() {
+    fn assert_compound(code: &str, expected: Option<&str>) {
+        let ast = parse(code).unwrap();
+        let assignment = match ast.nodes().iter_stmts().next() {
+            Some(ast::Stmt::Assignment(assignment)) => assignment,
+            _ => panic!("expected an assignment"),
+        };
+
+        assert_eq!(
+            assignment
+                .to_compound_assignment()
+                .map(|compound_assignment| compound_assignment.to_string())
+                .as_deref(),
+            expected,
+            "{}",
+            code
+        );
+    }
+
+    assert_compound("a = a + 1", Some("a += 1"));
+    assert_compound("t.x = t.x - 1", Some("t.x -= 1"));
+    assert_compound("t[1] = t[1] * 2", Some("t[1] *= 2"));
+    assert_compound("s = s .. \"!\"", Some("s ..= \"!\""));
+    assert_compound("a = a + b * c", Some("a += b * c"));
+
+    // Getting the variable could call a function
+    assert_compound("t[f()] = t[f()] + 1", None);
+    assert_compound("f().x = f().x + 1", None);
+
+    // The variable's operator isn't the last one to be done
+    assert_compound("a = a * b + c", None);
+    assert_compound("a = a - b - c", None);
+
+    assert_compound("a = b + a", None);
+    assert_compound("a = a == 1", None);
+    assert_compound("a, b = a + 1, b + 1", None);
+}
+
+#[test]
+fn test_nil_comparisons() {
+    let ast = parse(
+        "if x == nil then\n\tprint(nil ~= t.y, f() == nil)\nend\nlocal z = a + b == nil or not (c ~= nil)",
+    )
+    .unwrap();
+
+    let comparisons = ast.nil_comparisons();
+    let found: Vec<_> = comparisons
+        .iter()
+        .map(|comparison| {
+            (
+                comparison.value().to_string().trim().to_owned(),
+                comparison.var().is_some(),
+                comparison.is_equal(),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        found,
+        vec![
+            ("x".to_owned(), true, true),
+            ("t.y".to_owned(), true, false),
+            ("f()".to_owned(), false, true),
+            ("c".to_owned(), true, false),
+        ]
+    );
+
+    let start = comparisons[0].start_position().unwrap();
+    let end = comparisons[0].end_position().unwrap();
+    assert_eq!((start.bytes(), end.bytes()), (3, 11));
+    assert_eq!(comparisons[0].nil_token().start_position().bytes(), 8);
+
+    assert_eq!(comparisons[1].start_position().unwrap().bytes(), 24);
+    assert_eq!(comparisons[1].end_position().unwrap().bytes(), 34);
+}
+
+#[cfg(feature = "roblox")]
+#[test]
+fn test_luau_directives() {
+    let ast =
+        parse("-- a normal comment\n--!strict\n--!native\n\nlocal x = 1\n--!nocheck\n").unwrap();
+
+    let directives: Vec<_> = ast
+        .luau_directives()
+        .iter()
+        .map(|directive| (directive.name().to_owned(), directive.argument().is_none()))
+        .collect();
+
+    assert_eq!(
+        directives,
+        vec![("strict".to_owned(), true), ("native".to_owned(), true)]
+    );
+
+    let ast = parse("--!optimize   2  \nreturn").unwrap();
+    let directives = ast.luau_directives();
+    assert_eq!(directives[0].name(), "optimize");
+    assert_eq!(directives[0].argument(), Some("2"));
+    assert_eq!(directives[0].comment().to_string(), "--!optimize   2  ");
+}
+
+#[test]
+fn test_stmt_indentation() {
+    let ast = parse(
+        "call()\nif x then\n\t-- comment\n\tlocal y = 1\n\twhile true do\n\t  (f)()\n\tend\nend",
+    )
+    .unwrap();
+
+    let mut stmts = ast.nodes().iter_stmts();
+    assert_eq!(stmts.next().unwrap().indentation(), "");
+
+    let r#if = match stmts.next().unwrap() {
+        ast::Stmt::If(r#if) => r#if,
+        _ => unreachable!(),
+    };
+
+    let mut stmts = r#if.block().iter_stmts();
+    assert_eq!(stmts.next().unwrap().indentation(), "\t");
+
+    let r#while = match stmts.next().unwrap() {
+        ast::Stmt::While(r#while) => r#while,
+        _ => unreachable!(),
+    };
+
+    assert_eq!(
+        r#while.block().iter_stmts().next().unwrap().indentation(),
+        "\t  "
+    );
+
+    let ast = parse("  local x = 1; local y = 2").unwrap();
+    let indentations: Vec<_> = ast
+        .nodes()
+        .iter_stmts()
+        .map(ast::Stmt::indentation)
+        .collect();
+    assert_eq!(indentations, vec!["  ", ""]);
+}
+
+#[cfg(feature = "roblox")]
+#[test]
+fn test_parse_type() {
+    use full_moon::{ast::types::TypeInfo, parse_type};
+
+    let type_info = parse_type("{ [string]: number }").unwrap();
+    match &type_info {
+        TypeInfo::Table { fields, .. } => assert_eq!(fields.len(), 1),
+        other => panic!("expected a table type, got {:?}", other),
+    }
+    assert_eq!(type_info.to_string(), "{ [string]: number }");
+
+    let type_info = parse_type("  (number) -> string\n").unwrap();
+    match &type_info {
+        TypeInfo::Callback {
+            arguments,
+            return_type,
+            ..
+        } => {
+            assert_eq!(arguments.len(), 1);
+            assert_eq!(return_type.to_string(), "string\n");
+        }
+        other => panic!("expected a callback type, got {:?}", other),
+    }
+
+    assert!(parse_type("(number) => string").is_ok());
+    assert!(parse_type("number string").is_err());
+    assert!(parse_type("").is_err());
+    assert!(parse_type("local x = 1").is_err());
+}
+
+#[cfg(feature = "roblox")]
+#[test]
+fn test_named_callback_arguments() {
+    use full_moon::{ast::types::TypeInfo, parse_type};
+
+    let source = "(count: number, string, name : string?) -> ()";
+    let type_info = parse_type(source).unwrap();
+    assert_eq!(type_info.to_string(), source);
+
+    let names: Vec<_> = match &type_info {
+        TypeInfo::Callback { arguments, .. } => arguments
+            .iter()
+            .map(|argument| argument.name().map(ToString::to_string))
+            .collect(),
+        other => panic!("expected a callback type, got {:?}", other),
+    };
+    assert_eq!(
+        names,
+        vec![Some("count".to_owned()), None, Some("name".to_owned())]
+    );
+
+    assert!(parse_type("(count: number)").is_err());
+}
+
+#[test]
+fn test_eval_order() {
+    fn assert_eval_order(code: &str, expected: &[(&str, Option<&str>)]) {
+        let ast = parse(code).unwrap();
+        let evaluations: Vec<_> = return_expression(&ast)
+            .eval_order()
+            .map(|evaluation| {
+                (
+                    evaluation.value().to_string().trim().to_owned(),
+                    evaluation
+                        .short_circuit()
+                        .map(|bin_op| bin_op.to_string().trim().to_owned()),
+                )
+            })
+            .collect();
+
+        let expected: Vec<_> = expected
+            .iter()
+            .map(|(value, short_circuit)| {
+                (
+                    value.to_string(),
+                    short_circuit.map(|bin_op| bin_op.to_owned()),
+                )
+            })
+            .collect();
+
+        assert_eq!(evaluations, expected, "{}", code);
+    }
+
+    assert_eval_order("return f() + g()", &[("f()", None), ("g()", None)]);
+    assert_eval_order(
+        "return a + b * -c",
+        &[("a", None), ("b", None), ("c", None)],
+    );
+    assert_eval_order(
+        "return t[k](x, { y, [z] = w }):m(function() return never() end)",
+        &[
+            ("k", None),
+            ("x", None),
+            ("y", None),
+            ("z", None),
+            ("w", None),
+            ("{ y, [z] = w }", None),
+            ("function() return never() end", None),
+            (
+                "t[k](x, { y, [z] = w }):m(function() return never() end)",
+                None,
+            ),
+        ],
+    );
+    assert_eval_order(
+        "return a and b or f(c)",
+        &[
+            ("a", None),
+            ("b", Some("and")),
+            ("c", Some("or")),
+            ("f(c)", Some("or")),
+        ],
+    );
+    assert_eval_order(
+        "return (a or b) + c",
+        &[
+            ("a", None),
+            ("b", Some("or")),
+            ("(a or b)", None),
+            ("c", None),
+        ],
+    );
+}
+
+#[test]
+fn test_print_last_stmt_semicolons() {
+    for source in &[
+        "do return 1; end",
+        "while true do break; end",
+        "do return; end",
+        "local function f() return f(); end\nreturn f() ;\n",
+    ] {
+        let ast = parse(source).unwrap();
+        assert_eq!(full_moon::print(&ast), *source);
+    }
+
+    let ast = parse("do return 1; end").unwrap();
+    let r#do = match ast.nodes().iter_stmts().next() {
+        Some(ast::Stmt::Do(r#do)) => r#do,
+        _ => unreachable!(),
+    };
+    assert_eq!(r#do.block().to_string(), "return 1; ");
+}
+
+#[test]
+fn test_enclosing_function() {
+    use full_moon::ast::functions::EnclosingFunction;
+
+    let ast = parse(
+        "local a = 1\nfunction outer(x)\n\tlocal function inner(y)\n\t\treturn y\n\tend\n\treturn x\nend\nreturn a",
+    )
+    .unwrap();
+
+    let position = |text: &str, nth: usize| {
+        ast.iter_tokens()
+            .filter(|token| token.to_string() == text)
+            .nth(nth)
+            .unwrap()
+            .start_position()
+    };
+
+    assert_eq!(ast.enclosing_function(position("a", 0)), None);
+    assert_eq!(ast.enclosing_function(position("a", 1)), None);
+
+    match ast.enclosing_function(position("y", 1)) {
+        Some(EnclosingFunction::Local(local_function)) => {
+            assert_eq!(local_function.name().to_string(), "inner")
+        }
+        other => panic!("expected inner, got {:?}", other),
+    }
+
+    match ast.enclosing_function(position("x", 1)) {
+        Some(function @ EnclosingFunction::Declaration(_)) => {
+            assert_eq!(function.body().iter_parameters().count(), 1)
+        }
+        other => panic!("expected outer, got {:?}", other),
+    }
+
+    assert!(ast.enclosing_function(position("outer", 0)).is_some());
+}
+
+#[test]
+fn test_serde_ast() {
+    let source = "local x = 1 -- comment\nreturn x\n";
+    let ast = parse(source).unwrap();
+
+    let serialized = serde_json::to_string(&ast).unwrap();
+    let deserialized: ast::Ast = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(full_moon::print(&deserialized), source);
+
+    let version = format!("\"version\":{}", ast::SERDE_VERSION);
+    assert!(serialized.contains(&version));
+
+    let unknown_version = serialized.replace(&version, "\"version\":999");
+    let error = serde_json::from_str::<ast::Ast>(&unknown_version).unwrap_err();
+    assert!(error.to_string().contains("version 999"), "{}", error);
+
+    // Changes made to the nodes are kept
+    let mut ast = parse("t.insert(t, 1)\n").unwrap();
+    ast.use_method_calls();
+    let serialized = serde_json::to_string(&ast).unwrap();
+    let deserialized: ast::Ast = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(full_moon::print(&deserialized), "t:insert(1)\n");
+}
+
+#[test]
+fn test_collapse_blank_lines() {
+    let mut ast =
+        parse("local x = 1\n\n\n\n\nlocal y = [[\n\n\n\n]]\n  \n\t\n\n\n-- comment\nreturn")
+            .unwrap();
+    ast.collapse_blank_lines();
+    assert_eq!(
+        full_moon::print(&ast),
+        "local x = 1\n\n\nlocal y = [[\n\n\n\n]]\n  \n\t\n-- comment\nreturn"
+    );
+
+    let last = ast.iter_tokens().last().unwrap();
+    assert_eq!(last.start_position().line(), 12);
+
+    let mut ast = parse("\n\n\ncall()\n\n\n").unwrap();
+    ast.collapse_blank_lines_to(0);
+    assert_eq!(full_moon::print(&ast), "call()\n");
+}
+
+#[test]
+fn test_update_positions_offset() {
+    let offset = Position::new(100, 10, 5);
+    let mut ast = full_moon::parse_with_offset("local x = 1\n\n\n\nreturn x", offset).unwrap();
+    ast.collapse_blank_lines_to(0);
+
+    let changed = full_moon::print(&ast);
+    assert_eq!(changed, "local x = 1\nreturn x");
+    let reparsed = full_moon::parse_with_offset(&changed, offset).unwrap();
+
+    let first = ast.iter_tokens().next().unwrap();
+    assert_eq!(first.start_position(), offset);
+    assert_eq!(ast.eof().end_position(), reparsed.eof().end_position());
+}
+
+#[test]
+fn test_inline_block_comment() {
+    let source = "local z = x--[[c]]+y";
+    let ast = parse(source).unwrap();
+    assert_eq!(full_moon::print(&ast), source);
+
+    let (x, binop) = match ast.nodes().iter_stmts().next() {
+        Some(ast::Stmt::LocalAssignment(local_assignment)) => {
+            match local_assignment.expr_list().iter().next() {
+                Some(Expression::Value {
+                    value,
+                    binop: Some(binop),
+                    ..
+                }) => match &**value {
+                    ast::Value::Var(ast::Var::Name(x)) => (x, binop),
+                    _ => unreachable!(),
+                },
+                _ => unreachable!(),
+            }
+        }
+        _ => unreachable!(),
+    };
+
+    let trivia: Vec<_> = x.trailing_trivia().map(ToString::to_string).collect();
+    assert_eq!(trivia, vec!["--[[c]]"]);
+
+    let plus = match binop.bin_op() {
+        BinOp::Plus(plus) => plus,
+        _ => unreachable!(),
+    };
+    assert_eq!(plus.leading_trivia().count(), 0);
+    assert_eq!(plus.start_position().bytes(), 18);
+}
+
+#[test]
+fn test_table_constructor_style() {
+    fn return_table<'a>(ast: &'a ast::Ast<'a>) -> &'a ast::TableConstructor<'a> {
+        match return_expression(ast) {
+            Expression::Value { value, .. } => match &**value {
+                ast::Value::TableConstructor(table) => table,
+                _ => panic!("expected a table"),
+            },
+            _ => panic!("expected a table"),
+        }
+    }
+
+    let ast = parse("return {a=1,b=2}").unwrap();
+    let table = return_table(&ast);
+    assert_eq!(
+        table.to_multi_line("", "\t").to_string(),
+        "{\n\ta = 1,\n\tb = 2,\n}"
+    );
+    assert_eq!(
+        table.to_single_line().unwrap().to_string(),
+        "{ a = 1, b = 2 }"
+    );
+    assert_eq!(
+        table.to_canonical("  ", "  ", 80).to_string(),
+        "{ a = 1, b = 2 }"
+    );
+    assert_eq!(
+        table.to_canonical("  ", "  ", 10).to_string(),
+        "{\n    a = 1,\n    b = 2,\n  }"
+    );
+
+    let ast = parse(
+        "return { -- first\n\tf(x), -- one\n\t[k] --[[key]] = 2;\n\t-- last\n\t3\n} -- after",
+    )
+    .unwrap();
+    let table = return_table(&ast);
+    assert!(table.to_single_line().is_none());
+    assert_eq!(
+        table.to_multi_line("", "  ").to_string(),
+        "{\n  -- first\n  f(x), -- one\n  [k] --[[key]] = 2,\n  -- last\n  3,\n} -- after"
+    );
+
+    let ast = parse("return {}").unwrap();
+    assert_eq!(return_table(&ast).to_multi_line("", "\t").to_string(), "{}");
+}
+
+#[test]
+fn test_parameter_names() {
+    fn body_of(code: &str, test: impl Fn(&ast::FunctionBody)) {
+        let ast = parse(code).unwrap();
+        match return_expression(&ast) {
+            Expression::Value { value, .. } => match &**value {
+                ast::Value::Function((_, body)) => test(body),
+                _ => panic!("expected a function"),
+            },
+            _ => panic!("expected a function"),
+        }
+    }
+
+    body_of("return function(a, b, ...) end", |body| {
+        assert_eq!(body.parameter_names(), vec!["a", "b"]);
+        assert!(body.is_variadic());
+    });
+
+    body_of("return function(a) end", |body| {
+        assert_eq!(body.parameter_names(), vec!["a"]);
+        assert!(!body.is_variadic());
+    });
+
+    body_of("return function() end", |body| {
+        assert!(body.parameter_names().is_empty());
+        assert!(!body.is_variadic());
+    });
+}
+
+#[test]
+fn test_long_concat_chain() {
+    let source = format!("return {}", vec!["a"; 5000].join(" .. "));
+    let ast = parse(&source).unwrap();
+    assert_eq!(full_moon::print(&ast), source);
+}
@@ -0,0 +1 @@
+function a.b:c:d() end
\ No newline at end of file
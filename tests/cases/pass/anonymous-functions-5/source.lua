@@ -0,0 +1,3 @@
+local t = {f = function() end}
+local u = {function() end}
+pcall(function() end)
@@ -0,0 +1,11 @@
+do   end
+do end
+do do  end end
+if x then  end
+while x do
+
+end
+for i = 1, 2 do  end
+repeat  until x
+local f = function()   end
+function g(...) end
@@ -0,0 +1,5 @@
+local a = .5
+local b = 5.
+local c = 5.e2
+local d = 1..2
+local e = 1.5E-3 + 2e+4
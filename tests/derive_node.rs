@@ -30,3 +30,39 @@ fn test_position_min_max() {
     visitor.visit_ast(&ast);
     assert!(visitor.0, "TableConstructor was never found");
 }
+
+#[test]
+fn test_node_children() {
+    let ast = parse("if a then\n\tb()\nelseif c then\nelse\n\td = 1\nend\n").unwrap();
+    let r#if = match ast.nodes().iter_stmts().next() {
+        Some(ast::Stmt::If(r#if)) => r#if,
+        _ => unreachable!(),
+    };
+
+    // `if`, `a`, `then`, the first block, the `elseif`, `else`, the last block, and `end`
+    let children = r#if.children();
+    assert_eq!(children.len(), 8);
+
+    let spans: Vec<_> = children.iter().map(|child| child.span()).collect();
+    assert!(spans.contains(&r#if.condition().span()));
+    assert!(spans.contains(&r#if.block().span()));
+    assert!(spans.contains(&r#if.else_block().span()));
+    assert!(spans.contains(&r#if.else_if().unwrap()[0].span()));
+
+    // Walking down every node's children reaches every token
+    fn count_tokens(node: &dyn Node, count: &mut usize) {
+        let children = node.children();
+        if children.is_empty() && node.span().is_some() {
+            *count += 1;
+        }
+
+        for child in children {
+            count_tokens(child, count);
+        }
+    }
+
+    // `if`, `a`, `then`, `b`, `(`, `)`, `elseif`, `c`, `then`, `else`, `d`, `=`, `1` and `end`
+    let mut count = 0;
+    count_tokens(ast.nodes(), &mut count);
+    assert_eq!(count, 14);
+}
@@ -0,0 +1,6 @@
+local count = 0
+
+:: top ::
+count = count + 1
+
+if count < 3 then goto top end
@@ -0,0 +1,8 @@
+for index = 1, 3 do
+	if index == 2 then
+		goto continue
+	end
+
+	print(index)
+	::continue::
+end
@@ -1,4 +1,9 @@
-use full_moon::{node::Node, parse};
+use full_moon::{
+    node::{Node, Span, TriviaBlind},
+    parse,
+    tokenizer::Position,
+};
+use std::collections::HashSet;
 
 #[test]
 fn surrounding_ignore_tokens() {
@@ -27,3 +32,73 @@ fn test_similar() {
     assert!(stmts[1].similar(stmts[0]));
     assert!(!stmts[0].similar(stmts[2]));
 }
+
+#[test]
+// Tokens can be changed through a shared reference, but aren't while they're in the set
+#[allow(clippy::mutable_key_type)]
+fn test_trivia_blind_hash_set() {
+    let ast =
+        parse("local x = 1 -- one\nlocal  x  =  1\nlocal x = 2\ncall { 1 }\ncall{1}").unwrap();
+    let stmts = ast
+        .nodes()
+        .iter_stmts()
+        .map(TriviaBlind)
+        .collect::<HashSet<_>>();
+
+    assert_eq!(stmts.len(), 3);
+}
+
+#[test]
+fn test_span() {
+    let ast = parse("local x = 1\ncall(x)").unwrap();
+    let stmts = ast.nodes().iter_stmts().collect::<Vec<_>>();
+
+    let local = stmts[0].span().unwrap();
+    let call = stmts[1].span().unwrap();
+    assert_eq!(local.len_bytes(), 11);
+    assert_eq!(call.len_bytes(), 7);
+
+    assert!(local.contains(local.start()));
+    assert!(!local.contains(local.end()));
+    assert!(!local.contains(call.start()));
+    assert!(call.contains(call.start()));
+
+    assert!(!local.overlaps(call));
+    assert!(local.overlaps(local));
+
+    let whole = Span::new(local.start(), call.end());
+    assert!(whole.overlaps(local));
+    assert!(whole.overlaps(call));
+    assert_eq!(whole.len_bytes(), 19);
+}
+
+#[test]
+fn test_selection() {
+    let ast = parse("local x = 1\nlocal y = 2\nlocal z = 3\nreturn x").unwrap();
+    let stmts = ast.nodes().iter_stmts().collect::<Vec<_>>();
+
+    // From the middle of the first statement to the middle of the second
+    let selection = Span::new(
+        Position::new(8, 1, 9),
+        Position::new(stmts[1].start_position().unwrap().bytes() + 3, 2, 4),
+    );
+
+    assert!(stmts[0].intersects(selection));
+    assert!(stmts[1].intersects(selection));
+    assert!(!stmts[2].intersects(selection));
+    assert!(!ast.nodes().last_stmts().intersects(selection));
+
+    let selected = ast
+        .nodes()
+        .stmts_intersecting(selection)
+        .map(|stmt| stmt.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(selected, vec!["local x = 1\n", "local y = 2\n"]);
+
+    let whole = ast.nodes().span().unwrap();
+    assert!(whole.encloses(selection));
+    assert!(whole.encloses(whole));
+    assert!(!selection.encloses(whole));
+    assert_eq!(ast.nodes().stmts_intersecting(whole).count(), 3);
+    assert!(ast.nodes().last_stmts().intersects(whole));
+}
@@ -0,0 +1,9 @@
+@native local function f() end
+
+@native
+@checked
+function foo.bar(x: number)
+	return x
+end
+
+@native function baz() end
@@ -0,0 +1,9 @@
+a += 1
+b -= 2
+c *= 3
+d /= 4
+e %= 5
+f ^= 6
+g ..= "h"
+t.x += y * 2
+t[1] -= -1
@@ -0,0 +1,3 @@
+type Callback = (count: number, name: string) -> ()
+type Mixed = (number, name: string?) -> boolean
+local f: (a: (b: number) -> string) -> ()
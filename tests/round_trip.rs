@@ -0,0 +1,35 @@
+#![cfg(feature = "testing")]
+use std::{fs, path::Path};
+
+fn verify_folder<P: AsRef<Path>>(folder: P) -> usize {
+    let mut count = 0;
+
+    for entry in fs::read_dir(folder).expect("couldn't read directory") {
+        let path = entry.unwrap().path().join("source.lua");
+        let source = fs::read_to_string(&path).expect("couldn't read source.lua");
+
+        if let Err(error) = full_moon::verify_round_trip(&source) {
+            panic!("{:?} didn't round trip: {}", path, error);
+        }
+
+        count += 1;
+    }
+
+    count
+}
+
+#[test]
+#[cfg_attr(feature = "no-source-tests", ignore)]
+fn test_round_trip_corpus() {
+    for path in &["./benches/date.lua", "./benches/t.lua"] {
+        let source = fs::read_to_string(path).expect("couldn't read file");
+        if let Err(error) = full_moon::verify_round_trip(&source) {
+            panic!("{} didn't round trip: {}", path, error);
+        }
+    }
+
+    assert!(verify_folder("./tests/cases/pass") > 0);
+
+    #[cfg(feature = "roblox")]
+    assert!(verify_folder("./tests/roblox_cases/pass") > 0);
+}
@@ -1,6 +1,6 @@
 use full_moon::{
     ast, parse, print, tokenizer,
-    visitors::{Visitor, VisitorMut},
+    visitors::{VisitMut, Visitor, VisitorMut},
 };
 use std::borrow::Cow;
 
@@ -78,6 +78,143 @@ fn test_visitor_mut() {
     PositionValidator.visit_ast(&code);
 }
 
+#[test]
+fn test_visitor_mut_numbers() {
+    struct NumberUppercaser;
+
+    impl<'ast> VisitorMut<'ast> for NumberUppercaser {
+        fn visit_number(&mut self, token: &mut tokenizer::TokenReference<'ast>) {
+            let text = token.to_string().to_uppercase();
+            token.set_token_type(tokenizer::TokenType::Number {
+                text: Cow::from(text),
+            });
+        }
+    }
+
+    let mut code = parse("local x = 0xff + 1e5\nt[0xa] = { 2.5e-3, abc }").unwrap();
+    NumberUppercaser.visit_ast(&mut code);
+    assert_eq!(
+        print(&code),
+        "local x = 0XFF + 1E5\nt[0XA] = { 2.5E-3, abc }"
+    );
+}
+
+#[test]
+fn test_rewrite_strings_keeps_quotes() {
+    // Rewrites the contents of every string, keeping the style of quotes it was written with where it can
+    struct StringRewriter(Vec<&'static str>);
+
+    impl<'ast> VisitorMut<'ast> for StringRewriter {
+        fn visit_string_literal(&mut self, token: &mut tokenizer::TokenReference<'ast>) {
+            let quote_type = match &*token.token_type() {
+                tokenizer::TokenType::StringLiteral { quote_type, .. } => *quote_type,
+                _ => unreachable!(),
+            };
+
+            token.set_token_type(tokenizer::TokenType::string(self.0.remove(0), quote_type));
+        }
+    }
+
+    let mut code = parse(r#"call('a', 'a', "a", [[a]], [[a]])"#).unwrap();
+    // `visit_ast` visits every token of the Ast before visiting its nodes, so only the nodes are visited to see each string once
+    code.nodes_mut().visit_mut(&mut StringRewriter(vec![
+        "b",
+        "it's",
+        "say \"hi\"",
+        "c]]",
+        "\nd",
+    ]));
+    assert_eq!(
+        print(&code),
+        "call('b', \"it's\", 'say \"hi\"', [=[c]]]=], [[\n\nd]])"
+    );
+
+    // Contents with both quotes keep the preferred one and escape it
+    let token = tokenizer::Token::new(tokenizer::TokenType::string(
+        "'\"\\\0",
+        tokenizer::StringLiteralQuoteType::Single,
+    ));
+    assert_eq!(token.to_string(), r#"'\'"\\\000'"#);
+}
+
+#[test]
+fn test_update_positions() {
+    struct Renamer;
+
+    impl<'ast> VisitorMut<'ast> for Renamer {
+        fn visit_identifier(&mut self, token: &mut tokenizer::TokenReference<'ast>) {
+            if token.to_string() == "x" {
+                token.set_token_type(tokenizer::TokenType::Identifier {
+                    identifier: Cow::from("xyz"),
+                });
+            }
+        }
+    }
+
+    fn positions(ast: &ast::Ast) -> Vec<(String, tokenizer::Position, tokenizer::Position)> {
+        ast.iter_tokens()
+            .map(|token| {
+                (
+                    token.to_string(),
+                    token.start_position(),
+                    token.end_position(),
+                )
+            })
+            .collect()
+    }
+
+    let code = "local x = [[a\nb]] --[[c\nd]] print(x)\nlocal y = 1\n";
+    let mut ast = parse(code).unwrap();
+    let before = positions(&ast);
+
+    Renamer.visit_ast(&mut ast);
+    ast.update_positions();
+    let after = positions(&ast);
+
+    // The positions are the same as if the changed code was parsed
+    let changed = print(&ast);
+    assert_eq!(
+        changed,
+        "local xyz = [[a\nb]] --[[c\nd]] print(xyz)\nlocal y = 1\n"
+    );
+    assert_eq!(after, positions(&parse(&changed).unwrap()));
+
+    let find = |tokens: &[(String, tokenizer::Position, tokenizer::Position)], text: &str| {
+        tokens
+            .iter()
+            .find(|(token, _, _)| token == text)
+            .map(|(_, start, end)| (*start, *end))
+            .unwrap()
+    };
+
+    // The first `x` is 2 characters longer, so everything after it on the same line moves along by 2,
+    // but things on later lines only move by 2 bytes
+    let (string_start, _) = find(&before, "[[a\nb]]");
+    let (new_string_start, _) = find(&after, "[[a\nb]]");
+    assert_eq!(new_string_start.bytes(), string_start.bytes() + 2);
+    assert_eq!(new_string_start.line(), string_start.line());
+    assert_eq!(new_string_start.character(), string_start.character() + 2);
+
+    let (_, comment_end) = find(&before, "--[[c\nd]]");
+    let (_, new_comment_end) = find(&after, "--[[c\nd]]");
+    assert_eq!(new_comment_end.bytes(), comment_end.bytes() + 2);
+    assert_eq!(new_comment_end.line(), 3);
+    assert_eq!(new_comment_end.character(), comment_end.character());
+
+    // The second `x` is after the multi-line string and comment, on the same line as the end of the comment
+    let (_, print_end) = find(&before, "print");
+    let (_, new_print_end) = find(&after, "print");
+    assert_eq!(new_print_end.bytes(), print_end.bytes() + 2);
+    assert_eq!(new_print_end.line(), 3);
+    assert_eq!(new_print_end.character(), print_end.character());
+
+    let (_, y_end) = find(&before, "y");
+    let (_, new_y_end) = find(&after, "y");
+    assert_eq!(new_y_end.bytes(), y_end.bytes() + 4);
+    assert_eq!(new_y_end.line(), 4);
+    assert_eq!(new_y_end.character(), y_end.character());
+}
+
 #[test]
 fn test_visit_token() {
     #[derive(Default)]